@@ -0,0 +1,68 @@
+//! Compares decoding a million MT5 ticks from the numpy buffer against extracting the
+//! same ticks from the list of dicts the former pandas round-trip produced.
+//!
+//! Run with `cargo bench --bench structured_array`. The dict baseline leaves out the
+//! cost of building the DataFrame and calling `to_dict`, so the real speedup is larger.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use fishing_line::connection::numpy::from_structured_array;
+use fishing_line::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+const TICKS: usize = 1_000_000;
+
+const SETUP: &str = r#"
+import struct, types
+
+def scalar(kind, size):
+    return types.SimpleNamespace(kind=kind, itemsize=size, byteorder="<")
+
+class Array(bytes):
+    dtype = types.SimpleNamespace(
+        fields={
+            "time": (scalar("i", 8), 0),
+            "bid": (scalar("f", 8), 8),
+            "ask": (scalar("f", 8), 16),
+            "last": (scalar("f", 8), 24),
+            "volume": (scalar("u", 8), 32),
+            "time_msc": (scalar("i", 8), 40),
+            "flags": (scalar("u", 4), 48),
+            "volume_real": (scalar("f", 8), 52),
+        },
+        itemsize=60,
+    )
+
+record = struct.Struct("<qdddQqId")
+array = Array(b"".join(
+    record.pack(1720447200 + i // 1000, 1.1, 1.2, 0.0, 0, 1720447200000 + i, 6, 0.0)
+    for i in range(count)
+))
+records = [
+    dict(zip(("time", "bid", "ask", "last", "volume", "time_msc", "flags", "volume_real"), values))
+    for values in record.iter_unpack(array)
+]
+"#;
+
+fn ticks(c: &mut Criterion) {
+    Python::with_gil(|py| {
+        let globals = PyDict::new_bound(py);
+        globals.set_item("count", TICKS).unwrap();
+        py.run_bound(SETUP, Some(&globals), None).unwrap();
+        let array = globals.get_item("array").unwrap().unwrap();
+        let records = globals.get_item("records").unwrap().unwrap();
+
+        let mut group = c.benchmark_group("copy_ticks_1m");
+        group.sample_size(10);
+        group.bench_function("buffer", |b| {
+            b.iter(|| from_structured_array::<SymbolTick>(&array).unwrap())
+        });
+        group.bench_function("dict_records", |b| {
+            b.iter(|| records.extract::<Vec<SymbolTick>>().unwrap())
+        });
+        group.finish();
+    });
+}
+
+criterion_group!(benches, ticks);
+criterion_main!(benches);
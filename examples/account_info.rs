@@ -3,7 +3,8 @@ use fishing_line::prelude::*;
 fn main() {
     let terminal_path = std::env::var("TERMINAL_PATH").expect("TERMINAL_PATH must be set");
 
-    let runtime = MT5PythonConnection::new().initialize(&terminal_path);
+    let runtime =
+        MT5PythonConnection::new().and_then(|connection| connection.initialize(&terminal_path));
 
     if runtime.is_err() {
         panic!("Failed to initialize and connect to MT5 terminal");
@@ -24,9 +25,7 @@ fn main() {
     runtime
         .login(account_credentials, None)
         .unwrap_or_else(|err| {
-            let code = err.0;
-            let message = err.1;
-            panic!("Failed to login: {:?} - {}", code, message);
+            panic!("Failed to login: {}", err);
         });
 
     let account_info = runtime.account_info();
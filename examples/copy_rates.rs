@@ -4,7 +4,8 @@ use fishing_line::prelude::*;
 fn main() {
     let terminal_path = std::env::var("TERMINAL_PATH").expect("TERMINAL_PATH must be set");
 
-    let runtime = MT5PythonConnection::new().initialize(&terminal_path);
+    let runtime =
+        MT5PythonConnection::new().and_then(|connection| connection.initialize(&terminal_path));
 
     if runtime.is_err() {
         panic!("Failed to initialize and connect to MT5 terminal");
@@ -13,7 +14,7 @@ fn main() {
     let runtime = runtime.unwrap();
 
     let copy_rates_from = runtime
-        .copy_rates_from("EURUSD", Timeframe::D1, Local::now(), 10)
+        .copy_rates_from("EURUSD", Timeframe::D1, Local::now().to_utc(), 10)
         .expect("Unable to get symbol information");
 
     println!("Copy rates from: ");
@@ -40,8 +41,11 @@ fn main() {
         .copy_rates_range(
             "EURUSD",
             Timeframe::D1,
-            Local.with_ymd_and_hms(2024, 7, 8, 0, 0, 0).unwrap(),
-            Local::now(),
+            Local
+                .with_ymd_and_hms(2024, 7, 8, 0, 0, 0)
+                .unwrap()
+                .to_utc(),
+            Local::now().to_utc(),
         )
         .expect("Unable to get symbol information");
 
@@ -3,7 +3,8 @@ use fishing_line::prelude::*;
 fn main() {
     let terminal_path = std::env::var("TERMINAL_PATH").expect("TERMINAL_PATH must be set");
 
-    let runtime = MT5PythonConnection::new().initialize(&terminal_path);
+    let runtime =
+        MT5PythonConnection::new().and_then(|connection| connection.initialize(&terminal_path));
 
     if runtime.is_err() {
         panic!("Failed to initialize and connect to MT5 terminal");
@@ -0,0 +1,165 @@
+"""JSON-RPC worker serving the MetaTrader5 module to fishing-line's `MT5BridgeConnection`.
+
+Requests and responses are JSON-RPC 2.0 objects, one per line:
+
+    python bridge_worker.py                       # serve a single client on stdin/stdout
+    python bridge_worker.py --listen 0.0.0.0:8765  # serve TCP clients one after the other
+
+See the `fishing_line::connection::bridge` documentation for the protocol.
+"""
+
+import argparse
+import datetime
+import json
+import socket
+import sys
+import traceback
+
+METHODS = {
+    "initialize",
+    "login",
+    "shutdown",
+    "version",
+    "last_error",
+    "account_info",
+    "terminal_info",
+    "symbols_total",
+    "symbols_get",
+    "symbol_info",
+    "symbol_info_tick",
+    "symbol_select",
+    "market_book_add",
+    "market_book_get",
+    "market_book_release",
+    "copy_rates_from",
+    "copy_rates_from_pos",
+    "copy_rates_range",
+    "copy_ticks_from",
+    "copy_ticks_range",
+    "orders_total",
+    "orders_get",
+    "order_calc_margin",
+    "order_calc_profit",
+    "order_check",
+    "order_send",
+    "positions_total",
+    "positions_get",
+    "history_orders_total",
+    "history_orders_get",
+    "history_deals_total",
+    "history_deals_get",
+}
+
+# Methods whose result is returned as is, without the `last_error` and `None` checks.
+UNCHECKED = {"last_error", "shutdown"}
+
+PARSE_ERROR = -32700
+INVALID_REQUEST = -32600
+METHOD_NOT_FOUND = -32601
+PYTHON_ERROR = -32000
+NO_DATA = -1
+
+
+def to_json(value):
+    """Converts named tuples, numpy arrays and scalars into JSON values."""
+    if value is None or isinstance(value, (bool, int, float, str)):
+        return value
+    if hasattr(value, "_asdict"):
+        return {key: to_json(item) for key, item in value._asdict().items()}
+    if hasattr(value, "dtype"):
+        names = value.dtype.names
+        if names and getattr(value, "ndim", 0) == 1:
+            return [dict(zip(names, to_json(row.tolist()))) for row in value]
+        return to_json(value.tolist())
+    if isinstance(value, (tuple, list)):
+        return [to_json(item) for item in value]
+    if isinstance(value, dict):
+        return {str(key): to_json(item) for key, item in value.items()}
+    if isinstance(value, datetime.datetime):
+        return int(value.timestamp())
+    if isinstance(value, bytes):
+        return value.decode(errors="replace")
+    return str(value)
+
+
+def from_json(value):
+    """Turns `{"$datetime": seconds}` arguments, with fractional milliseconds, back into UTC datetimes."""
+    if isinstance(value, dict):
+        if list(value) == ["$datetime"]:
+            return datetime.datetime.fromtimestamp(value["$datetime"], tz=datetime.timezone.utc)
+        return {key: from_json(item) for key, item in value.items()}
+    if isinstance(value, list):
+        return [from_json(item) for item in value]
+    return value
+
+
+def error(request_id, code, message, data=None):
+    response = {"jsonrpc": "2.0", "id": request_id, "error": {"code": code, "message": message}}
+    if data is not None:
+        response["error"]["data"] = data
+    return response
+
+
+def handle(mt5, line):
+    try:
+        request = json.loads(line)
+    except ValueError as exception:
+        return error(None, PARSE_ERROR, "Parse error: {}".format(exception))
+    if not isinstance(request, dict):
+        return error(None, INVALID_REQUEST, "Invalid request")
+    request_id = request.get("id")
+    method = request.get("method")
+    params = request.get("params") or {}
+    if not isinstance(method, str) or not isinstance(params, dict):
+        return error(request_id, INVALID_REQUEST, "Invalid request")
+    if method not in METHODS:
+        return error(request_id, METHOD_NOT_FOUND, "Method not found: {}".format(method))
+
+    try:
+        args = from_json(params.get("args", []))
+        kwargs = from_json(params.get("kwargs", {}))
+        result = getattr(mt5, method)(*args, **kwargs)
+        if method not in UNCHECKED:
+            code, message = mt5.last_error()
+            if code < 0:
+                return error(request_id, code, message)
+            if result is None:
+                return error(request_id, NO_DATA, "`{}` returned no data".format(method))
+        return {"jsonrpc": "2.0", "id": request_id, "result": to_json(result)}
+    except Exception as exception:
+        return error(request_id, PYTHON_ERROR, str(exception), {"traceback": traceback.format_exc()})
+
+
+def serve(mt5, reader, writer):
+    for line in reader:
+        if not line.strip():
+            continue
+        writer.write(json.dumps(handle(mt5, line)) + "\n")
+        writer.flush()
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--listen", metavar="HOST:PORT", help="serve over TCP instead of stdio")
+    options = parser.parse_args()
+
+    # The protocol owns stdout, anything printed by the MetaTrader5 module goes to stderr.
+    protocol = sys.stdout
+    sys.stdout = sys.stderr
+
+    import MetaTrader5 as mt5
+
+    if options.listen is None:
+        serve(mt5, sys.stdin, protocol)
+        return
+
+    host, _, port = options.listen.rpartition(":")
+    with socket.create_server((host, int(port))) as server:
+        while True:
+            connection, _ = server.accept()
+            with connection, connection.makefile("r") as reader, connection.makefile("w") as writer:
+                serve(mt5, reader, writer)
+
+
+if __name__ == "__main__":
+    main()
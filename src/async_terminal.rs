@@ -0,0 +1,467 @@
+//! Async access to a [`Terminal`] for executor-based services.
+//!
+//! Every backend blocks, the Python one inside `Python::with_gil`. [`AsyncTerminal`]
+//! moves the terminal onto a dedicated worker thread and hands calls to it, so
+//! awaiting a call never stalls the executor. It only depends on `futures`
+//! channels and works with any executor.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+
+use chrono::{DateTime, Utc};
+use futures_channel::oneshot;
+
+use crate::prelude::*;
+
+type Job = Box<dyn FnOnce(&dyn Terminal) + Send>;
+
+fn stopped() -> FishingLineError {
+    FishingLineError::Environment("terminal worker thread has stopped".to_string())
+}
+
+/// A [`Terminal`] owned by a worker thread and driven through `async` methods.
+///
+/// Calls run one at a time, in the order they were made. Dropping the
+/// `AsyncTerminal` does not wait for the worker thread: it finishes the calls
+/// already sent, then drops the terminal in the background. Await
+/// [`AsyncTerminal::shutdown`] to wait for that instead.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// # async fn run() -> MQLResult<()> {
+/// let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+/// let terminal = AsyncTerminal::spawn(move || {
+///     MT5PythonConnection::new()?.initialize(terminal_path.as_str())
+/// })?;
+/// let tick = terminal.symbol_info_tick("EURUSD").await?;
+/// println!("spread: {}", tick.ask - tick.bid);
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncTerminal {
+    jobs: mpsc::Sender<Job>,
+    finished: oneshot::Receiver<()>,
+}
+
+impl AsyncTerminal {
+    /// Moves `terminal` onto a new worker thread.
+    pub fn new<T: Terminal + Send + 'static>(terminal: T) -> MQLResult<Self> {
+        AsyncTerminal::spawn(move || Ok(terminal))
+    }
+
+    /// Builds the terminal on a new worker thread, which then keeps it for its whole
+    /// life. Blocks until `connect` returns.
+    pub fn spawn<T, F>(connect: F) -> MQLResult<Self>
+    where
+        T: Terminal + 'static,
+        F: FnOnce() -> MQLResult<T> + Send + 'static,
+    {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let (connected, connection) = mpsc::channel();
+        let (done, finished) = oneshot::channel();
+        thread::Builder::new()
+            .name("fishing-line-terminal".to_string())
+            .spawn(move || {
+                let terminal = match connect() {
+                    Ok(terminal) => {
+                        let _ = connected.send(Ok(()));
+                        terminal
+                    }
+                    Err(error) => {
+                        let _ = connected.send(Err(error));
+                        return;
+                    }
+                };
+                for job in receiver {
+                    job(&terminal);
+                }
+                drop(terminal);
+                let _ = done.send(());
+            })
+            .map_err(|error| {
+                FishingLineError::Environment(format!(
+                    "Unable to spawn terminal worker thread: {}",
+                    error
+                ))
+            })?;
+        connection.recv().map_err(|_| stopped())??;
+        Ok(AsyncTerminal { jobs, finished })
+    }
+
+    /// Stops the worker thread once the calls already sent have run, and waits for it
+    /// to drop the terminal.
+    pub async fn shutdown(self) {
+        let AsyncTerminal { jobs, finished } = self;
+        drop(jobs);
+        let _ = finished.await;
+    }
+
+    /// Runs `call` on the worker thread and waits for its result.
+    ///
+    /// This reaches every method of the blocking traits, including the ones without
+    /// an `async` shortcut below. A panicking call is reported as an error and leaves
+    /// the worker running.
+    pub async fn run<R, F>(&self, call: F) -> MQLResult<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&dyn Terminal) -> MQLResult<R> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move |terminal| {
+            let result = catch_unwind(AssertUnwindSafe(|| call(terminal))).unwrap_or_else(|_| {
+                Err(FishingLineError::Environment(
+                    "terminal call panicked".to_string(),
+                ))
+            });
+            let _ = sender.send(result);
+        });
+        self.jobs.send(job).map_err(|_| stopped())?;
+        receiver.await.map_err(|_| stopped())?
+    }
+
+    pub async fn last_error(&self) -> MQLResult<(RuntimeError, String)> {
+        self.run(|terminal| terminal.last_error()).await
+    }
+
+    pub async fn account_info(&self) -> MQLResult<AccountInfo> {
+        self.run(|terminal| terminal.account_info()).await
+    }
+
+    pub async fn terminal_info(&self) -> MQLResult<TerminalInfo> {
+        self.run(|terminal| terminal.terminal_info()).await
+    }
+
+    pub async fn version(&self) -> MQLResult<TerminalVersion> {
+        self.run(|terminal| terminal.version()).await
+    }
+
+    pub async fn symbols_total(&self) -> MQLResult<i32> {
+        self.run(|terminal| terminal.symbols_total()).await
+    }
+
+    pub async fn symbols_get(&self, group: Option<&str>) -> MQLResult<Vec<SymbolInfo>> {
+        let group = group.map(str::to_string);
+        self.run(move |terminal| terminal.symbols_get(group.as_deref()))
+            .await
+    }
+
+    pub async fn symbol_info(&self, symbol: &str) -> MQLResult<SymbolInfo> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.symbol_info(&symbol))
+            .await
+    }
+
+    pub async fn symbol_info_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.symbol_info_tick(&symbol))
+            .await
+    }
+
+    pub async fn symbol_select(&self, symbol: &str, enable: Option<bool>) -> MQLResult<bool> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.symbol_select(&symbol, enable))
+            .await
+    }
+
+    pub async fn copy_rates_from(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.copy_rates_from(&symbol, timeframe, date_from, count))
+            .await
+    }
+
+    pub async fn copy_rates_from_pos(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.copy_rates_from_pos(&symbol, timeframe, start_pos, count))
+            .await
+    }
+
+    pub async fn copy_rates_range(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.copy_rates_range(&symbol, timeframe, date_from, date_to))
+            .await
+    }
+
+    pub async fn copy_ticks_from(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.copy_ticks_from(&symbol, date_from, count, flags))
+            .await
+    }
+
+    pub async fn copy_ticks_range(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.copy_ticks_range(&symbol, date_from, date_to, flags))
+            .await
+    }
+
+    pub async fn orders_total(&self) -> MQLResult<i64> {
+        self.run(|terminal| terminal.orders_total()).await
+    }
+
+    pub async fn orders_get(&self) -> MQLResult<Vec<Order>> {
+        self.run(|terminal| terminal.orders_get()).await
+    }
+
+    pub async fn orders_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Order>> {
+        let filter = filter.clone();
+        self.run(move |terminal| terminal.orders_get_filtered(&filter))
+            .await
+    }
+
+    pub async fn orders_total_filtered(&self, filter: &TradeFilter) -> MQLResult<i64> {
+        let filter = filter.clone();
+        self.run(move |terminal| terminal.orders_total_filtered(&filter))
+            .await
+    }
+
+    pub async fn order_calc_margin(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+    ) -> MQLResult<f64> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| terminal.order_calc_margin(action, &symbol, volume, price))
+            .await
+    }
+
+    pub async fn order_calc_profit(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price_open: f64,
+        price_close: f64,
+    ) -> MQLResult<f64> {
+        let symbol = symbol.to_string();
+        self.run(move |terminal| {
+            terminal.order_calc_profit(action, &symbol, volume, price_open, price_close)
+        })
+        .await
+    }
+
+    pub async fn order_check(&self, request: &TradeRequestBuilder) -> MQLResult<CheckResult> {
+        let request = request.clone();
+        self.run(move |terminal| terminal.order_check(&request))
+            .await
+    }
+
+    pub async fn order_send(&self, request: TradeRequestBuilder) -> MQLResult<TradeResult> {
+        self.run(move |terminal| terminal.order_send(request)).await
+    }
+
+    pub async fn positions_total(&self) -> MQLResult<i64> {
+        self.run(|terminal| terminal.positions_total()).await
+    }
+
+    pub async fn positions_get(&self) -> MQLResult<Vec<Position>> {
+        self.run(|terminal| terminal.positions_get()).await
+    }
+
+    pub async fn positions_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Position>> {
+        let filter = filter.clone();
+        self.run(move |terminal| terminal.positions_get_filtered(&filter))
+            .await
+    }
+
+    pub async fn positions_total_filtered(&self, filter: &TradeFilter) -> MQLResult<i64> {
+        let filter = filter.clone();
+        self.run(move |terminal| terminal.positions_total_filtered(&filter))
+            .await
+    }
+
+    pub async fn history_orders_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.run(move |terminal| terminal.history_orders_total(date_from, date_to))
+            .await
+    }
+
+    pub async fn history_orders_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Order>> {
+        self.run(move |terminal| terminal.history_orders_get(date_from, date_to))
+            .await
+    }
+
+    pub async fn history_deals_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.run(move |terminal| terminal.history_deals_total(date_from, date_to))
+            .await
+    }
+
+    pub async fn history_deals_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Deals>> {
+        self.run(move |terminal| terminal.history_deals_get(date_from, date_to))
+            .await
+    }
+
+    pub async fn history_orders_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Order>> {
+        let query = query.clone();
+        self.run(move |terminal| terminal.history_orders_query(&query))
+            .await
+    }
+
+    pub async fn history_deals_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Deals>> {
+        let query = query.clone();
+        self.run(move |terminal| terminal.history_deals_query(&query))
+            .await
+    }
+
+    pub async fn history_order(&self, ticket: usize) -> MQLResult<Option<Order>> {
+        self.run(move |terminal| terminal.history_order(ticket))
+            .await
+    }
+
+    pub async fn history_position(&self, position: usize) -> MQLResult<PositionHistory> {
+        self.run(move |terminal| terminal.history_position(position))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    use chrono::{DateTime, TimeDelta};
+    use futures_executor::block_on;
+    use futures_util::future::join;
+    use futures_util::FutureExt;
+
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_calls_run_on_worker_thread() {
+        let terminal = AsyncTerminal::new(MockTerminal::new().with_symbol(eurusd())).unwrap();
+        let (tick, thread) = block_on(join(
+            terminal.symbol_info_tick("EURUSD"),
+            terminal.run(|_| Ok(std::thread::current().name().map(str::to_string))),
+        ));
+        assert_eq!(tick.unwrap().ask, 1.1002);
+        assert_eq!(thread.unwrap().as_deref(), Some("fishing-line-terminal"));
+
+        let error = block_on(terminal.symbol_info("XAUUSD")).unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+    }
+
+    #[test]
+    fn test_panicking_call_keeps_worker() {
+        let terminal = AsyncTerminal::new(MockTerminal::new().with_symbol(eurusd())).unwrap();
+        let panicked = block_on(terminal.run(|_| -> MQLResult<()> { panic!("boom") }));
+        assert!(panicked.is_err(), "Panic was not reported");
+        assert_eq!(block_on(terminal.symbols_total()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_history_totals() {
+        let time = DateTime::from_timestamp(1720447200, 0).unwrap();
+        let terminal = AsyncTerminal::new(
+            MockTerminal::new()
+                .with_history_order(Order {
+                    ticket: 3,
+                    time_setup: time,
+                    ..Default::default()
+                })
+                .with_deal(Deals {
+                    ticket: 4,
+                    time,
+                    ..Default::default()
+                }),
+        )
+        .unwrap();
+        let (from, to) = (time - TimeDelta::hours(1), time + TimeDelta::hours(1));
+        assert_eq!(
+            block_on(terminal.history_orders_total(from, to)).unwrap(),
+            1
+        );
+        assert_eq!(block_on(terminal.history_deals_total(from, to)).unwrap(), 1);
+        assert_eq!(
+            block_on(terminal.history_deals_total(to, to + TimeDelta::hours(1))).unwrap(),
+            0
+        );
+        assert!(block_on(terminal.history_order(3)).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_spawn_reports_connect_error() {
+        let result = AsyncTerminal::spawn(|| -> MQLResult<MockTerminal> {
+            Err(FishingLineError::Environment("no terminal".to_string()))
+        });
+        assert!(result.is_err(), "Connect error was swallowed");
+    }
+
+    #[test]
+    fn test_drop_does_not_wait_for_worker() {
+        let terminal = AsyncTerminal::new(MockTerminal::new()).unwrap();
+        let (release, blocked) = mpsc::channel::<()>();
+        let call = terminal.run(move |_| {
+            blocked.recv().ok();
+            Ok(())
+        });
+        assert!(call.now_or_never().is_none());
+
+        drop(terminal);
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn test_shutdown_waits_for_sent_calls() {
+        let terminal = AsyncTerminal::new(MockTerminal::new()).unwrap();
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = ran.clone();
+        let call = terminal.run(move |_| {
+            std::thread::sleep(Duration::from_millis(50));
+            flag.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        assert!(call.now_or_never().is_none());
+
+        block_on(terminal.shutdown());
+        assert!(ran.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,379 @@
+//! Arrow record batches built straight from the numpy arrays returned by MetaTrader5.
+//!
+//! Every column is filled from the array's buffer in a single pass, without building
+//! a [`SymbolRates`] or [`SymbolTick`] per row. Times are normalized to UTC with the
+//! connection's [`ServerTimezone`].
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, Float64Array, Int32Array, RecordBatch, TimestampMillisecondArray,
+    TimestampSecondArray, UInt32Array, UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use pyo3::prelude::*;
+
+use crate::connection::numpy::StructuredArray;
+use crate::prelude::*;
+
+fn timestamp(unit: TimeUnit) -> DataType {
+    DataType::Timestamp(unit, Some("UTC".into()))
+}
+
+/// Schema of the batches returned by [`SymbolRatesArrowTrait`].
+pub fn rates_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("time", timestamp(TimeUnit::Second), false),
+        Field::new("open", DataType::Float64, false),
+        Field::new("high", DataType::Float64, false),
+        Field::new("low", DataType::Float64, false),
+        Field::new("close", DataType::Float64, false),
+        Field::new("tick_volume", DataType::UInt64, false),
+        Field::new("spread", DataType::Int32, false),
+        Field::new("real_volume", DataType::UInt64, false),
+    ]))
+}
+
+/// Schema of the batches returned by [`SymbolTicksArrowTrait`].
+pub fn ticks_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("time", timestamp(TimeUnit::Second), false),
+        Field::new("bid", DataType::Float64, false),
+        Field::new("ask", DataType::Float64, false),
+        Field::new("last", DataType::Float64, false),
+        Field::new("volume", DataType::UInt64, false),
+        Field::new("time_msc", timestamp(TimeUnit::Millisecond), false),
+        Field::new("flags", DataType::UInt32, false),
+        Field::new("volume_real", DataType::Float64, false),
+    ]))
+}
+
+fn batch_error(error: ArrowError) -> FishingLineError {
+    FishingLineError::Conversion {
+        field: "RecordBatch".to_string(),
+        message: error.to_string(),
+    }
+}
+
+/// Reads one column of `array`, converting every record with `read`.
+fn column<T>(
+    array: &StructuredArray<'_>,
+    name: &str,
+    read: impl Fn(&crate::connection::numpy::Field, &[u8]) -> T,
+) -> MQLResult<Vec<T>> {
+    let field = array.field(name)?;
+    Ok(array.records().map(|record| read(&field, record)).collect())
+}
+
+fn seconds(array: &StructuredArray<'_>, timezone: &ServerTimezone) -> MQLResult<ArrayRef> {
+    let values = column(array, "time", |field, record| {
+        timezone.seconds_to_utc(field.i64(record))
+    })?;
+    Ok(Arc::new(
+        TimestampSecondArray::from(values).with_timezone("UTC"),
+    ))
+}
+
+fn floats(array: &StructuredArray<'_>, name: &str) -> MQLResult<ArrayRef> {
+    Ok(Arc::new(Float64Array::from(column(
+        array,
+        name,
+        |field, record| field.f64(record),
+    )?)))
+}
+
+fn unsigned(array: &StructuredArray<'_>, name: &str) -> MQLResult<ArrayRef> {
+    Ok(Arc::new(UInt64Array::from(column(
+        array,
+        name,
+        |field, record| field.i64(record) as u64,
+    )?)))
+}
+
+/// Builds a rates batch from the array returned by a `copy_rates_*` call.
+pub(crate) fn rates_batch(
+    array: &Bound<'_, PyAny>,
+    timezone: &ServerTimezone,
+) -> MQLResult<RecordBatch> {
+    let array = StructuredArray::new(array, "SymbolRates")?;
+    let spread = column(&array, "spread", |field, record| field.i64(record) as i32)?;
+    RecordBatch::try_new(
+        rates_schema(),
+        vec![
+            seconds(&array, timezone)?,
+            floats(&array, "open")?,
+            floats(&array, "high")?,
+            floats(&array, "low")?,
+            floats(&array, "close")?,
+            unsigned(&array, "tick_volume")?,
+            Arc::new(Int32Array::from(spread)),
+            unsigned(&array, "real_volume")?,
+        ],
+    )
+    .map_err(batch_error)
+}
+
+/// Builds a ticks batch from the array returned by a `copy_ticks_*` call.
+pub(crate) fn ticks_batch(
+    array: &Bound<'_, PyAny>,
+    timezone: &ServerTimezone,
+) -> MQLResult<RecordBatch> {
+    let array = StructuredArray::new(array, "SymbolTick")?;
+    let time_msc = column(&array, "time_msc", |field, record| {
+        timezone.millis_to_utc(field.i64(record))
+    })?;
+    let flags = column(&array, "flags", |field, record| field.i64(record) as u32)?;
+    RecordBatch::try_new(
+        ticks_schema(),
+        vec![
+            seconds(&array, timezone)?,
+            floats(&array, "bid")?,
+            floats(&array, "ask")?,
+            floats(&array, "last")?,
+            unsigned(&array, "volume")?,
+            Arc::new(TimestampMillisecondArray::from(time_msc).with_timezone("UTC")),
+            Arc::new(UInt32Array::from(flags)),
+            floats(&array, "volume_real")?,
+        ],
+    )
+    .map_err(batch_error)
+}
+
+/// Builds a rates batch from records that were already decoded and normalized to UTC.
+pub(crate) fn rates_batch_from_records(rates: &[SymbolRates]) -> MQLResult<RecordBatch> {
+    let floats = |read: fn(&SymbolRates) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(rates.iter().map(read)))
+    };
+    let unsigned = |read: fn(&SymbolRates) -> isize| -> ArrayRef {
+        Arc::new(UInt64Array::from_iter_values(
+            rates.iter().map(|rate| read(rate) as u64),
+        ))
+    };
+    RecordBatch::try_new(
+        rates_schema(),
+        vec![
+            Arc::new(
+                TimestampSecondArray::from_iter_values(
+                    rates.iter().map(|rate| rate.time.timestamp()),
+                )
+                .with_timezone("UTC"),
+            ),
+            floats(|rate| rate.open),
+            floats(|rate| rate.high),
+            floats(|rate| rate.low),
+            floats(|rate| rate.close),
+            unsigned(|rate| rate.tick_volume),
+            Arc::new(Int32Array::from_iter_values(
+                rates.iter().map(|rate| rate.spread as i32),
+            )),
+            unsigned(|rate| rate.real_volume),
+        ],
+    )
+    .map_err(batch_error)
+}
+
+/// Builds a ticks batch from records that were already decoded and normalized to UTC.
+pub(crate) fn ticks_batch_from_records(ticks: &[SymbolTick]) -> MQLResult<RecordBatch> {
+    let floats = |read: fn(&SymbolTick) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(ticks.iter().map(read)))
+    };
+    RecordBatch::try_new(
+        ticks_schema(),
+        vec![
+            Arc::new(
+                TimestampSecondArray::from_iter_values(ticks.iter().map(|tick| tick.time))
+                    .with_timezone("UTC"),
+            ),
+            floats(|tick| tick.bid),
+            floats(|tick| tick.ask),
+            floats(|tick| tick.last),
+            Arc::new(UInt64Array::from_iter_values(
+                ticks.iter().map(|tick| tick.volume as u64),
+            )),
+            Arc::new(
+                TimestampMillisecondArray::from_iter_values(ticks.iter().map(|tick| tick.time_msc))
+                    .with_timezone("UTC"),
+            ),
+            Arc::new(UInt32Array::from_iter_values(
+                ticks.iter().map(|tick| tick.flags as u32),
+            )),
+            floats(|tick| tick.volume_real),
+        ],
+    )
+    .map_err(batch_error)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        Array, Float64Array, Int32Array, RecordBatch, TimestampMillisecondArray,
+        TimestampSecondArray, UInt32Array, UInt64Array,
+    };
+    use chrono::{DateTime, FixedOffset};
+    use pyo3::{prelude::*, types::PyDict};
+
+    use super::{rates_batch, rates_batch_from_records, ticks_batch, ticks_batch_from_records};
+    use crate::connection::numpy::test::{tick_array, TICK_FIELDS};
+    use crate::prelude::*;
+
+    /// Two bars laid out like MT5's rates array.
+    fn rate_array(py: Python<'_>) -> Bound<'_, PyAny> {
+        let globals = PyDict::new_bound(py);
+        py.run_bound(
+            r#"
+import struct, types
+
+def scalar(kind, size):
+    return types.SimpleNamespace(kind=kind, itemsize=size, byteorder="<")
+
+class Array(bytes):
+    dtype = types.SimpleNamespace(
+        fields={
+            "time": (scalar("i", 8), 0),
+            "open": (scalar("f", 8), 8),
+            "high": (scalar("f", 8), 16),
+            "low": (scalar("f", 8), 24),
+            "close": (scalar("f", 8), 32),
+            "tick_volume": (scalar("u", 8), 40),
+            "spread": (scalar("i", 4), 48),
+            "real_volume": (scalar("u", 8), 52),
+        },
+        itemsize=60,
+    )
+
+array = Array(
+    struct.pack("<qddddQiQ", 1720447200, 1.1, 1.3, 1.0, 1.2, 40, 2, 0)
+    + struct.pack("<qddddQiQ", 1720450800, 1.2, 1.4, 1.1, 1.3, 55, 3, 7)
+)
+"#,
+            Some(&globals),
+            None,
+        )
+        .unwrap();
+        globals.get_item("array").unwrap().unwrap()
+    }
+
+    fn column<'a, T: Array + 'static>(batch: &'a RecordBatch, name: &str) -> &'a T {
+        batch
+            .column_by_name(name)
+            .and_then(|column| column.as_any().downcast_ref::<T>())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ticks_batch_columns() {
+        let timezone = ServerTimezone::Fixed(FixedOffset::east_opt(3 * 3600).unwrap());
+        let batch =
+            Python::with_gil(|py| ticks_batch(&tick_array(py, TICK_FIELDS), &timezone)).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), super::ticks_schema());
+
+        assert_eq!(column::<Float64Array>(&batch, "bid").values(), &[1.1, 1.3]);
+        assert_eq!(
+            column::<TimestampMillisecondArray>(&batch, "time_msc").value(1),
+            1720447201500 - 3 * 3600 * 1000
+        );
+        assert_eq!(column::<UInt32Array>(&batch, "flags").values(), &[6, 2]);
+    }
+
+    #[test]
+    fn test_rates_batch_columns() {
+        let timezone = ServerTimezone::Fixed(FixedOffset::east_opt(3 * 3600).unwrap());
+        let batch = Python::with_gil(|py| rates_batch(&rate_array(py), &timezone)).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), super::rates_schema());
+
+        let time = column::<TimestampSecondArray>(&batch, "time");
+        assert_eq!(time.value(0), 1720447200 - 3 * 3600);
+        assert_eq!(time.timezone(), Some("UTC"));
+        assert_eq!(
+            column::<Float64Array>(&batch, "close").values(),
+            &[1.2, 1.3]
+        );
+        assert_eq!(
+            column::<UInt64Array>(&batch, "tick_volume").values(),
+            &[40, 55]
+        );
+        assert_eq!(column::<Int32Array>(&batch, "spread").values(), &[2, 3]);
+        assert_eq!(
+            column::<UInt64Array>(&batch, "real_volume").values(),
+            &[0, 7]
+        );
+    }
+
+    #[test]
+    fn test_rates_batch_from_records() {
+        let rates = [
+            SymbolRates {
+                time: DateTime::from_timestamp(1720447200, 0).unwrap(),
+                open: 1.1,
+                high: 1.3,
+                low: 1.0,
+                close: 1.2,
+                tick_volume: 40,
+                spread: 2.0,
+                real_volume: 0,
+            },
+            SymbolRates {
+                time: DateTime::from_timestamp(1720450800, 0).unwrap(),
+                close: 1.3,
+                tick_volume: 55,
+                spread: 3.0,
+                real_volume: 7,
+                ..Default::default()
+            },
+        ];
+        let batch = rates_batch_from_records(&rates).unwrap();
+        assert_eq!(batch.schema(), super::rates_schema());
+        assert_eq!(
+            column::<TimestampSecondArray>(&batch, "time").values(),
+            &[1720447200, 1720450800]
+        );
+        assert_eq!(
+            column::<Float64Array>(&batch, "close").values(),
+            &[1.2, 1.3]
+        );
+        assert_eq!(column::<Float64Array>(&batch, "high").values(), &[1.3, 0.0]);
+        assert_eq!(
+            column::<UInt64Array>(&batch, "tick_volume").values(),
+            &[40, 55]
+        );
+        assert_eq!(column::<Int32Array>(&batch, "spread").values(), &[2, 3]);
+        assert_eq!(
+            column::<UInt64Array>(&batch, "real_volume").values(),
+            &[0, 7]
+        );
+    }
+
+    #[test]
+    fn test_ticks_batch_from_records() {
+        let ticks = [SymbolTick {
+            time: 1720447201,
+            bid: 1.3,
+            ask: 1.4,
+            last: 0.0,
+            volume: 2.0,
+            time_msc: 1720447201500,
+            flags: 6,
+            volume_real: 0.5,
+        }];
+        let batch = ticks_batch_from_records(&ticks).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.schema(), super::ticks_schema());
+        assert_eq!(
+            column::<TimestampSecondArray>(&batch, "time").value(0),
+            1720447201
+        );
+        assert_eq!(column::<Float64Array>(&batch, "ask").values(), &[1.4]);
+        assert_eq!(column::<UInt64Array>(&batch, "volume").values(), &[2]);
+        assert_eq!(
+            column::<TimestampMillisecondArray>(&batch, "time_msc").value(0),
+            1720447201500
+        );
+        assert_eq!(column::<UInt32Array>(&batch, "flags").values(), &[6]);
+        assert_eq!(
+            column::<Float64Array>(&batch, "volume_real").values(),
+            &[0.5]
+        );
+    }
+}
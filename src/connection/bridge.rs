@@ -0,0 +1,1003 @@
+//! Out-of-process backend talking JSON-RPC to a Python worker that hosts MetaTrader5.
+//!
+//! [`MT5BridgeConnection`] does not embed CPython: it drives `python/bridge_worker.py`
+//! ([`WORKER_SCRIPT`]), spawned as a child process over stdio or reached over TCP, so
+//! a service on Linux can use a terminal running on a Windows machine.
+//!
+//! # Protocol
+//!
+//! Messages are [JSON-RPC 2.0](https://www.jsonrpc.org/specification) objects encoded
+//! as UTF-8, one per line. The client sends one request at a time and waits for its
+//! response.
+//!
+//! * `method` is the name of a `MetaTrader5` module function, e.g. `copy_rates_from`.
+//! * `params` is `{"args": [...], "kwargs": {...}}`, the positional and keyword
+//!   arguments of that function. Both may be omitted.
+//! * Datetime arguments are sent as `{"$datetime": <epoch seconds>}`, with the
+//!   milliseconds as a fraction, and become UTC aware `datetime`s in the worker. Enums are sent as their MQL5 integer value.
+//! * `result` is the function's return value as JSON: named tuples become objects,
+//!   tuples become arrays and numpy structured arrays become arrays of objects.
+//!
+//! ```text
+//! --> {"jsonrpc":"2.0","id":7,"method":"symbols_get","params":{"kwargs":{"group":"*USD*"}}}
+//! <-- {"jsonrpc":"2.0","id":7,"result":[{"name":"EURUSD","digits":5,...}]}
+//! ```
+//!
+//! Errors use the terminal's own codes where there is one:
+//!
+//! | `error.code`       | Meaning                                                      |
+//! |--------------------|--------------------------------------------------------------|
+//! | MQL5 runtime code  | `last_error()` reported a negative code after the call        |
+//! | `-1`               | the function returned `None`                                  |
+//! | `-32000`           | the call raised, `error.data.traceback` holds the traceback   |
+//! | `-32700`, `-32600`, `-32601` | parse error, invalid request, unknown method        |
+//!
+//! `last_error` and `shutdown` are answered as is, without the `last_error` check.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+#[cfg(feature = "arrow")]
+use crate::connection::arrow::{rates_batch_from_records, ticks_batch_from_records};
+use crate::error::check_trade_result;
+use crate::prelude::*;
+use crate::time::ServerTime;
+
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
+/// Source of the Python worker, to run with `python -c` or save as `bridge_worker.py`.
+pub const WORKER_SCRIPT: &str = include_str!("../../python/bridge_worker.py");
+
+/// JSON-RPC code of a Python exception raised in the worker.
+const PYTHON_ERROR: i64 = -32000;
+
+struct Channel {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: Option<u64>,
+    result: Option<Value>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl From<ResponseError> for FishingLineError {
+    fn from(error: ResponseError) -> Self {
+        match error.code {
+            PYTHON_ERROR => FishingLineError::Python {
+                message: error.message,
+                traceback: error
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.get("traceback"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            },
+            code if code <= -32600 => FishingLineError::Environment(format!(
+                "bridge worker rejected the request ({}): {}",
+                code, error.message
+            )),
+            code => FishingLineError::runtime(RuntimeError::from_value(code), error.message),
+        }
+    }
+}
+
+fn transport_error(error: std::io::Error) -> FishingLineError {
+    FishingLineError::Environment(format!("bridge worker is unreachable: {}", error))
+}
+
+/// Connection to a MetaTrader5 terminal through a JSON-RPC worker process.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+/// let connection = MT5BridgeConnection::spawn_python("python")
+///     .and_then(|connection| connection.initialize(terminal_path.as_str()))
+///     .expect("Unable to connect to terminal");
+/// println!("{:?}", connection.version());
+/// ```
+pub struct MT5BridgeConnection {
+    channel: Mutex<Channel>,
+    next_id: AtomicU64,
+    worker: Option<Child>,
+    timezone: ServerTimezone,
+}
+
+impl MT5BridgeConnection {
+    /// Talks to a worker through any pair of streams.
+    pub fn from_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+    ) -> Self {
+        MT5BridgeConnection {
+            channel: Mutex::new(Channel {
+                reader: Box::new(BufReader::new(reader)),
+                writer: Box::new(writer),
+            }),
+            next_id: AtomicU64::new(1),
+            worker: None,
+            timezone: ServerTimezone::default(),
+        }
+    }
+
+    /// Spawns `command` as a worker serving the protocol on its stdin and stdout.
+    ///
+    /// The worker is killed when the connection is dropped.
+    pub fn spawn(mut command: Command) -> MQLResult<Self> {
+        let mut worker = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                FishingLineError::Environment(format!("Unable to spawn bridge worker: {}", error))
+            })?;
+        let (Some(stdin), Some(stdout)) = (worker.stdin.take(), worker.stdout.take()) else {
+            return Err(FishingLineError::Environment(
+                "Bridge worker has no stdio".to_string(),
+            ));
+        };
+        let mut connection = MT5BridgeConnection::from_streams(stdout, stdin);
+        connection.worker = Some(worker);
+        Ok(connection)
+    }
+
+    /// Spawns [`WORKER_SCRIPT`] with the given Python interpreter, which must be able
+    /// to import MetaTrader5.
+    pub fn spawn_python(python: impl AsRef<std::ffi::OsStr>) -> MQLResult<Self> {
+        let mut command = Command::new(python);
+        command.arg("-u").arg("-c").arg(WORKER_SCRIPT);
+        MT5BridgeConnection::spawn(command)
+    }
+
+    /// Connects to a worker started with `bridge_worker.py --listen HOST:PORT`.
+    pub fn connect(address: impl ToSocketAddrs) -> MQLResult<Self> {
+        let stream = TcpStream::connect(address).map_err(transport_error)?;
+        stream.set_nodelay(true).map_err(transport_error)?;
+        let reader = stream.try_clone().map_err(transport_error)?;
+        Ok(MT5BridgeConnection::from_streams(reader, stream))
+    }
+
+    /// Sets the trade server time zone used to convert time arguments and results.
+    ///
+    /// Without it, server times are treated as UTC.
+    pub fn with_server_timezone(mut self, timezone: ServerTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn server_timezone(&self) -> ServerTimezone {
+        self.timezone
+    }
+
+    fn channel(&self) -> MutexGuard<'_, Channel> {
+        self.channel
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sends one request and waits for its result.
+    fn request(&self, method: &str, args: Value, kwargs: Value) -> MQLResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": { "args": args, "kwargs": kwargs },
+        });
+
+        let mut channel = self.channel();
+        let mut line = request.to_string();
+        line.push('\n');
+        channel
+            .writer
+            .write_all(line.as_bytes())
+            .and_then(|_| channel.writer.flush())
+            .map_err(transport_error)?;
+
+        line.clear();
+        if channel
+            .reader
+            .read_line(&mut line)
+            .map_err(transport_error)?
+            == 0
+        {
+            return Err(FishingLineError::Environment(
+                "bridge worker closed the connection".to_string(),
+            ));
+        }
+        let response: Response =
+            serde_json::from_str(&line).map_err(|error| FishingLineError::Conversion {
+                field: method.to_string(),
+                message: format!("invalid bridge response: {}", error),
+            })?;
+        if response.id != Some(id) {
+            return Err(FishingLineError::Environment(format!(
+                "bridge worker answered request {:?} instead of {}",
+                response.id, id
+            )));
+        }
+        match (response.error, response.result) {
+            (Some(error), _) => Err(error.into()),
+            (None, result) => Ok(result.unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Calls `method` and decodes its result.
+    fn call<T: DeserializeOwned>(&self, method: &str, args: Value, kwargs: Value) -> MQLResult<T> {
+        let result = self.request(method, args, kwargs)?;
+        serde_json::from_value(result).map_err(|error| FishingLineError::Conversion {
+            field: method.to_string(),
+            message: error.to_string(),
+        })
+    }
+
+    /// Calls `method` and decodes a list of records, restoring their millisecond times.
+    fn records<T: BridgeRecord>(
+        &self,
+        method: &str,
+        args: Value,
+        kwargs: Value,
+    ) -> MQLResult<Vec<T>> {
+        let records: Vec<T> = self.call(method, args, kwargs)?;
+        Ok(records
+            .into_iter()
+            .map(|record| record.restore_millis().into_utc(&self.timezone))
+            .collect())
+    }
+
+    /// Encodes `date` as a datetime argument, in server time.
+    fn datetime(&self, date: DateTime<Utc>) -> Value {
+        let server = self.timezone.to_server(&date);
+        json!({ "$datetime": server.timestamp_millis() as f64 / 1000.0 })
+    }
+
+    fn trade_request(&self, request: &TradeRequestBuilder) -> MQLResult<Value> {
+        serde_json::to_value(request.clone().into_server_time(&self.timezone)).map_err(|error| {
+            FishingLineError::Conversion {
+                field: "TradeRequestBuilder".to_string(),
+                message: error.to_string(),
+            }
+        })
+    }
+
+    fn history_params(&self, query: &HistoryQuery) -> (Value, Value) {
+        match query {
+            HistoryQuery::Range {
+                date_from,
+                date_to,
+                group,
+            } => {
+                let mut kwargs = Map::new();
+                if let Some(group) = group {
+                    kwargs.insert("group".to_string(), json!(group));
+                }
+                (
+                    json!([self.datetime(*date_from), self.datetime(*date_to)]),
+                    Value::Object(kwargs),
+                )
+            }
+            HistoryQuery::Ticket(ticket) => (json!([]), json!({ "ticket": ticket })),
+            HistoryQuery::Position(position) => (json!([]), json!({ "position": position })),
+        }
+    }
+
+    fn initialize_with(&self, args: Value, kwargs: Value) -> MQLResult<()> {
+        let initialized: bool = self.call("initialize", args, kwargs)?;
+        if !initialized {
+            return Err(FishingLineError::runtime(
+                RuntimeError::AuthFailed,
+                "Failed to initialize MetaTrader5",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MT5BridgeConnection {
+    fn drop(&mut self) {
+        if let Some(mut worker) = self.worker.take() {
+            let _ = worker.kill();
+            let _ = worker.wait();
+        }
+    }
+}
+
+/// Builds the `ticket=`, `symbol=` or `group=` argument of `orders_get`/`positions_get`.
+fn trade_filter_kwargs(filter: &TradeFilter) -> Value {
+    if let Some(ticket) = filter.ticket {
+        json!({ "ticket": ticket })
+    } else if let Some(symbol) = &filter.symbol {
+        json!({ "symbol": symbol })
+    } else if let Some(group) = &filter.group {
+        json!({ "group": group })
+    } else {
+        json!({})
+    }
+}
+
+/// Records decoded from the worker's JSON.
+///
+/// Serde reads times from the whole-second fields, records that also carry `*_msc`
+/// fields take their millisecond times from those, as the embedded backend does.
+trait BridgeRecord: DeserializeOwned + ServerTime {
+    fn restore_millis(self) -> Self {
+        self
+    }
+}
+
+fn millis(value: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(value).unwrap_or_default()
+}
+
+impl BridgeRecord for SymbolInfo {}
+impl BridgeRecord for SymbolRates {}
+impl BridgeRecord for SymbolTick {}
+
+impl BridgeRecord for Order {
+    fn restore_millis(mut self) -> Self {
+        self.time_setup = millis(self.time_setup_msc as i64);
+        self.time_done = millis(self.time_done_msc as i64);
+        self
+    }
+}
+
+impl BridgeRecord for Position {
+    fn restore_millis(mut self) -> Self {
+        self.time = millis(self.time_msc as i64);
+        self.time_update = millis(self.time_update_msc as i64);
+        self
+    }
+}
+
+impl BridgeRecord for Deals {
+    fn restore_millis(mut self) -> Self {
+        self.time = millis(self.time_msc);
+        self
+    }
+}
+
+impl ConnectionTrait<MT5BridgeConnection> for MT5BridgeConnection {
+    fn initialize(self, path: &str) -> MQLResult<Self> {
+        self.initialize_with(json!([path]), json!({}))?;
+        Ok(self)
+    }
+
+    fn initialize_with_credentials(
+        self,
+        path: &str,
+        credentials: AccountCredentials,
+        timeout: i64,
+        portable: Option<bool>,
+    ) -> MQLResult<Self> {
+        let mut kwargs = json!({
+            "login": credentials.login,
+            "password": credentials.password,
+            "server": credentials.server,
+            "timeout": timeout,
+        });
+        if let Some(portable) = portable {
+            kwargs["portable"] = json!(portable);
+        }
+        self.initialize_with(json!([path]), kwargs)?;
+        Ok(self)
+    }
+
+    fn login(&self, credentials: AccountCredentials, timeout: Option<i64>) -> MQLResult<bool> {
+        let mut kwargs = json!({
+            "password": credentials.password,
+            "server": credentials.server,
+        });
+        if let Some(timeout) = timeout {
+            kwargs["timeout"] = json!(timeout);
+        }
+        self.call("login", json!([credentials.login]), kwargs)
+    }
+
+    fn shutdown(self) -> MQLResult<()> {
+        self.request("shutdown", json!([]), json!({}))?;
+        Ok(())
+    }
+}
+
+impl ErrorTrait for MT5BridgeConnection {
+    fn last_error(&self) -> MQLResult<(RuntimeError, String)> {
+        self.call("last_error", json!([]), json!({}))
+    }
+}
+
+impl AccountInfoTrait for MT5BridgeConnection {
+    fn account_info(&self) -> MQLResult<AccountInfo> {
+        self.call("account_info", json!([]), json!({}))
+    }
+}
+
+impl TerminalInfoTrait for MT5BridgeConnection {
+    fn terminal_info(&self) -> MQLResult<TerminalInfo> {
+        self.call("terminal_info", json!([]), json!({}))
+    }
+
+    fn version(&self) -> MQLResult<TerminalVersion> {
+        self.call("version", json!([]), json!({}))
+    }
+}
+
+impl SymbolInfoTrait for MT5BridgeConnection {
+    fn symbols_total(&self) -> MQLResult<i32> {
+        self.call("symbols_total", json!([]), json!({}))
+    }
+
+    fn symbols_get(&self, group: Option<&str>) -> MQLResult<Vec<SymbolInfo>> {
+        let kwargs = match group {
+            Some(group) => json!({ "group": group }),
+            None => json!({}),
+        };
+        self.records("symbols_get", json!([]), kwargs)
+    }
+
+    fn symbol_info(&self, symbol: &str) -> MQLResult<SymbolInfo> {
+        self.call("symbol_info", json!([symbol]), json!({}))
+            .map(|info: SymbolInfo| info.into_utc(&self.timezone))
+    }
+
+    fn symbol_info_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
+        self.call("symbol_info_tick", json!([symbol]), json!({}))
+            .map(|tick: SymbolTick| tick.into_utc(&self.timezone))
+    }
+
+    fn symbol_select(&self, symbol: &str, enable: Option<bool>) -> MQLResult<bool> {
+        self.call(
+            "symbol_select",
+            json!([symbol, enable.unwrap_or(true)]),
+            json!({}),
+        )
+    }
+}
+
+impl SymbolRatesTrait for MT5BridgeConnection {
+    fn copy_rates_from(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.records(
+            "copy_rates_from",
+            json!([symbol, timeframe as i64, self.datetime(date_from), count]),
+            json!({}),
+        )
+    }
+
+    fn copy_rates_from_pos(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.records(
+            "copy_rates_from_pos",
+            json!([symbol, timeframe as i64, start_pos, count]),
+            json!({}),
+        )
+    }
+
+    fn copy_rates_range(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.records(
+            "copy_rates_range",
+            json!([
+                symbol,
+                timeframe as i64,
+                self.datetime(date_from),
+                self.datetime(date_to)
+            ]),
+            json!({}),
+        )
+    }
+}
+
+impl SymbolTicksTrait for MT5BridgeConnection {
+    fn copy_ticks_from(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        self.records(
+            "copy_ticks_from",
+            json!([symbol, self.datetime(date_from), count, flags as i64]),
+            json!({}),
+        )
+    }
+
+    fn copy_ticks_range(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        self.records(
+            "copy_ticks_range",
+            json!([
+                symbol,
+                self.datetime(date_from),
+                self.datetime(date_to),
+                flags as i64
+            ]),
+            json!({}),
+        )
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl SymbolRatesArrowTrait for MT5BridgeConnection {
+    fn copy_rates_from_arrow(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<RecordBatch> {
+        rates_batch_from_records(&self.copy_rates_from(symbol, timeframe, date_from, count)?)
+    }
+
+    fn copy_rates_from_pos_arrow(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<RecordBatch> {
+        rates_batch_from_records(&self.copy_rates_from_pos(symbol, timeframe, start_pos, count)?)
+    }
+
+    fn copy_rates_range_arrow(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<RecordBatch> {
+        rates_batch_from_records(&self.copy_rates_range(symbol, timeframe, date_from, date_to)?)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl SymbolTicksArrowTrait for MT5BridgeConnection {
+    fn copy_ticks_from_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<RecordBatch> {
+        ticks_batch_from_records(&self.copy_ticks_from(symbol, date_from, count, flags)?)
+    }
+
+    fn copy_ticks_range_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<RecordBatch> {
+        ticks_batch_from_records(&self.copy_ticks_range(symbol, date_from, date_to, flags)?)
+    }
+}
+
+impl OrderTrait for MT5BridgeConnection {
+    fn orders_total(&self) -> MQLResult<i64> {
+        self.call("orders_total", json!([]), json!({}))
+    }
+
+    fn orders_get(&self) -> MQLResult<Vec<Order>> {
+        self.records("orders_get", json!([]), json!({}))
+    }
+
+    fn orders_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Order>> {
+        let orders: Vec<Order> =
+            self.records("orders_get", json!([]), trade_filter_kwargs(filter))?;
+        Ok(orders
+            .into_iter()
+            .filter(|order| filter.matches_order(order))
+            .collect())
+    }
+
+    fn order_calc_margin(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+    ) -> MQLResult<f64> {
+        self.call(
+            "order_calc_margin",
+            json!([action, symbol, volume, price]),
+            json!({}),
+        )
+    }
+
+    fn order_calc_profit(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price_open: f64,
+        price_close: f64,
+    ) -> MQLResult<f64> {
+        self.call(
+            "order_calc_profit",
+            json!([action, symbol, volume, price_open, price_close]),
+            json!({}),
+        )
+    }
+
+    fn order_check(&self, request: &TradeRequestBuilder) -> MQLResult<CheckResult> {
+        self.call(
+            "order_check",
+            json!([self.trade_request(request)?]),
+            json!({}),
+        )
+        .map(|result: CheckResult| result.into_utc(&self.timezone))
+    }
+
+    /// Sends a trade request to the trade server.
+    ///
+    /// Answers other than `DONE`, `PLACED` and `DonePartial` are returned as
+    /// [`FishingLineError::TradeRejected`] carrying the full [`TradeResult`].
+    fn order_send(&self, request: TradeRequestBuilder) -> MQLResult<TradeResult> {
+        let trade_result: TradeResult = self
+            .call(
+                "order_send",
+                json!([self.trade_request(&request)?]),
+                json!({}),
+            )
+            .map(|result: TradeResult| result.into_utc(&self.timezone))?;
+        check_trade_result(trade_result)
+    }
+}
+
+impl MarketBookTrait for MT5BridgeConnection {
+    fn market_book_add(&self, symbol: &str) -> MQLResult<bool> {
+        self.call("market_book_add", json!([symbol]), json!({}))
+    }
+
+    fn market_book_get(&self, symbol: &str) -> MQLResult<MarketBook> {
+        Ok(MarketBook {
+            entries: self.call("market_book_get", json!([symbol]), json!({}))?,
+        })
+    }
+
+    fn market_book_release(&self, symbol: &str) -> MQLResult<bool> {
+        self.call("market_book_release", json!([symbol]), json!({}))
+    }
+}
+
+impl PositionTrait for MT5BridgeConnection {
+    fn positions_total(&self) -> MQLResult<i64> {
+        self.call("positions_total", json!([]), json!({}))
+    }
+
+    fn positions_get(&self) -> MQLResult<Vec<Position>> {
+        self.records("positions_get", json!([]), json!({}))
+    }
+
+    fn positions_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Position>> {
+        let positions: Vec<Position> =
+            self.records("positions_get", json!([]), trade_filter_kwargs(filter))?;
+        Ok(positions
+            .into_iter()
+            .filter(|position| filter.matches_position(position))
+            .collect())
+    }
+}
+
+impl HistoryTrait for MT5BridgeConnection {
+    fn history_orders_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.call(
+            "history_orders_total",
+            json!([self.datetime(date_from), self.datetime(date_to)]),
+            json!({}),
+        )
+    }
+
+    fn history_orders_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Order>> {
+        self.history_orders_query(&HistoryQuery::range(date_from, date_to))
+    }
+
+    fn history_deals_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.call(
+            "history_deals_total",
+            json!([self.datetime(date_from), self.datetime(date_to)]),
+            json!({}),
+        )
+    }
+
+    fn history_deals_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Deals>> {
+        self.history_deals_query(&HistoryQuery::range(date_from, date_to))
+    }
+
+    fn history_orders_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Order>> {
+        let (args, kwargs) = self.history_params(query);
+        self.records("history_orders_get", args, kwargs)
+    }
+
+    fn history_deals_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Deals>> {
+        let (args, kwargs) = self.history_params(query);
+        self.records("history_deals_get", args, kwargs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::process::Command;
+
+    use chrono::{FixedOffset, TimeZone, Utc};
+    use serde_json::{json, Value};
+
+    use super::WORKER_SCRIPT;
+    use crate::prelude::*;
+
+    /// Serves `answer(method, params)` to a single client on a local port.
+    fn fake_worker(answer: fn(&str, &Value) -> Value) -> MT5BridgeConnection {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines() {
+                let request: Value = serde_json::from_str(&line.unwrap()).unwrap();
+                let mut response = answer(request["method"].as_str().unwrap(), &request["params"]);
+                response["jsonrpc"] = json!("2.0");
+                response["id"] = request["id"].clone();
+                writeln!(writer, "{}", response).unwrap();
+            }
+        });
+        MT5BridgeConnection::connect(address).unwrap()
+    }
+
+    #[test]
+    fn test_bridge_decodes_records() {
+        let connection = fake_worker(|method, params| match method {
+            "positions_get" => {
+                assert_eq!(params["kwargs"], json!({ "symbol": "EURUSD" }));
+                json!({ "result": [{
+                    "ticket": 5, "time": 1720447200, "time_msc": 1720447200250_i64,
+                    "time_update": 1720447200, "time_update_msc": 1720447200250_i64,
+                    "type": 1, "magic": 7, "identifier": 5, "reason": 3, "volume": 0.1,
+                    "price_open": 1.1, "sl": 0.0, "tp": 0.0, "price_current": 1.1,
+                    "swap": 0.0, "profit": 0.0, "symbol": "EURUSD", "comment": "grid",
+                    "external_id": "",
+                }]})
+            }
+            "copy_ticks_from" => {
+                assert_eq!(
+                    params["args"][1],
+                    json!({ "$datetime": (1720447200 + 3 * 3600) as f64 })
+                );
+                json!({ "result": [{
+                    "time": 1720447200, "bid": 1.1, "ask": 1.2, "last": 0.0, "volume": 0,
+                    "time_msc": 1720447200250_i64, "flags": 6, "volume_real": 0.0,
+                }]})
+            }
+            _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+        })
+        .with_server_timezone(ServerTimezone::Fixed(
+            FixedOffset::east_opt(3 * 3600).unwrap(),
+        ));
+
+        let positions = connection
+            .positions_get_filtered(&TradeFilter::new().symbol("EURUSD"))
+            .unwrap();
+        assert_eq!(positions[0].r#type, PositionType::SELL);
+        assert_eq!(positions[0].reason, PositionReason::EXPERT);
+        assert_eq!(
+            positions[0].time.timestamp_millis(),
+            1720447200250 - 3 * 3600 * 1000
+        );
+
+        let date_from = Utc.timestamp_opt(1720447200, 0).unwrap();
+        let ticks = connection
+            .copy_ticks_from("EURUSD", date_from, 1, CopyTicksFlags::ALL)
+            .unwrap();
+        assert_eq!(ticks[0].time, 1720447200 - 3 * 3600);
+        assert_eq!(ticks[0].flags, 6);
+    }
+
+    #[test]
+    fn test_bridge_sends_millisecond_datetimes() {
+        let connection = fake_worker(|method, params| match method {
+            "copy_ticks_range" => {
+                assert_eq!(params["args"][1], json!({ "$datetime": 1720447200.25 }));
+                assert_eq!(params["args"][2], json!({ "$datetime": 1720447201.5 }));
+                json!({ "result": [] })
+            }
+            _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+        });
+
+        let ticks = connection
+            .copy_ticks_range(
+                "EURUSD",
+                Utc.timestamp_millis_opt(1720447200250).unwrap(),
+                Utc.timestamp_millis_opt(1720447201500).unwrap(),
+                CopyTicksFlags::ALL,
+            )
+            .unwrap();
+        assert!(ticks.is_empty());
+    }
+
+    #[test]
+    fn test_bridge_serializes_trade_requests() {
+        let connection = fake_worker(|method, params| match method {
+            "order_send" => {
+                let request = &params["args"][0];
+                let expected = if request["action"] == json!(1) {
+                    json!({ "action": 1, "symbol": "EURUSD", "volume": 0.1, "type": 0 })
+                } else {
+                    json!({ "action": 8, "order": 8 })
+                };
+                assert_eq!(request, &expected);
+                json!({ "result": {
+                    "retcode": 10009, "deal": 0, "order": 8, "volume": 0.0, "price": 0.0,
+                    "bid": 0.0, "ask": 0.0, "comment": "", "request_id": 1,
+                    "retcode_external": 0, "request": {
+                        "action": 8, "magic": 0, "order": 8, "symbol": "", "volume": 0.0,
+                        "price": 0.0, "stoplimit": 0.0, "sl": 0.0, "tp": 0.0, "deviation": 0,
+                        "type": 0, "type_filling": 0, "type_time": 0, "expiration": 0,
+                        "comment": "", "position": 0, "position_by": 0,
+                    },
+                }})
+            }
+            _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+        });
+
+        connection
+            .order_send(MarketOrder::buy("EURUSD", 0.1).into())
+            .unwrap();
+        let result = connection.order_send(CancelPending::new(8).into()).unwrap();
+        assert_eq!(result.retcode, ReturnCode::DONE);
+    }
+
+    #[test]
+    fn test_bridge_maps_errors() {
+        let connection = fake_worker(|method, _| match method {
+            "symbol_info_tick" => {
+                json!({ "error": { "code": -4, "message": "Terminal: Not found" } })
+            }
+            "order_send" => json!({ "error": {
+                "code": -32000,
+                "message": "boom",
+                "data": { "traceback": "Traceback (most recent call last)" },
+            }}),
+            _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+        });
+
+        let error = connection.symbol_info_tick("XAUUSD").unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+
+        match connection
+            .order_send(TradeRequestBuilder::new())
+            .unwrap_err()
+        {
+            FishingLineError::Python { message, traceback } => {
+                assert_eq!(message, "boom");
+                assert!(traceback.is_some(), "Traceback was dropped");
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+
+        match connection.account_info().unwrap_err() {
+            FishingLineError::Environment(message) => {
+                assert!(message.contains("-32601"), "Unexpected message {}", message)
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+
+    /// Runs the real worker script against a fake `MetaTrader5` module.
+    #[test]
+    fn test_worker_script() {
+        let module =
+            std::env::temp_dir().join(format!("fishing-line-bridge-{}", std::process::id()));
+        std::fs::create_dir_all(&module).unwrap();
+        std::fs::write(
+            module.join("MetaTrader5.py"),
+            r#"
+import collections
+
+Tick = collections.namedtuple("Tick", "time bid ask last volume time_msc flags volume_real")
+error = (1, "Success")
+
+def initialize(path, **kwargs):
+    return path == "terminal64.exe"
+
+def last_error():
+    return error
+
+def version():
+    return (500, 4410, "12 Jul 2024")
+
+def copy_ticks_from(symbol, date_from, count, flags):
+    global error
+    if symbol != "EURUSD":
+        error = (-4, "Terminal: Not found")
+        return None
+    error = (1, "Success")
+    time = int(date_from.timestamp())
+    assert date_from.tzinfo is not None
+    return tuple(Tick(time + i, 1.1, 1.2, 0.0, 0, (time + i) * 1000, 6, 0.0) for i in range(count))
+"#,
+        )
+        .unwrap();
+
+        let mut command = Command::new(if cfg!(windows) { "python" } else { "python3" });
+        command
+            .arg("-u")
+            .arg("-c")
+            .arg(WORKER_SCRIPT)
+            .env("PYTHONPATH", &module);
+        let connection = MT5BridgeConnection::spawn(command)
+            .and_then(|connection| connection.initialize("terminal64.exe"))
+            .unwrap();
+
+        assert_eq!(connection.version().unwrap().build, 4410);
+        let date_from = Utc.timestamp_opt(1720447200, 0).unwrap();
+        let ticks = connection
+            .copy_ticks_from("EURUSD", date_from, 2, CopyTicksFlags::ALL)
+            .unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[1].time_msc, 1720447201000);
+        let error = connection
+            .copy_ticks_from("XAUUSD", date_from, 2, CopyTicksFlags::ALL)
+            .unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+
+        drop(connection);
+        std::fs::remove_dir_all(module).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+//! Locates the Python packages `MT5PythonConnection` imports.
+//!
+//! The MetaTrader5 module and numpy can come from a virtualenv, a poetry, uv or conda
+//! environment, explicit `site-packages` directories or the interpreter's own
+//! packages. Environment roots are resolved with either the Windows layout
+//! (`Lib/site-packages`) or the POSIX one (`lib/pythonX.Y/site-packages`), so an
+//! environment created for the Windows Python can be used from a POSIX path under Wine.
+
+use std::path::{Path, PathBuf};
+
+use pyo3::prelude::*;
+
+use crate::prelude::*;
+
+/// Directory layout of a Python environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvLayout {
+    /// `<root>/Lib/site-packages`, used by Windows Python, including under Wine.
+    Windows,
+    /// `<root>/lib/pythonX.Y/site-packages`.
+    Posix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvKind {
+    Venv,
+    Poetry,
+    Conda,
+    Uv,
+}
+
+impl EnvKind {
+    fn name(self) -> &'static str {
+        match self {
+            EnvKind::Venv => "virtualenv",
+            EnvKind::Poetry => "poetry environment",
+            EnvKind::Conda => "conda environment",
+            EnvKind::Uv => "uv environment",
+        }
+    }
+}
+
+/// Modules imported by [`PythonEnvConfig::import`], dependencies first.
+const MODULES: [&str; 2] = ["numpy", "MetaTrader5"];
+
+/// Where [`MT5PythonConnection`] finds the MetaTrader5 and numpy packages.
+///
+/// Without any environment the interpreter's own `sys.path` is used, which covers a
+/// system-wide installation.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// let config = PythonEnvConfig::new().poetry("/path/to/poetry/virtualenv");
+/// let connection = MT5PythonConnection::with_config(&config)
+///     .expect("Unable to import MetaTrader5");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PythonEnvConfig {
+    environments: Vec<(EnvKind, PathBuf)>,
+    site_packages: Vec<PathBuf>,
+    layout: Option<EnvLayout>,
+}
+
+impl PythonEnvConfig {
+    /// Uses the interpreter's own packages only.
+    pub fn new() -> Self {
+        PythonEnvConfig::default()
+    }
+
+    /// Detects the active environment from `VIRTUAL_ENV`, then `POETRY_ENVIRONMENT`,
+    /// then `CONDA_PREFIX`, falling back to the interpreter's own packages.
+    pub fn from_env() -> Self {
+        PythonEnvConfig::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        let config = PythonEnvConfig::new();
+        if let Some(root) = var("VIRTUAL_ENV") {
+            config.venv(root)
+        } else if let Some(root) = var("POETRY_ENVIRONMENT") {
+            config.poetry(root)
+        } else if let Some(prefix) = var("CONDA_PREFIX") {
+            config.conda(prefix)
+        } else {
+            config
+        }
+    }
+
+    fn environment(mut self, kind: EnvKind, root: impl Into<PathBuf>) -> Self {
+        self.environments.push((kind, root.into()));
+        self
+    }
+
+    /// Virtualenv created by `python -m venv` or `virtualenv`.
+    pub fn venv(self, root: impl Into<PathBuf>) -> Self {
+        self.environment(EnvKind::Venv, root)
+    }
+
+    /// Poetry virtualenv, as printed by `poetry env info --path`.
+    pub fn poetry(self, root: impl Into<PathBuf>) -> Self {
+        self.environment(EnvKind::Poetry, root)
+    }
+
+    /// Conda environment prefix, as found in `CONDA_PREFIX`.
+    pub fn conda(self, prefix: impl Into<PathBuf>) -> Self {
+        self.environment(EnvKind::Conda, prefix)
+    }
+
+    /// uv project directory, whose `.venv` is used, or the virtualenv itself.
+    pub fn uv(self, project: impl Into<PathBuf>) -> Self {
+        self.environment(EnvKind::Uv, project)
+    }
+
+    /// Drops every configured environment and directory to use the interpreter's own packages.
+    pub fn system(self) -> Self {
+        PythonEnvConfig {
+            layout: self.layout,
+            ..PythonEnvConfig::default()
+        }
+    }
+
+    /// Adds a `site-packages` directory as is.
+    pub fn site_packages(mut self, path: impl Into<PathBuf>) -> Self {
+        self.site_packages.push(path.into());
+        self
+    }
+
+    /// Forces the layout of environment roots instead of probing both.
+    pub fn layout(mut self, layout: EnvLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    fn layouts(&self) -> Vec<EnvLayout> {
+        match self.layout {
+            Some(layout) => vec![layout],
+            None if cfg!(windows) => vec![EnvLayout::Windows, EnvLayout::Posix],
+            None => vec![EnvLayout::Posix, EnvLayout::Windows],
+        }
+    }
+
+    fn environment_site_packages(
+        &self,
+        kind: EnvKind,
+        root: &Path,
+        version: (u8, u8),
+    ) -> MQLResult<PathBuf> {
+        let root = match kind {
+            EnvKind::Uv if root.join(".venv").is_dir() => root.join(".venv"),
+            _ => root.to_path_buf(),
+        };
+        let candidates: Vec<PathBuf> = self
+            .layouts()
+            .into_iter()
+            .map(|layout| match layout {
+                EnvLayout::Windows => root.join("Lib").join("site-packages"),
+                EnvLayout::Posix => root
+                    .join("lib")
+                    .join(format!("python{}.{}", version.0, version.1))
+                    .join("site-packages"),
+            })
+            .collect();
+        candidates
+            .iter()
+            .find(|candidate| candidate.is_dir())
+            .cloned()
+            .ok_or_else(|| {
+                FishingLineError::Environment(format!(
+                    "{} `{}` has no site-packages directory, looked for {}",
+                    kind.name(),
+                    root.display(),
+                    candidates
+                        .iter()
+                        .map(|candidate| format!("`{}`", candidate.display()))
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                ))
+            })
+    }
+
+    /// Resolves the `site-packages` directories for a Python `major.minor` interpreter.
+    pub fn resolve(&self, version: (u8, u8)) -> MQLResult<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for (kind, root) in &self.environments {
+            paths.push(self.environment_site_packages(*kind, root, version)?);
+        }
+        for path in &self.site_packages {
+            if !path.is_dir() {
+                return Err(FishingLineError::Environment(format!(
+                    "site-packages directory `{}` does not exist",
+                    path.display()
+                )));
+            }
+            paths.push(path.clone());
+        }
+        Ok(paths)
+    }
+
+    /// Adds the resolved directories in front of `sys.path` and imports numpy and
+    /// MetaTrader5, returning the MetaTrader5 module.
+    pub(crate) fn import<'py>(&self, py: Python<'py>) -> MQLResult<Bound<'py, PyModule>> {
+        self.import_modules(py, &MODULES)
+    }
+
+    /// Imports `modules` in order, returning the last one.
+    fn import_modules<'py>(
+        &self,
+        py: Python<'py>,
+        modules: &[&str],
+    ) -> MQLResult<Bound<'py, PyModule>> {
+        let version = py.version_info();
+        let paths = self.resolve((version.major, version.minor))?;
+        let sys_path = py.import_bound("sys")?.getattr("path")?;
+        for path in paths.iter().rev() {
+            let path = path.to_string_lossy();
+            if !sys_path.contains(path.as_ref())? {
+                sys_path.call_method1("insert", (0, path.as_ref()))?;
+            }
+        }
+        let mut module = None;
+        for name in modules.iter().copied() {
+            let imported =
+                py.import_bound(name)
+                    .map_err(|error| FishingLineError::ModuleImport {
+                        module: name.to_string(),
+                        message: import_message(py, &error, &paths),
+                    })?;
+            module = Some(imported);
+        }
+        module.ok_or_else(|| FishingLineError::Environment("No module to import".to_string()))
+    }
+}
+
+fn import_message(py: Python<'_>, error: &PyErr, paths: &[PathBuf]) -> String {
+    let searched = if paths.is_empty() {
+        "the interpreter's sys.path".to_string()
+    } else {
+        paths
+            .iter()
+            .map(|path| format!("`{}`", path.display()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("{} (searched {})", error.value_bound(py), searched)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use pyo3::prelude::*;
+
+    use super::PythonEnvConfig;
+    use crate::prelude::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fishing-line-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_from_vars_prefers_virtual_env() {
+        let vars = HashMap::from([
+            ("VIRTUAL_ENV", "/envs/venv"),
+            ("POETRY_ENVIRONMENT", "/envs/poetry"),
+        ]);
+        let config = PythonEnvConfig::from_vars(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(config.environments.len(), 1);
+        assert_eq!(config.environments[0].1, PathBuf::from("/envs/venv"));
+
+        let config = PythonEnvConfig::from_vars(|_| None);
+        assert!(
+            config.environments.is_empty(),
+            "System Python has no environment"
+        );
+    }
+
+    #[test]
+    fn test_resolve_layouts() {
+        let root = temp_dir("layouts");
+        let windows = root.join("wine").join("Lib").join("site-packages");
+        let posix = root
+            .join("uv")
+            .join(".venv")
+            .join("lib")
+            .join("python3.11")
+            .join("site-packages");
+        std::fs::create_dir_all(&windows).unwrap();
+        std::fs::create_dir_all(&posix).unwrap();
+
+        let config = PythonEnvConfig::new()
+            .poetry(root.join("wine"))
+            .uv(root.join("uv"));
+        assert_eq!(config.resolve((3, 11)).unwrap(), vec![windows, posix]);
+
+        let error = PythonEnvConfig::new()
+            .layout(EnvLayout::Windows)
+            .uv(root.join("uv"))
+            .resolve((3, 11))
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("uv environment"),
+            "Unexpected error {}",
+            error
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_import_names_missing_module() {
+        let error = Python::with_gil(|py| {
+            PythonEnvConfig::new()
+                .import_modules(py, &["json", "fishing_line_missing"])
+                .unwrap_err()
+        });
+        match error {
+            FishingLineError::ModuleImport { module, message } => {
+                assert_eq!(module, "fishing_line_missing");
+                assert!(
+                    message.contains("sys.path"),
+                    "Unexpected message {}",
+                    message
+                );
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+}
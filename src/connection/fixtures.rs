@@ -0,0 +1,31 @@
+//! Test fixtures shared by the backends and the modules built on [`Terminal`](crate::prelude::Terminal).
+
+use crate::prelude::*;
+
+/// EURUSD on a 5 digit broker quoted at 1.10000/1.10020, with a 10 point stops
+/// level, market execution, IOC filling and every order type and lifetime allowed.
+pub(crate) fn eurusd() -> SymbolInfo {
+    SymbolInfo {
+        name: "EURUSD".to_string(),
+        bid: 1.10000,
+        ask: 1.10020,
+        digits: 5,
+        point: 0.00001,
+        trade_tick_size: 0.00001,
+        trade_contract_size: 100_000.0,
+        trade_stops_level: 10,
+        trade_mode: SymbolTradeMode::SymbolTradeModeFull,
+        trade_exemode: SymbolTradeExecution::Market,
+        trade_calc_mode: SymbolCalcMode::FOREX,
+        order_mode: SymbolOrderMode::All,
+        expiration_mode: SymbolExpirationMode::All,
+        volume_min: 0.01,
+        volume_max: 100.0,
+        volume_step: 0.01,
+        filling_mode: SymbolFillingMode::Ioc,
+        currency_base: "EUR".to_string(),
+        currency_margin: "EUR".to_string(),
+        currency_profit: "USD".to_string(),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,1199 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+use crate::error::check_trade_result;
+use crate::prelude::*;
+use crate::schemas::group_matches;
+
+type OrderSendHandler = Box<dyn FnMut(&TradeRequestBuilder) -> MQLResult<TradeResult> + Send>;
+type OrderCheckHandler = Box<dyn FnMut(&TradeRequestBuilder) -> MQLResult<CheckResult> + Send>;
+type CalcMarginHandler =
+    Box<dyn FnMut(TradeActionRequest, &str, f64, f64) -> MQLResult<f64> + Send>;
+type CalcProfitHandler =
+    Box<dyn FnMut(TradeActionRequest, &str, f64, f64, f64) -> MQLResult<f64> + Send>;
+
+struct MockState {
+    connected: bool,
+    account: Option<AccountInfo>,
+    terminal_info: Option<TerminalInfo>,
+    version: Option<TerminalVersion>,
+    symbols: Vec<SymbolInfo>,
+    ticks: HashMap<String, Vec<SymbolTick>>,
+    rates: HashMap<(String, i64), Vec<SymbolRates>>,
+    orders: Vec<Order>,
+    positions: Vec<Position>,
+    history_orders: Vec<Order>,
+    deals: Vec<Deals>,
+    books: HashMap<String, Vec<BookEntry>>,
+    book_subscriptions: HashSet<String>,
+    last_error: (RuntimeError, String),
+    sticky_error: Option<MQLError>,
+    queued_errors: HashMap<String, VecDeque<MQLError>>,
+    order_send_handler: Option<OrderSendHandler>,
+    order_check_handler: Option<OrderCheckHandler>,
+    calc_margin_handler: Option<CalcMarginHandler>,
+    calc_profit_handler: Option<CalcProfitHandler>,
+    sent_requests: Vec<TradeRequestBuilder>,
+    next_ticket: usize,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        MockState {
+            connected: false,
+            account: None,
+            terminal_info: None,
+            version: None,
+            symbols: Vec::new(),
+            ticks: HashMap::new(),
+            rates: HashMap::new(),
+            orders: Vec::new(),
+            positions: Vec::new(),
+            history_orders: Vec::new(),
+            deals: Vec::new(),
+            books: HashMap::new(),
+            book_subscriptions: HashSet::new(),
+            last_error: (RuntimeError::Ok, "Success".to_string()),
+            sticky_error: None,
+            queued_errors: HashMap::new(),
+            order_send_handler: None,
+            order_check_handler: None,
+            calc_margin_handler: None,
+            calc_profit_handler: None,
+            sent_requests: Vec::new(),
+            next_ticket: 1,
+        }
+    }
+}
+
+impl MockState {
+    fn symbol(&self, symbol: &str) -> MQLResult<&SymbolInfo> {
+        self.symbols
+            .iter()
+            .find(|info| info.name == symbol)
+            .ok_or_else(|| not_found(format!("Symbol `{}` not found", symbol)))
+    }
+
+    fn last_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
+        if let Some(tick) = self.ticks.get(symbol).and_then(|ticks| ticks.last()) {
+            return Ok(tick.clone());
+        }
+        let info = self.symbol(symbol)?;
+        Ok(SymbolTick {
+            time: info.time,
+            bid: info.bid,
+            ask: info.ask,
+            last: info.last,
+            volume: info.volume,
+            time_msc: info.time * 1000,
+            flags: 0,
+            volume_real: info.volume_real,
+        })
+    }
+
+    fn rates(&self, symbol: &str, timeframe: Timeframe) -> MQLResult<&Vec<SymbolRates>> {
+        self.rates
+            .get(&(symbol.to_string(), timeframe as i64))
+            .ok_or_else(|| not_found(format!("No rates scripted for `{}`", symbol)))
+    }
+
+    fn ticks(&self, symbol: &str) -> MQLResult<&Vec<SymbolTick>> {
+        self.ticks
+            .get(symbol)
+            .ok_or_else(|| not_found(format!("No ticks scripted for `{}`", symbol)))
+    }
+
+    fn next_ticket(&mut self) -> usize {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        ticket
+    }
+}
+
+fn not_found(message: String) -> MQLError {
+    FishingLineError::runtime(RuntimeError::NotFound, message)
+}
+
+/// The `last_error` the terminal reports after a call failed with `error`.
+fn reported_error(error: &MQLError) -> (RuntimeError, String) {
+    match error {
+        FishingLineError::Runtime { code, message } => (*code, message.clone()),
+        _ => (RuntimeError::Ok, "Success".to_string()),
+    }
+}
+
+fn tick_matches_flags(tick: &SymbolTick, flags: CopyTicksFlags) -> bool {
+    match flags {
+        CopyTicksFlags::ALL => true,
+        CopyTicksFlags::INFO => tick.flags & (TicksFlag::BID as i64 | TicksFlag::ASK as i64) != 0,
+        CopyTicksFlags::TRADE => {
+            tick.flags & (TicksFlag::LAST as i64 | TicksFlag::VOLUME as i64) != 0
+        }
+    }
+}
+
+/// The `count` bars of time-ordered `rates` opened at or before `date_from`.
+pub(crate) fn rates_from(
+    rates: &[SymbolRates],
+    date_from: DateTime<Utc>,
+    count: i32,
+) -> Vec<SymbolRates> {
+    let end = rates.partition_point(|rate| rate.time <= date_from);
+    let start = end.saturating_sub(count.max(0) as usize);
+    rates[start..end].to_vec()
+}
+
+/// The `count` bars of time-ordered `rates` ending `start_pos` bars before the last.
+pub(crate) fn rates_from_pos(
+    rates: &[SymbolRates],
+    start_pos: i32,
+    count: i32,
+) -> Vec<SymbolRates> {
+    let end = rates.len().saturating_sub(start_pos.max(0) as usize);
+    let start = end.saturating_sub(count.max(0) as usize);
+    rates[start..end].to_vec()
+}
+
+pub(crate) fn rates_range(
+    rates: &[SymbolRates],
+    date_from: DateTime<Utc>,
+    date_to: DateTime<Utc>,
+) -> Vec<SymbolRates> {
+    rates
+        .iter()
+        .filter(|rate| rate.time >= date_from && rate.time <= date_to)
+        .cloned()
+        .collect()
+}
+
+/// The first `count` of `ticks` matching `flags` at or after `date_from`.
+pub(crate) fn ticks_from(
+    ticks: &[SymbolTick],
+    date_from: DateTime<Utc>,
+    count: i32,
+    flags: CopyTicksFlags,
+) -> Vec<SymbolTick> {
+    ticks
+        .iter()
+        .filter(|tick| tick.time_msc >= date_from.timestamp_millis())
+        .filter(|tick| tick_matches_flags(tick, flags))
+        .take(count.max(0) as usize)
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn ticks_range(
+    ticks: &[SymbolTick],
+    date_from: DateTime<Utc>,
+    date_to: DateTime<Utc>,
+    flags: CopyTicksFlags,
+) -> Vec<SymbolTick> {
+    ticks
+        .iter()
+        .filter(|tick| {
+            tick.time_msc >= date_from.timestamp_millis()
+                && tick.time_msc <= date_to.timestamp_millis()
+        })
+        .filter(|tick| tick_matches_flags(tick, flags))
+        .cloned()
+        .collect()
+}
+
+/// In-memory terminal backend for offline testing.
+///
+/// `MockTerminal` implements every trait in [`crate::traits`] against scripted state,
+/// so strategies can be exercised with `cargo test` without a running MetaTrader5
+/// terminal. Clones share the same state, which lets a test keep a handle to push
+/// new ticks or positions while the code under test owns another.
+///
+/// Failures are scripted with [`MockTerminal::fail_next`] (one-off, per method) or
+/// [`MockTerminal::set_last_error`] (sticky until cleared), and `last_error` reports the
+/// outcome of the most recent call just like the terminal does.
+/// Trade responses default to a filled `DONE` result and can be replaced with
+/// [`MockTerminal::on_order_send`] and [`MockTerminal::on_order_check`].
+///
+/// ```rust
+/// use fishing_line::prelude::*;
+///
+/// let terminal = MockTerminal::new().with_symbol(SymbolInfo {
+///     name: "EURUSD".to_string(),
+///     bid: 1.1000,
+///     ask: 1.1002,
+///     ..Default::default()
+/// });
+///
+/// let tick = terminal.symbol_info_tick("EURUSD").unwrap();
+/// assert_eq!(tick.ask, 1.1002);
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTerminal {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTerminal {
+    pub fn new() -> Self {
+        MockTerminal::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs `call` against the scripted state, honouring queued and sticky errors.
+    fn call<R>(
+        &self,
+        method: &str,
+        call: impl FnOnce(&mut MockState) -> MQLResult<R>,
+    ) -> MQLResult<R> {
+        let mut state = self.state();
+
+        if let Some(error) = state
+            .queued_errors
+            .get_mut(method)
+            .and_then(VecDeque::pop_front)
+        {
+            state.last_error = reported_error(&error);
+            return Err(error);
+        }
+
+        if let Some(error) = state.sticky_error.clone() {
+            state.last_error = reported_error(&error);
+            return Err(error);
+        }
+
+        let result = call(&mut state);
+        state.last_error = match &result {
+            Ok(_) => (RuntimeError::Ok, "Success".to_string()),
+            Err(error) => reported_error(error),
+        };
+        result
+    }
+
+    pub fn with_account(self, account: AccountInfo) -> Self {
+        self.set_account(account);
+        self
+    }
+
+    pub fn with_terminal_info(self, terminal_info: TerminalInfo) -> Self {
+        self.state().terminal_info = Some(terminal_info);
+        self
+    }
+
+    pub fn with_version(self, version: TerminalVersion) -> Self {
+        self.state().version = Some(version);
+        self
+    }
+
+    pub fn with_symbol(self, symbol: SymbolInfo) -> Self {
+        self.set_symbol(symbol);
+        self
+    }
+
+    pub fn with_ticks(self, symbol: &str, ticks: Vec<SymbolTick>) -> Self {
+        self.state().ticks.insert(symbol.to_string(), ticks);
+        self
+    }
+
+    pub fn with_rates(self, symbol: &str, timeframe: Timeframe, rates: Vec<SymbolRates>) -> Self {
+        self.state()
+            .rates
+            .insert((symbol.to_string(), timeframe as i64), rates);
+        self
+    }
+
+    pub fn with_order(self, order: Order) -> Self {
+        self.state().orders.push(order);
+        self
+    }
+
+    pub fn with_position(self, position: Position) -> Self {
+        self.state().positions.push(position);
+        self
+    }
+
+    pub fn with_history_order(self, order: Order) -> Self {
+        self.state().history_orders.push(order);
+        self
+    }
+
+    pub fn with_deal(self, deal: Deals) -> Self {
+        self.state().deals.push(deal);
+        self
+    }
+
+    pub fn with_market_book(self, symbol: &str, entries: Vec<BookEntry>) -> Self {
+        self.set_market_book(symbol, entries);
+        self
+    }
+
+    pub fn set_account(&self, account: AccountInfo) {
+        self.state().account = Some(account);
+    }
+
+    /// Adds a symbol or replaces the one with the same name.
+    pub fn set_symbol(&self, symbol: SymbolInfo) {
+        let mut state = self.state();
+        match state
+            .symbols
+            .iter_mut()
+            .find(|info| info.name == symbol.name)
+        {
+            Some(existing) => *existing = symbol,
+            None => state.symbols.push(symbol),
+        }
+    }
+
+    /// Appends a tick, which also becomes the symbol's current `symbol_info_tick`.
+    pub fn push_tick(&self, symbol: &str, tick: SymbolTick) {
+        self.state()
+            .ticks
+            .entry(symbol.to_string())
+            .or_default()
+            .push(tick);
+    }
+
+    pub fn push_rate(&self, symbol: &str, timeframe: Timeframe, rate: SymbolRates) {
+        self.state()
+            .rates
+            .entry((symbol.to_string(), timeframe as i64))
+            .or_default()
+            .push(rate);
+    }
+
+    pub fn set_orders(&self, orders: Vec<Order>) {
+        self.state().orders = orders;
+    }
+
+    pub fn set_positions(&self, positions: Vec<Position>) {
+        self.state().positions = positions;
+    }
+
+    pub fn push_history_order(&self, order: Order) {
+        self.state().history_orders.push(order);
+    }
+
+    pub fn push_deal(&self, deal: Deals) {
+        self.state().deals.push(deal);
+    }
+
+    /// Replaces the market depth returned by `market_book_get` for `symbol`.
+    pub fn set_market_book(&self, symbol: &str, entries: Vec<BookEntry>) {
+        self.state().books.insert(symbol.to_string(), entries);
+    }
+
+    /// Whether `market_book_add` was called for `symbol` without a matching release.
+    pub fn is_book_subscribed(&self, symbol: &str) -> bool {
+        self.state().book_subscriptions.contains(symbol)
+    }
+
+    /// Makes the next call to `method` (e.g. `"order_send"`) fail with the given error.
+    ///
+    /// Calls queue up, so scripting the same method twice fails its next two calls.
+    pub fn fail_next(&self, method: &str, code: RuntimeError, message: &str) {
+        self.state()
+            .queued_errors
+            .entry(method.to_string())
+            .or_default()
+            .push_back(FishingLineError::runtime(code, message));
+    }
+
+    /// Fails every call with the given error until [`MockTerminal::clear_last_error`].
+    pub fn set_last_error(&self, code: RuntimeError, message: &str) {
+        let mut state = self.state();
+        state.last_error = (code, message.to_string());
+        state.sticky_error = Some(FishingLineError::runtime(code, message));
+    }
+
+    pub fn clear_last_error(&self) {
+        let mut state = self.state();
+        state.last_error = (RuntimeError::Ok, "Success".to_string());
+        state.sticky_error = None;
+    }
+
+    pub fn on_order_send(
+        &self,
+        handler: impl FnMut(&TradeRequestBuilder) -> MQLResult<TradeResult> + Send + 'static,
+    ) {
+        self.state().order_send_handler = Some(Box::new(handler));
+    }
+
+    pub fn on_order_check(
+        &self,
+        handler: impl FnMut(&TradeRequestBuilder) -> MQLResult<CheckResult> + Send + 'static,
+    ) {
+        self.state().order_check_handler = Some(Box::new(handler));
+    }
+
+    pub fn on_order_calc_margin(
+        &self,
+        handler: impl FnMut(TradeActionRequest, &str, f64, f64) -> MQLResult<f64> + Send + 'static,
+    ) {
+        self.state().calc_margin_handler = Some(Box::new(handler));
+    }
+
+    pub fn on_order_calc_profit(
+        &self,
+        handler: impl FnMut(TradeActionRequest, &str, f64, f64, f64) -> MQLResult<f64> + Send + 'static,
+    ) {
+        self.state().calc_profit_handler = Some(Box::new(handler));
+    }
+
+    /// Every request passed to `order_send`, oldest first.
+    pub fn sent_requests(&self) -> Vec<TradeRequestBuilder> {
+        self.state().sent_requests.clone()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state().connected
+    }
+}
+
+impl ConnectionTrait<MockTerminal> for MockTerminal {
+    fn initialize(self, _path: &str) -> MQLResult<MockTerminal> {
+        self.call("initialize", |state| {
+            state.connected = true;
+            Ok(())
+        })?;
+        Ok(self)
+    }
+
+    fn initialize_with_credentials(
+        self,
+        _path: &str,
+        credentials: AccountCredentials,
+        _timeout: i64,
+        _portable: Option<bool>,
+    ) -> MQLResult<MockTerminal> {
+        self.call("initialize_with_credentials", |state| {
+            if let Some(account) = &state.account {
+                if account.login != credentials.login {
+                    return Err(FishingLineError::runtime(
+                        RuntimeError::AuthFailed,
+                        "Authorization failed",
+                    ));
+                }
+            }
+            state.connected = true;
+            Ok(())
+        })?;
+        Ok(self)
+    }
+
+    fn login(&self, credentials: AccountCredentials, _timeout: Option<i64>) -> MQLResult<bool> {
+        self.call("login", |state| match &state.account {
+            Some(account) if account.login != credentials.login => Err(FishingLineError::runtime(
+                RuntimeError::AuthFailed,
+                "Authorization failed",
+            )),
+            _ => Ok(true),
+        })
+    }
+
+    fn shutdown(self) -> MQLResult<()> {
+        self.call("shutdown", |state| {
+            state.connected = false;
+            Ok(())
+        })
+    }
+}
+
+impl ErrorTrait for MockTerminal {
+    fn last_error(&self) -> MQLResult<(RuntimeError, String)> {
+        Ok(self.state().last_error.clone())
+    }
+}
+
+impl AccountInfoTrait for MockTerminal {
+    fn account_info(&self) -> MQLResult<AccountInfo> {
+        self.call("account_info", |state| {
+            state
+                .account
+                .clone()
+                .ok_or_else(|| not_found("No account scripted".to_string()))
+        })
+    }
+}
+
+impl TerminalInfoTrait for MockTerminal {
+    fn terminal_info(&self) -> MQLResult<TerminalInfo> {
+        self.call("terminal_info", |state| {
+            Ok(state.terminal_info.clone().unwrap_or_default())
+        })
+    }
+
+    fn version(&self) -> MQLResult<TerminalVersion> {
+        self.call("version", |state| {
+            Ok(state.version.clone().unwrap_or_default())
+        })
+    }
+}
+
+impl SymbolInfoTrait for MockTerminal {
+    fn symbols_total(&self) -> MQLResult<i32> {
+        self.call("symbols_total", |state| Ok(state.symbols.len() as i32))
+    }
+
+    fn symbols_get(&self, group: Option<&str>) -> MQLResult<Vec<SymbolInfo>> {
+        self.call("symbols_get", |state| {
+            Ok(state
+                .symbols
+                .iter()
+                .filter(|info| group.is_none_or(|group| group_matches(group, &info.name)))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn symbol_info(&self, symbol: &str) -> MQLResult<SymbolInfo> {
+        self.call("symbol_info", |state| state.symbol(symbol).cloned())
+    }
+
+    fn symbol_info_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
+        self.call("symbol_info_tick", |state| state.last_tick(symbol))
+    }
+
+    fn symbol_select(&self, symbol: &str, enable: Option<bool>) -> MQLResult<bool> {
+        self.call("symbol_select", |state| {
+            let enable = enable.unwrap_or(true);
+            let info = state
+                .symbols
+                .iter_mut()
+                .find(|info| info.name == symbol)
+                .ok_or_else(|| not_found(format!("Symbol `{}` not found", symbol)))?;
+            info.select = enable;
+            info.visible = enable;
+            Ok(true)
+        })
+    }
+}
+
+impl SymbolRatesTrait for MockTerminal {
+    fn copy_rates_from(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.call("copy_rates_from", |state| {
+            Ok(rates_from(
+                state.rates(symbol, timeframe)?,
+                date_from,
+                count,
+            ))
+        })
+    }
+
+    fn copy_rates_from_pos(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.call("copy_rates_from_pos", |state| {
+            Ok(rates_from_pos(
+                state.rates(symbol, timeframe)?,
+                start_pos,
+                count,
+            ))
+        })
+    }
+
+    fn copy_rates_range(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        self.call("copy_rates_range", |state| {
+            Ok(rates_range(
+                state.rates(symbol, timeframe)?,
+                date_from,
+                date_to,
+            ))
+        })
+    }
+}
+
+impl SymbolTicksTrait for MockTerminal {
+    fn copy_ticks_from(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        self.call("copy_ticks_from", |state| {
+            Ok(ticks_from(state.ticks(symbol)?, date_from, count, flags))
+        })
+    }
+
+    fn copy_ticks_range(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        self.call("copy_ticks_range", |state| {
+            Ok(ticks_range(state.ticks(symbol)?, date_from, date_to, flags))
+        })
+    }
+}
+
+impl OrderTrait for MockTerminal {
+    fn orders_total(&self) -> MQLResult<i64> {
+        self.call("orders_total", |state| Ok(state.orders.len() as i64))
+    }
+
+    fn orders_get(&self) -> MQLResult<Vec<Order>> {
+        self.call("orders_get", |state| Ok(state.orders.clone()))
+    }
+
+    /// Defaults to `volume * contract size * price / leverage`.
+    fn order_calc_margin(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+    ) -> MQLResult<f64> {
+        self.call("order_calc_margin", |state| {
+            if let Some(handler) = state.calc_margin_handler.as_mut() {
+                return handler(action, symbol, volume, price);
+            }
+            let contract_size = state.symbol(symbol)?.trade_contract_size;
+            let leverage = state
+                .account
+                .as_ref()
+                .map_or(1, |account| account.leverage.max(1));
+            Ok(volume * contract_size * price / leverage as f64)
+        })
+    }
+
+    /// Defaults to `(close - open) * volume * contract size`, negated for a sell (`action`
+    /// value 1). Other `action` values fail with `RuntimeError::InvalidParams`.
+    fn order_calc_profit(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price_open: f64,
+        price_close: f64,
+    ) -> MQLResult<f64> {
+        self.call("order_calc_profit", |state| {
+            if let Some(handler) = state.calc_profit_handler.as_mut() {
+                return handler(action, symbol, volume, price_open, price_close);
+            }
+            let direction = match action.value() {
+                0 => 1.0,
+                1 => -1.0,
+                value => {
+                    return Err(FishingLineError::runtime(
+                        RuntimeError::InvalidParams,
+                        format!("Invalid position direction `{}`", value),
+                    ))
+                }
+            };
+            let contract_size = state.symbol(symbol)?.trade_contract_size;
+            Ok(direction * (price_close - price_open) * volume * contract_size)
+        })
+    }
+
+    fn order_check(&self, request: &TradeRequestBuilder) -> MQLResult<CheckResult> {
+        self.call("order_check", |state| {
+            if let Some(handler) = state.order_check_handler.as_mut() {
+                return handler(request);
+            }
+            let account = state.account.clone().unwrap_or_default();
+            Ok(CheckResult {
+                retcode: ReturnCode::CHECKED,
+                balance: account.balance,
+                equity: account.equity,
+                profit: account.profit,
+                margin: account.margin,
+                margin_free: account.margin_free,
+                margin_level: account.margin_level,
+                comment: "Done".to_string(),
+                request: TradeRequest::from(request.clone()),
+            })
+        })
+    }
+
+    fn order_send(&self, request: TradeRequestBuilder) -> MQLResult<TradeResult> {
+        self.call("order_send", |state| {
+            state.sent_requests.push(request.clone());
+            if let Some(handler) = state.order_send_handler.as_mut() {
+                return handler(&request).and_then(check_trade_result);
+            }
+            let tick = match &request.symbol {
+                Some(symbol) => state.last_tick(symbol).unwrap_or_default(),
+                None => SymbolTick::default(),
+            };
+            let price = request.price.unwrap_or(match request.r#type {
+                Some(OrderType::SELL) => tick.bid,
+                _ => tick.ask,
+            });
+            let ticket = state.next_ticket();
+            Ok(TradeResult {
+                retcode: ReturnCode::DONE,
+                deal: ticket,
+                order: ticket,
+                volume: request.volume.unwrap_or_default(),
+                price,
+                bid: tick.bid,
+                ask: tick.ask,
+                comment: "Request executed".to_string(),
+                request_id: ticket as u64,
+                retcode_external: 0,
+                request: TradeRequest::from(request),
+            })
+        })
+    }
+}
+
+impl MarketBookTrait for MockTerminal {
+    fn market_book_add(&self, symbol: &str) -> MQLResult<bool> {
+        self.call("market_book_add", |state| {
+            state.symbol(symbol)?;
+            state.book_subscriptions.insert(symbol.to_string());
+            Ok(true)
+        })
+    }
+
+    fn market_book_get(&self, symbol: &str) -> MQLResult<MarketBook> {
+        self.call("market_book_get", |state| {
+            if !state.book_subscriptions.contains(symbol) {
+                return Err(FishingLineError::runtime(
+                    RuntimeError::Fail,
+                    format!("Market depth of `{}` is not subscribed", symbol),
+                ));
+            }
+            Ok(MarketBook {
+                entries: state.books.get(symbol).cloned().unwrap_or_default(),
+            })
+        })
+    }
+
+    fn market_book_release(&self, symbol: &str) -> MQLResult<bool> {
+        self.call("market_book_release", |state| {
+            Ok(state.book_subscriptions.remove(symbol))
+        })
+    }
+}
+
+impl PositionTrait for MockTerminal {
+    fn positions_total(&self) -> MQLResult<i64> {
+        self.call("positions_total", |state| Ok(state.positions.len() as i64))
+    }
+
+    fn positions_get(&self) -> MQLResult<Vec<Position>> {
+        self.call("positions_get", |state| Ok(state.positions.clone()))
+    }
+}
+
+impl HistoryTrait for MockTerminal {
+    fn history_orders_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.call("history_orders_total", |state| {
+            Ok(state
+                .history_orders
+                .iter()
+                .filter(|order| order.time_setup >= date_from && order.time_setup <= date_to)
+                .count() as i64)
+        })
+    }
+
+    fn history_orders_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Order>> {
+        self.call("history_orders_get", |state| {
+            Ok(state
+                .history_orders
+                .iter()
+                .filter(|order| order.time_setup >= date_from && order.time_setup <= date_to)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn history_deals_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        self.call("history_deals_total", |state| {
+            Ok(state
+                .deals
+                .iter()
+                .filter(|deal| deal.time >= date_from && deal.time <= date_to)
+                .count() as i64)
+        })
+    }
+
+    fn history_deals_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Deals>> {
+        self.call("history_deals_get", |state| {
+            Ok(state
+                .deals
+                .iter()
+                .filter(|deal| deal.time >= date_from && deal.time <= date_to)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn history_orders_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Order>> {
+        self.call("history_orders_get", |state| {
+            Ok(state
+                .history_orders
+                .iter()
+                .filter(|order| query.matches_order(order))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn history_deals_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Deals>> {
+        self.call("history_deals_get", |state| {
+            Ok(state
+                .deals
+                .iter()
+                .filter(|deal| query.matches_deal(deal))
+                .cloned()
+                .collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Local, TimeZone, Utc};
+
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_mock_as_terminal_object() {
+        let terminal: Box<dyn Terminal> = Box::new(
+            MockTerminal::new()
+                .with_account(AccountInfo {
+                    login: 42,
+                    balance: 1000.0,
+                    ..Default::default()
+                })
+                .with_symbol(eurusd()),
+        );
+        let account_info = terminal.account_info();
+        assert!(account_info.is_ok(), "Unable to get account info");
+        assert_eq!(account_info.unwrap().login, 42);
+        assert_eq!(terminal.symbols_total().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_mock_scripted_errors() {
+        let terminal = MockTerminal::new().with_symbol(eurusd());
+
+        terminal.fail_next("symbol_info", RuntimeError::InternalFailTimeout, "Timeout");
+        let symbol_info = terminal.symbol_info("EURUSD");
+        assert_eq!(
+            symbol_info.unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert_eq!(
+            terminal.last_error().unwrap().0,
+            RuntimeError::InternalFailTimeout
+        );
+        assert!(
+            terminal.symbol_info("EURUSD").is_ok(),
+            "Error was not one-off"
+        );
+
+        terminal.set_last_error(RuntimeError::AutoTradingDisabled, "AutoTrading disabled");
+        assert!(
+            terminal.positions_get().is_err(),
+            "Sticky error was ignored"
+        );
+        terminal.clear_last_error();
+        assert!(terminal.positions_get().is_ok(), "Unable to clear error");
+
+        assert_eq!(
+            terminal.symbol_info("GBPUSD").unwrap_err().code(),
+            Some(RuntimeError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_mock_fail_next_queues_per_method() {
+        let terminal = MockTerminal::new().with_position(Position::default());
+        terminal.fail_next(
+            "positions_get",
+            RuntimeError::InternalFailTimeout,
+            "Timeout",
+        );
+        terminal.fail_next("positions_get", RuntimeError::NoMemory, "Out of memory");
+
+        assert_eq!(terminal.positions_total().unwrap(), 1);
+        assert_eq!(
+            terminal.positions_get().unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert_eq!(
+            terminal.last_error().unwrap(),
+            (RuntimeError::InternalFailTimeout, "Timeout".to_string())
+        );
+        assert_eq!(
+            terminal.positions_get().unwrap_err().code(),
+            Some(RuntimeError::NoMemory)
+        );
+        assert_eq!(terminal.positions_get().unwrap().len(), 1);
+        assert_eq!(terminal.last_error().unwrap().0, RuntimeError::Ok);
+
+        let now = Utc::now();
+        terminal.fail_next(
+            "history_orders_total",
+            RuntimeError::InternalFailTimeout,
+            "Timeout",
+        );
+        terminal.fail_next(
+            "history_deals_total",
+            RuntimeError::InternalFailTimeout,
+            "Timeout",
+        );
+        assert!(terminal.history_orders_get(now, now).is_ok());
+        assert_eq!(
+            terminal.history_orders_total(now, now).unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert_eq!(
+            terminal.history_deals_total(now, now).unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert_eq!(terminal.history_deals_total(now, now).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_mock_sticky_last_error() {
+        let terminal = MockTerminal::new().with_symbol(eurusd());
+        terminal.set_last_error(RuntimeError::AutoTradingDisabled, "AutoTrading disabled");
+
+        for _ in 0..2 {
+            assert_eq!(
+                terminal.symbol_info("EURUSD").unwrap_err().code(),
+                Some(RuntimeError::AutoTradingDisabled)
+            );
+            assert_eq!(
+                terminal.orders_total().unwrap_err().code(),
+                Some(RuntimeError::AutoTradingDisabled)
+            );
+        }
+        assert_eq!(
+            terminal.last_error().unwrap(),
+            (
+                RuntimeError::AutoTradingDisabled,
+                "AutoTrading disabled".to_string()
+            )
+        );
+
+        terminal.fail_next("orders_total", RuntimeError::InternalFailTimeout, "Timeout");
+        assert_eq!(
+            terminal.orders_total().unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+
+        terminal.clear_last_error();
+        assert_eq!(terminal.last_error().unwrap().0, RuntimeError::Ok);
+        assert_eq!(terminal.orders_total().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_mock_order_check_handler() {
+        let terminal = MockTerminal::new().with_account(AccountInfo {
+            balance: 1000.0,
+            ..Default::default()
+        });
+        let request = TradeRequestBuilder::new()
+            .action(TradeActionRequest::DEAL)
+            .symbol("EURUSD".to_string());
+
+        let check = terminal.order_check(&request).unwrap();
+        assert_eq!(
+            (check.retcode, check.balance),
+            (ReturnCode::CHECKED, 1000.0)
+        );
+
+        terminal.on_order_check(|request| {
+            Ok(CheckResult {
+                retcode: ReturnCode::NoMoney,
+                comment: request.symbol.clone().unwrap_or_default(),
+                ..Default::default()
+            })
+        });
+        let check = terminal.order_check(&request).unwrap();
+        assert_eq!(check.retcode, ReturnCode::NoMoney);
+        assert_eq!(check.comment, "EURUSD");
+        assert!(terminal.sent_requests().is_empty());
+    }
+
+    #[test]
+    fn test_mock_order_calc_handlers() {
+        let terminal = MockTerminal::new()
+            .with_account(AccountInfo {
+                leverage: 100,
+                ..Default::default()
+            })
+            .with_symbol(eurusd());
+        let buy = TradeActionRequest::from_value(0);
+        let sell = TradeActionRequest::from_value(1);
+
+        let margin = terminal.order_calc_margin(buy, "EURUSD", 1.0, 1.1).unwrap();
+        assert!((margin - 1100.0).abs() < 1e-9);
+        let profit = terminal
+            .order_calc_profit(buy, "EURUSD", 1.0, 1.1, 1.1010)
+            .unwrap();
+        assert!((profit - 100.0).abs() < 1e-6);
+        let profit = terminal
+            .order_calc_profit(sell, "EURUSD", 1.0, 1.1, 1.1010)
+            .unwrap();
+        assert!((profit + 100.0).abs() < 1e-6);
+        assert_eq!(
+            terminal
+                .order_calc_profit(
+                    TradeActionRequest::from_value(5),
+                    "EURUSD",
+                    1.0,
+                    1.1,
+                    1.1010
+                )
+                .unwrap_err()
+                .code(),
+            Some(RuntimeError::InvalidParams)
+        );
+
+        terminal.on_order_calc_margin(|action, symbol, volume, price| {
+            assert_eq!((action.value(), symbol), (1, "EURUSD"));
+            Ok(volume * price)
+        });
+        terminal.on_order_calc_profit(|action, _, volume, price_open, price_close| {
+            let direction = if action.value() == 1 { -1.0 } else { 1.0 };
+            Ok(direction * (price_close - price_open) * volume)
+        });
+        assert_eq!(
+            terminal
+                .order_calc_margin(sell, "EURUSD", 2.0, 1.5)
+                .unwrap(),
+            3.0
+        );
+        assert_eq!(
+            terminal
+                .order_calc_profit(sell, "EURUSD", 2.0, 1.5, 1.0)
+                .unwrap(),
+            1.0
+        );
+        assert_eq!(
+            terminal
+                .order_calc_profit(sell, "GBPUSD", 1.0, 1.0, 1.0)
+                .unwrap(),
+            0.0,
+            "Handler was bypassed for an unknown symbol"
+        );
+    }
+
+    #[test]
+    fn test_mock_symbols_get_group() {
+        let terminal = MockTerminal::new()
+            .with_symbol(eurusd())
+            .with_symbol(SymbolInfo {
+                name: "GBPUSD".to_string(),
+                ..Default::default()
+            })
+            .with_symbol(SymbolInfo {
+                name: "BTCUSD".to_string(),
+                ..Default::default()
+            });
+        let symbols = terminal.symbols_get(Some("*USD,!BTC*")).unwrap();
+        let names: Vec<String> = symbols.into_iter().map(|symbol| symbol.name).collect();
+        assert_eq!(names, vec!["EURUSD", "GBPUSD"]);
+    }
+
+    #[test]
+    fn test_mock_order_send() {
+        let terminal = MockTerminal::new().with_symbol(eurusd());
+        let request = TradeRequestBuilder::new()
+            .action(TradeActionRequest::DEAL)
+            .symbol("EURUSD".to_string())
+            .volume(0.1)
+            .r#type(OrderType::BUY);
+
+        let result = terminal.order_send(request).unwrap();
+        assert_eq!(result.retcode, ReturnCode::DONE);
+        assert_eq!(result.price, 1.1002);
+        assert_eq!(terminal.sent_requests().len(), 1);
+
+        terminal.on_order_send(|request| {
+            Ok(TradeResult {
+                retcode: ReturnCode::REQUOTE,
+                request: TradeRequest::from(request.clone()),
+                ..Default::default()
+            })
+        });
+        let result = terminal.order_send(TradeRequestBuilder::new().symbol("EURUSD".to_string()));
+        assert_eq!(result.unwrap_err().retcode(), Some(ReturnCode::REQUOTE));
+    }
+
+    #[test]
+    fn test_mock_copy_rates() {
+        let rates = (0..10)
+            .map(|hour| SymbolRates {
+                time: Local
+                    .with_ymd_and_hms(2024, 7, 8, hour, 0, 0)
+                    .unwrap()
+                    .to_utc(),
+                close: hour as f64,
+                ..Default::default()
+            })
+            .collect();
+        let terminal = MockTerminal::new().with_rates("EURUSD", Timeframe::H1, rates);
+
+        let latest = terminal
+            .copy_rates_from_pos("EURUSD", Timeframe::H1, 0, 3)
+            .unwrap();
+        let closes: Vec<f64> = latest.iter().map(|rate| rate.close).collect();
+        assert_eq!(closes, vec![7.0, 8.0, 9.0]);
+
+        let from = terminal
+            .copy_rates_from(
+                "EURUSD",
+                Timeframe::H1,
+                Local
+                    .with_ymd_and_hms(2024, 7, 8, 4, 30, 0)
+                    .unwrap()
+                    .to_utc(),
+                2,
+            )
+            .unwrap();
+        let closes: Vec<f64> = from.iter().map(|rate| rate.close).collect();
+        assert_eq!(closes, vec![3.0, 4.0]);
+
+        assert!(
+            terminal
+                .copy_rates_from_pos("EURUSD", Timeframe::D1, 0, 3)
+                .is_err(),
+            "Unscripted timeframe returned rates"
+        );
+    }
+}
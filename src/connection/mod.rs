@@ -1 +1,10 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod bridge;
+pub mod environment;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod mock;
+pub mod numpy;
 pub mod python;
+pub mod simulated;
@@ -0,0 +1,339 @@
+//! Reads the numpy structured arrays returned by `copy_rates_*` and `copy_ticks_*`.
+//!
+//! Records are decoded straight from the array's memory through the buffer protocol,
+//! using the field offsets of its `dtype`, so neither numpy nor pandas has to be
+//! imported and no Python object is created per record.
+
+use std::mem::MaybeUninit;
+
+use chrono::DateTime;
+use pyo3::ffi;
+use pyo3::prelude::*;
+
+use crate::prelude::*;
+
+/// Scalar layout of one field of a structured array.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Int,
+    Unsigned,
+    Float,
+}
+
+/// A field of a structured array, resolved from its `dtype`.
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    offset: usize,
+    size: usize,
+    kind: Kind,
+    big_endian: bool,
+}
+
+impl Field {
+    fn bytes<const N: usize>(&self, record: &[u8]) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&record[self.offset..self.offset + N]);
+        if self.big_endian == cfg!(target_endian = "little") {
+            bytes.reverse();
+        }
+        bytes
+    }
+
+    /// Reads the field as an integer, truncating floats.
+    pub fn i64(&self, record: &[u8]) -> i64 {
+        match (self.kind, self.size) {
+            (Kind::Int, 1) => i8::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Int, 2) => i16::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Int, 4) => i32::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Int, _) => i64::from_ne_bytes(self.bytes(record)),
+            (Kind::Unsigned, 1) => u8::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Unsigned, 2) => u16::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Unsigned, 4) => u32::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Unsigned, _) => u64::from_ne_bytes(self.bytes(record)) as i64,
+            (Kind::Float, _) => self.f64(record) as i64,
+        }
+    }
+
+    /// Reads the field as a float.
+    pub fn f64(&self, record: &[u8]) -> f64 {
+        match (self.kind, self.size) {
+            (Kind::Float, 4) => f32::from_ne_bytes(self.bytes(record)) as f64,
+            (Kind::Float, _) => f64::from_ne_bytes(self.bytes(record)),
+            (Kind::Unsigned, _) => self.i64(record) as u64 as f64,
+            (Kind::Int, _) => self.i64(record) as f64,
+        }
+    }
+}
+
+/// Records that can be decoded from a numpy structured array.
+pub trait FromStructuredArray: Sized {
+    /// Field names read from every record, in the order they are passed to `from_record`.
+    const FIELDS: &'static [&'static str];
+
+    fn from_record(fields: &[Field], record: &[u8]) -> Self;
+}
+
+impl FromStructuredArray for SymbolRates {
+    const FIELDS: &'static [&'static str] = &[
+        "time",
+        "open",
+        "high",
+        "low",
+        "close",
+        "tick_volume",
+        "spread",
+        "real_volume",
+    ];
+
+    fn from_record(fields: &[Field], record: &[u8]) -> Self {
+        SymbolRates {
+            time: DateTime::from_timestamp(fields[0].i64(record), 0).unwrap_or_default(),
+            open: fields[1].f64(record),
+            high: fields[2].f64(record),
+            low: fields[3].f64(record),
+            close: fields[4].f64(record),
+            tick_volume: fields[5].i64(record) as isize,
+            spread: fields[6].f64(record),
+            real_volume: fields[7].i64(record) as isize,
+        }
+    }
+}
+
+impl FromStructuredArray for SymbolTick {
+    const FIELDS: &'static [&'static str] = &[
+        "time",
+        "bid",
+        "ask",
+        "last",
+        "volume",
+        "time_msc",
+        "flags",
+        "volume_real",
+    ];
+
+    fn from_record(fields: &[Field], record: &[u8]) -> Self {
+        SymbolTick {
+            time: fields[0].i64(record),
+            bid: fields[1].f64(record),
+            ask: fields[2].f64(record),
+            last: fields[3].f64(record),
+            volume: fields[4].f64(record),
+            time_msc: fields[5].i64(record),
+            flags: fields[6].i64(record),
+            volume_real: fields[7].f64(record),
+        }
+    }
+}
+
+/// A contiguous buffer borrowed from a Python object, released on drop.
+struct Buffer {
+    view: ffi::Py_buffer,
+}
+
+impl Buffer {
+    fn get(array: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let mut view = MaybeUninit::<ffi::Py_buffer>::uninit();
+        // SAFETY: the GIL is held, `view` is only read after a successful call.
+        let status = unsafe {
+            ffi::PyObject_GetBuffer(array.as_ptr(), view.as_mut_ptr(), ffi::PyBUF_C_CONTIGUOUS)
+        };
+        if status == -1 {
+            return Err(PyErr::fetch(array.py()));
+        }
+        Ok(Buffer {
+            // SAFETY: `PyObject_GetBuffer` succeeded and filled `view`.
+            view: unsafe { view.assume_init() },
+        })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        if self.view.buf.is_null() || self.view.len <= 0 {
+            return &[];
+        }
+        // SAFETY: the exporter guarantees `len` readable bytes at `buf` until release.
+        unsafe { std::slice::from_raw_parts(self.view.buf as *const u8, self.view.len as usize) }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        // SAFETY: the view was obtained by `PyObject_GetBuffer` and is released once.
+        Python::with_gil(|_| unsafe { ffi::PyBuffer_Release(&mut self.view) });
+    }
+}
+
+/// Unqualified name of a record type, used in conversion errors.
+fn record_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// A numpy structured array whose records are read in place.
+pub(crate) struct StructuredArray<'py> {
+    dtype: Bound<'py, PyAny>,
+    itemsize: usize,
+    buffer: Buffer,
+    record: &'static str,
+}
+
+impl<'py> StructuredArray<'py> {
+    /// Borrows the memory of `array`, whose records decode into the type named `record`.
+    pub(crate) fn new(array: &Bound<'py, PyAny>, record: &'static str) -> MQLResult<Self> {
+        let dtype = array.getattr("dtype")?;
+        let itemsize: usize = dtype.getattr("itemsize")?.extract()?;
+        let buffer = Buffer::get(array)?;
+        let length = buffer.bytes().len();
+        if itemsize == 0 || length % itemsize != 0 {
+            return Err(FishingLineError::Conversion {
+                field: record.to_string(),
+                message: format!(
+                    "buffer of {} bytes is not made of {} byte records",
+                    length, itemsize
+                ),
+            });
+        }
+        Ok(StructuredArray {
+            dtype,
+            itemsize,
+            buffer,
+            record,
+        })
+    }
+
+    pub(crate) fn records(&self) -> std::slice::ChunksExact<'_, u8> {
+        self.buffer.bytes().chunks_exact(self.itemsize)
+    }
+
+    /// Resolves field `name` from the array's `dtype`.
+    pub(crate) fn field(&self, name: &str) -> MQLResult<Field> {
+        let conversion = |message: String| FishingLineError::Conversion {
+            field: format!("{}.{}", self.record, name),
+            message,
+        };
+        let field = self
+            .dtype
+            .getattr("fields")?
+            .get_item(name)
+            .map_err(|_| conversion("missing from the array dtype".to_string()))?;
+        let field_dtype = field.get_item(0)?;
+        let offset: usize = field.get_item(1)?.extract()?;
+        let size: usize = field_dtype.getattr("itemsize")?.extract()?;
+        let kind = match field_dtype.getattr("kind")?.extract::<String>()?.as_str() {
+            "i" => Kind::Int,
+            "u" | "b" => Kind::Unsigned,
+            "f" => Kind::Float,
+            other => return Err(conversion(format!("unsupported dtype kind `{}`", other))),
+        };
+        if !matches!(
+            (kind, size),
+            (Kind::Float, 4 | 8) | (Kind::Int | Kind::Unsigned, 1 | 2 | 4 | 8)
+        ) {
+            return Err(conversion(format!("unsupported field size {}", size)));
+        }
+        if offset + size > self.itemsize {
+            return Err(conversion("field lies outside of the record".to_string()));
+        }
+        let big_endian = match field_dtype
+            .getattr("byteorder")?
+            .extract::<String>()?
+            .as_str()
+        {
+            ">" => true,
+            "<" => false,
+            _ => cfg!(target_endian = "big"),
+        };
+        Ok(Field {
+            offset,
+            size,
+            kind,
+            big_endian,
+        })
+    }
+}
+
+/// Decodes every record of a numpy structured array.
+pub fn from_structured_array<T: FromStructuredArray>(
+    array: &Bound<'_, PyAny>,
+) -> MQLResult<Vec<T>> {
+    let array = StructuredArray::new(array, record_name::<T>())?;
+    let fields = T::FIELDS
+        .iter()
+        .map(|name| array.field(name))
+        .collect::<MQLResult<Vec<_>>>()?;
+    Ok(array
+        .records()
+        .map(|record| T::from_record(&fields, record))
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use pyo3::{prelude::*, types::PyDict};
+
+    use super::from_structured_array;
+    use crate::prelude::*;
+
+    /// Builds a `bytes` object carrying a numpy-like `dtype`, laid out like MT5's tick array.
+    pub(crate) fn tick_array<'py>(py: Python<'py>, fields: &str) -> Bound<'py, PyAny> {
+        let globals = PyDict::new_bound(py);
+        py.run_bound(
+            &format!(
+                r#"
+import struct, types
+
+def scalar(kind, size, byteorder="<"):
+    return types.SimpleNamespace(kind=kind, itemsize=size, byteorder=byteorder)
+
+class Array(bytes):
+    dtype = types.SimpleNamespace(fields={{{}}}, itemsize=60)
+
+array = Array(
+    struct.pack("<qdddQqId", 1720447200, 1.1, 1.2, 0.0, 0, 1720447200250, 6, 0.0)
+    + struct.pack("<qdddQqId", 1720447201, 1.3, 1.4, 0.0, 2, 1720447201500, 2, 0.5)
+)
+"#,
+                fields
+            ),
+            Some(&globals),
+            None,
+        )
+        .unwrap();
+        globals.get_item("array").unwrap().unwrap()
+    }
+
+    pub(crate) const TICK_FIELDS: &str = r#"
+        "time": (scalar("i", 8), 0),
+        "bid": (scalar("f", 8), 8),
+        "ask": (scalar("f", 8), 16),
+        "last": (scalar("f", 8), 24),
+        "volume": (scalar("u", 8), 32),
+        "time_msc": (scalar("i", 8), 40),
+        "flags": (scalar("u", 4), 48),
+        "volume_real": (scalar("f", 8), 52),
+    "#;
+
+    #[test]
+    fn test_decode_ticks() {
+        let ticks: Vec<SymbolTick> =
+            Python::with_gil(|py| from_structured_array(&tick_array(py, TICK_FIELDS)).unwrap());
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].time, 1720447200);
+        assert_eq!(ticks[0].bid, 1.1);
+        assert_eq!(ticks[0].flags, 6);
+        assert_eq!(ticks[1].time_msc, 1720447201500);
+        assert_eq!(ticks[1].volume, 2.0);
+        assert_eq!(ticks[1].volume_real, 0.5);
+    }
+
+    #[test]
+    fn test_missing_field_is_named() {
+        let fields = TICK_FIELDS.replace(r#""flags": (scalar("u", 4), 48),"#, "");
+        let error = Python::with_gil(|py| {
+            from_structured_array::<SymbolTick>(&tick_array(py, &fields)).unwrap_err()
+        });
+        match error {
+            FishingLineError::Conversion { field, .. } => assert_eq!(field, "SymbolTick.flags"),
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+}
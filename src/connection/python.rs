@@ -1,85 +1,264 @@
+#[cfg(feature = "arrow")]
+use crate::connection::arrow::{rates_batch, ticks_batch};
+use crate::connection::numpy::from_structured_array;
+use crate::error::check_trade_result;
 use crate::prelude::*;
-use chrono::{DateTime, Local};
-use chrono::{Datelike, Timelike};
+use crate::time::ServerTime;
+use chrono::{DateTime, Utc};
 use pyo3::prelude::*;
-use pyo3::types::{PyDateTime, PyDict};
+use pyo3::types::{timezone_utc_bound, PyDateTime, PyDict, PyTuple};
 use pyo3::PyObject;
 use pyo3::Python;
 
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
 pub struct MT5PythonConnection {
-    runtime: Option<PyObject>,
+    runtime: PyObject,
+    timezone: ServerTimezone,
 }
 
-impl Default for MT5PythonConnection {
-    fn default() -> Self {
-        Self::new()
+impl MT5PythonConnection {
+    /// Connects to the MetaTrader5 module of the environment detected by
+    /// [`PythonEnvConfig::from_env`].
+    pub fn new() -> MQLResult<Self> {
+        MT5PythonConnection::with_config(&PythonEnvConfig::from_env())
     }
-}
 
-impl MT5PythonConnection {
-    pub fn new() -> Self {
-        let result: PyResult<MT5PythonConnection> = Python::with_gil(|py| {
-            let sys = py
-                .import_bound("sys")
-                .expect("Unable to import `sys` module");
-            let path = sys.getattr("path").expect("Unable to get `path` attribute");
-            let poetry_environment_path = format!(
-                "{}\\lib\\site-packages\\",
-                std::env::var("POETRY_ENVIRONMENT").expect("Unable to find `POETRY_ENVIRONMENT`")
-            );
-            path.getattr("append")
-                .expect("Unable to get `append` attribute")
-                .call1((poetry_environment_path,))
-                .expect("Unable to call `append` method");
+    /// Connects to the MetaTrader5 module found through `config`.
+    ///
+    /// Fails with [`FishingLineError::ModuleImport`] naming the module that could
+    /// not be imported.
+    pub fn with_config(config: &PythonEnvConfig) -> MQLResult<Self> {
+        Python::with_gil(|py| {
+            let runtime = config.import(py)?;
             Ok(MT5PythonConnection {
-                runtime: Some(
-                    py.import_bound("MetaTrader5")
-                        .expect("Unable to find `MetaTrader5` module")
-                        .extract()
-                        .expect("Unable to extract `MetaTrader5` module"),
-                ),
+                runtime: runtime.into_py(py),
+                timezone: ServerTimezone::default(),
             })
-        });
-        result.expect("Unable to initialize MetaTrader5")
+        })
+    }
+
+    /// Sets the trade server time zone used to convert time arguments and results.
+    ///
+    /// Without it, server times are treated as UTC.
+    pub fn with_server_timezone(mut self, timezone: ServerTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn server_timezone(&self) -> ServerTimezone {
+        self.timezone
     }
+
+    /// Builds the UTC-aware `datetime` MT5 expects for `date`, in server time.
+    fn server_datetime<'py>(
+        &self,
+        py: Python<'py>,
+        date: DateTime<Utc>,
+    ) -> MQLResult<Bound<'py, PyDateTime>> {
+        let server = self.timezone.to_server(&date);
+        Ok(PyDateTime::from_timestamp_bound(
+            py,
+            server.timestamp_millis() as f64 / 1000.0,
+            Some(&timezone_utc_bound(py)),
+        )?)
+    }
+
+    /// Calls one of the `copy_*` methods and decodes the numpy array it returns.
+    fn copy_array<T>(
+        &self,
+        method: &str,
+        args: impl FnOnce(Python<'_>) -> MQLResult<Py<PyTuple>>,
+        decode: impl FnOnce(&Bound<'_, PyAny>) -> MQLResult<T>,
+    ) -> MQLResult<T> {
+        Python::with_gil(|py| {
+            let array = self.invoke(py, method, args(py)?.into_bound(py), None)?;
+            decode(&array)
+        })
+    }
+
+    fn rates(&self, array: &Bound<'_, PyAny>) -> MQLResult<Vec<SymbolRates>> {
+        from_structured_array(array).map(|rates: Vec<SymbolRates>| rates.into_utc(&self.timezone))
+    }
+
+    fn ticks(&self, array: &Bound<'_, PyAny>) -> MQLResult<Vec<SymbolTick>> {
+        from_structured_array(array).map(|ticks: Vec<SymbolTick>| ticks.into_utc(&self.timezone))
+    }
+
+    fn rates_from_args(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<Py<PyTuple>> {
+        let date_from = self.server_datetime(py, date_from)?;
+        Ok((symbol, timeframe as i64, date_from, count).into_py(py))
+    }
+
+    fn rates_range_args(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Py<PyTuple>> {
+        let date_from = self.server_datetime(py, date_from)?;
+        let date_to = self.server_datetime(py, date_to)?;
+        Ok((symbol, timeframe as i64, date_from, date_to).into_py(py))
+    }
+
+    fn ticks_from_args(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Py<PyTuple>> {
+        let date_from = self.server_datetime(py, date_from)?;
+        Ok((symbol, date_from, count, flags as i64).into_py(py))
+    }
+
+    fn ticks_range_args(
+        &self,
+        py: Python<'_>,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Py<PyTuple>> {
+        let date_from = self.server_datetime(py, date_from)?;
+        let date_to = self.server_datetime(py, date_to)?;
+        Ok((symbol, date_from, date_to, flags as i64).into_py(py))
+    }
+
+    /// Arguments of `history_orders_get`/`history_deals_get` for `query`.
+    fn history_args<'py>(
+        &self,
+        py: Python<'py>,
+        query: &HistoryQuery,
+    ) -> MQLResult<(Py<PyTuple>, Bound<'py, PyDict>)> {
+        let kwargs = PyDict::new_bound(py);
+        let args = match query {
+            HistoryQuery::Range {
+                date_from,
+                date_to,
+                group,
+            } => {
+                if let Some(group) = group {
+                    kwargs.set_item("group", group)?;
+                }
+                (
+                    self.server_datetime(py, *date_from)?,
+                    self.server_datetime(py, *date_to)?,
+                )
+                    .into_py(py)
+            }
+            HistoryQuery::Ticket(ticket) => {
+                kwargs.set_item("ticket", ticket)?;
+                PyTuple::empty_bound(py).unbind()
+            }
+            HistoryQuery::Position(position) => {
+                kwargs.set_item("position", position)?;
+                PyTuple::empty_bound(py).unbind()
+            }
+        };
+        Ok((args, kwargs))
+    }
+
+    /// Calls `method` on the MetaTrader5 module.
+    ///
+    /// A `None` result or a negative `last_error` code is returned as a
+    /// [`FishingLineError::Runtime`] error.
+    fn invoke<'py>(
+        &self,
+        py: Python<'py>,
+        method: &str,
+        args: impl IntoPy<Py<PyTuple>>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> MQLResult<Bound<'py, PyAny>> {
+        let result = self.runtime.bind(py).call_method(method, args, kwargs)?;
+        let (code, message) = self.last_error_with(py)?;
+        if code.value().is_negative() {
+            return Err(FishingLineError::Runtime { code, message });
+        }
+        if result.is_none() {
+            return Err(FishingLineError::Runtime {
+                code: RuntimeError::Fail,
+                message: format!("`{}` returned no data", method),
+            });
+        }
+        Ok(result)
+    }
+
+    fn last_error_with(&self, py: Python<'_>) -> MQLResult<(RuntimeError, String)> {
+        let error = self.runtime.bind(py).call_method0("last_error")?;
+        extract(&error, "last_error")
+    }
+}
+
+fn extract<'py, T: FromPyObject<'py>>(value: &Bound<'py, PyAny>, context: &str) -> MQLResult<T> {
+    value
+        .extract()
+        .map_err(|error| FishingLineError::conversion(context, error))
+}
+
+/// Converts a tuple of named tuples into a list of records.
+fn records<'py, T: FromPyObject<'py>>(
+    value: &Bound<'py, PyAny>,
+    context: &str,
+) -> MQLResult<Vec<T>> {
+    value
+        .iter()?
+        .map(|item| extract(&item?.call_method0("_asdict")?, context))
+        .collect()
+}
+
+/// Builds the `ticket=`, `symbol=` or `group=` argument of `orders_get`/`positions_get`.
+///
+/// The terminal honours a single filter per call, so the most specific one is sent and
+/// the others are applied by [`TradeFilter`] on the returned records.
+fn trade_filter_kwargs<'py>(
+    py: Python<'py>,
+    filter: &TradeFilter,
+) -> MQLResult<Bound<'py, PyDict>> {
+    let kwargs = PyDict::new_bound(py);
+    if let Some(ticket) = filter.ticket {
+        kwargs.set_item("ticket", ticket)?;
+    } else if let Some(symbol) = &filter.symbol {
+        kwargs.set_item("symbol", symbol)?;
+    } else if let Some(group) = &filter.group {
+        kwargs.set_item("group", group)?;
+    }
+    Ok(kwargs)
+}
+
+/// Converts an `order_check`/`order_send` result, including its nested request, into a dict.
+fn trade_dict<'py>(value: &Bound<'py, PyAny>) -> MQLResult<Bound<'py, PyAny>> {
+    let trade_result = value.call_method0("_asdict")?;
+    let trade_request = trade_result
+        .call_method1("get", ("request",))?
+        .call_method0("_asdict")?;
+    trade_result.set_item("request", trade_request)?;
+    Ok(trade_result)
 }
 
 impl ConnectionTrait<MT5PythonConnection> for MT5PythonConnection {
     fn login(&self, credentials: AccountCredentials, timeout: Option<i64>) -> MQLResult<bool> {
-        let result: PyResult<bool> = Python::with_gil(|py| {
+        Python::with_gil(|py| {
             let kwargs = PyDict::new_bound(py);
-            kwargs
-                .set_item("password", credentials.password)
-                .expect("Unable to set `password`");
-            kwargs
-                .set_item("server", credentials.server)
-                .expect("Unable to set `server`");
+            kwargs.set_item("password", credentials.password)?;
+            kwargs.set_item("server", credentials.server)?;
             if let Some(timeout) = timeout {
-                kwargs
-                    .set_item("timeout", timeout)
-                    .expect("Unable to set `timeout`");
+                kwargs.set_item("timeout", timeout)?;
             }
-            let runtime = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module");
-            let runtime = runtime
-                .getattr(py, "login")
-                .expect("Unable to find `login` method")
-                .call_bound(py, (credentials.login,), Some(&kwargs))
-                .expect("Unable to call `login` method");
-            Ok(runtime
-                .extract(py)
-                .expect("Unable to extract `login` result"))
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to login"))
+            let logged_in = self.invoke(py, "login", (credentials.login,), Some(&kwargs))?;
+            extract(&logged_in, "login")
+        })
     }
 
     fn initialize_with_credentials(
@@ -89,79 +268,38 @@ impl ConnectionTrait<MT5PythonConnection> for MT5PythonConnection {
         timeout: i64,
         portable: Option<bool>,
     ) -> MQLResult<MT5PythonConnection> {
-        let result: PyResult<bool> = Python::with_gil(|py| {
+        let initialized: bool = Python::with_gil(|py| {
             let kwargs = PyDict::new_bound(py);
-            kwargs
-                .set_item("login", credentials.login)
-                .expect("Unable to set `login`");
-            kwargs
-                .set_item("password", credentials.password)
-                .expect("Unable to set `password`");
-            kwargs
-                .set_item("server", credentials.server)
-                .expect("Unable to set `server`");
-            kwargs
-                .set_item("timeout", timeout)
-                .expect("Unable to set `timeout`");
+            kwargs.set_item("login", credentials.login)?;
+            kwargs.set_item("password", credentials.password)?;
+            kwargs.set_item("server", credentials.server)?;
+            kwargs.set_item("timeout", timeout)?;
             if let Some(portable) = portable {
-                kwargs
-                    .set_item("portable", portable)
-                    .expect("Unable to set `portable`");
+                kwargs.set_item("portable", portable)?;
             }
-            let runtime = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module");
-            let runtime = runtime
-                .getattr(py, "initialize")
-                .expect("Unable to find `initialize` method")
-                .call_bound(py, (path,), Some(&kwargs))
-                .expect("Unable to call `initialize` method");
-            Ok(runtime
-                .extract(py)
-                .expect("Unable to extract `initialize` result"))
-        });
-
-        let (code, message) = self.last_error();
+            let initialized = self.invoke(py, "initialize", (path,), Some(&kwargs))?;
+            extract(&initialized, "initialize")
+        })?;
 
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        if !result.expect("Unable to initialize MetaTrader5") {
-            return Err((
+        if !initialized {
+            return Err(FishingLineError::runtime(
                 RuntimeError::AuthFailed,
-                "Failed to initialize MetaTrader5".to_string(),
+                "Failed to initialize MetaTrader5",
             ));
         }
 
         Ok(self)
     }
     fn initialize(self, path: &str) -> MQLResult<Self> {
-        let result: PyResult<bool> = Python::with_gil(|py| {
-            let runtime = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module");
-            let runtime = runtime
-                .getattr(py, "initialize")
-                .expect("Unable to find `initialize` method")
-                .call1(py, (path,))
-                .expect("Unable to call `initialize` method")
-                .extract(py);
-            Ok(runtime.expect("Unable to extract `initialize` result"))
-        });
-
-        let (code, message) = self.last_error();
+        let initialized: bool = Python::with_gil(|py| {
+            let initialized = self.invoke(py, "initialize", (path,), None)?;
+            extract(&initialized, "initialize")
+        })?;
 
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        if !result.expect("Unable to initialize MetaTrader5") {
-            return Err((
+        if !initialized {
+            return Err(FishingLineError::runtime(
                 RuntimeError::AuthFailed,
-                "Failed to initialize MetaTrader5".to_string(),
+                "Failed to initialize MetaTrader5",
             ));
         }
 
@@ -169,252 +307,95 @@ impl ConnectionTrait<MT5PythonConnection> for MT5PythonConnection {
     }
 
     fn shutdown(self) -> MQLResult<()> {
-        let _result: PyResult<()> = Python::with_gil(|py| {
-            let _runtime = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "shutdown")
-                .expect("Unable to find `shutdown` method")
-                .call0(py)
-                .expect("Unable to call `shutdown` method");
+        Python::with_gil(|py| {
+            self.runtime.bind(py).call_method0("shutdown")?;
             Ok(())
-        });
-
-        Ok(())
+        })
     }
 }
 
 impl ErrorTrait for MT5PythonConnection {
-    fn last_error(&self) -> crate::prelude::MQLError {
-        let result: PyResult<MQLError> = Python::with_gil(|py| {
-            let error = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "last_error")
-                .expect("Unable to call `last_error` method")
-                .extract(py)
-                .expect("Unable to extract `last_error` result");
-            Ok(error)
-        });
-        result.expect("Unable to get last error")
+    fn last_error(&self) -> MQLResult<(RuntimeError, String)> {
+        Python::with_gil(|py| self.last_error_with(py))
     }
 }
 
 impl AccountInfoTrait for MT5PythonConnection {
     fn account_info(&self) -> crate::prelude::MQLResult<crate::schemas::AccountInfo> {
-        let result: PyResult<AccountInfo> = Python::with_gil(|py| {
+        Python::with_gil(|py| {
             let account = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "account_info")
-                .expect("Unable to call `account_info` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method")
-                .extract(py)
-                .expect("Unable to extract `account_info` result");
-            Ok(account)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get account info"))
+                .invoke(py, "account_info", (), None)?
+                .call_method0("_asdict")?;
+            extract(&account, "account_info")
+        })
     }
 }
 
 impl TerminalInfoTrait for MT5PythonConnection {
     fn terminal_info(&self) -> crate::prelude::MQLResult<crate::schemas::TerminalInfo> {
-        let result: PyResult<TerminalInfo> = Python::with_gil(|py| {
+        Python::with_gil(|py| {
             let terminal = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "terminal_info")
-                .expect("Unable to call `terminal_info` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method")
-                .extract(py)
-                .expect("Unable to extract `terminal_info` result");
-            Ok(terminal)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get terminal info"))
+                .invoke(py, "terminal_info", (), None)?
+                .call_method0("_asdict")?;
+            extract(&terminal, "terminal_info")
+        })
     }
     fn version(&self) -> MQLResult<crate::schemas::TerminalVersion> {
-        let result: PyResult<TerminalVersion> = Python::with_gil(|py| {
-            let (terminal_version, build, build_date) = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "version")
-                .expect("Unable to call `version` method")
-                .extract::<(i64, i64, String)>(py)
-                .expect("Unable to extract `version` result");
+        Python::with_gil(|py| {
+            let version = self.invoke(py, "version", (), None)?;
+            let (terminal_version, build, build_date) = extract(&version, "version")?;
             Ok(TerminalVersion {
                 terminal_version,
                 build,
                 build_date,
             })
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get terminal version"))
+        })
     }
 }
 
 impl SymbolInfoTrait for MT5PythonConnection {
     fn symbols_total(&self) -> MQLResult<i32> {
-        let result: PyResult<i32> = Python::with_gil(|py| {
-            let total = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "symbols_total")
-                .expect("Unable to call `symbols_total` method")
-                .extract(py)
-                .expect("Unable to extract `symbols_total` result");
-            Ok(total)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get total symbols"))
+        Python::with_gil(|py| {
+            let total = self.invoke(py, "symbols_total", (), None)?;
+            extract(&total, "symbols_total")
+        })
     }
     fn symbol_info(&self, symbol: &str) -> MQLResult<SymbolInfo> {
-        let result: PyResult<SymbolInfo> = Python::with_gil(|py| {
+        Python::with_gil(|py| {
             let symbol = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(py, "symbol_info", (symbol,))
-                .expect("Unable to call `symbol_info` method")
-                .getattr(py, "_asdict")
-                .expect("Unable to get `_asdict` attribute")
-                .call0(py)
-                .expect("Unable to call `_asdict` method")
-                .extract(py)
-                .expect("Unable to extract `symbol_info` result");
-            Ok(symbol)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get symbol info"))
+                .invoke(py, "symbol_info", (symbol,), None)?
+                .call_method0("_asdict")?;
+            extract(&symbol, "symbol_info").map(|info: SymbolInfo| info.into_utc(&self.timezone))
+        })
     }
 
     fn symbol_info_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
-        let result: PyResult<SymbolTick> = Python::with_gil(|py| {
-            let ticks = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(py, "symbol_info_tick", (symbol,))
-                .expect("Unable to call `symbol_info_tick` method")
-                .getattr(py, "_asdict")
-                .expect("Unable to get `_asdict` attribute")
-                .call0(py)
-                .expect("Unable to call `_asdict` method")
-                .extract(py)
-                .expect("Unable to extract `symbol_info_tick` result");
-            Ok(ticks)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get symbol tick"))
+        Python::with_gil(|py| {
+            let tick = self
+                .invoke(py, "symbol_info_tick", (symbol,), None)?
+                .call_method0("_asdict")?;
+            extract(&tick, "symbol_info_tick").map(|tick: SymbolTick| tick.into_utc(&self.timezone))
+        })
     }
 
     fn symbol_select(&self, symbol: &str, enable: Option<bool>) -> crate::prelude::MQLResult<bool> {
-        let result: PyResult<bool> = Python::with_gil(|py| {
-            let selected_symbol = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "symbol_select")
-                .expect("Unable to find `symbol_select` method")
-                .call_bound(py, (symbol, enable.unwrap_or(true)), None)
-                .expect("Unable to call `symbol_select` method")
-                .extract(py)
-                .expect("Unable to extract `symbol_select` result");
-
-            Ok(selected_symbol)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to select symbol"))
+        Python::with_gil(|py| {
+            let selected_symbol =
+                self.invoke(py, "symbol_select", (symbol, enable.unwrap_or(true)), None)?;
+            extract(&selected_symbol, "symbol_select")
+        })
     }
 
     fn symbols_get(&self, group: Option<&str>) -> MQLResult<Vec<crate::schemas::SymbolInfo>> {
-        let result: PyResult<Vec<SymbolInfo>> = Python::with_gil(|py| {
+        Python::with_gil(|py| {
             let kwargs = PyDict::new_bound(py);
             if let Some(group) = group {
-                kwargs
-                    .set_item("group", group)
-                    .expect("Unable to set `group`");
+                kwargs.set_item("group", group)?;
             }
-            let symbols = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "symbols_get")
-                .expect("Unable to find `symbols_get` method")
-                .call_bound(py, (), Some(&kwargs))
-                .expect("Unable to call `symbols_get` method");
-
-            let symbols_kw = PyDict::new_bound(py);
-            symbols_kw
-                .set_item("symbols", symbols)
-                .expect("Unable to set `symbols`");
-
-            let symbols = py
-                .eval_bound("[s._asdict() for s in symbols]", Some(&symbols_kw), None)
-                .expect("Unable to evaluate `symbols`")
-                .extract()
-                .expect("Unable to extract `symbols`");
-            Ok(symbols)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get symbols"))
+            let symbols = self.invoke(py, "symbols_get", (), Some(&kwargs))?;
+            records(&symbols, "symbols_get")
+                .map(|values: Vec<SymbolInfo>| values.into_utc(&self.timezone))
+        })
     }
 }
 
@@ -423,68 +404,14 @@ impl SymbolRatesTrait for MT5PythonConnection {
         &self,
         symbol: &str,
         timeframe: crate::enums::Timeframe,
-        date_from: DateTime<Local>,
+        date_from: DateTime<Utc>,
         count: i32,
     ) -> MQLResult<Vec<crate::schemas::SymbolRates>> {
-        let result: PyResult<Vec<SymbolRates>> = Python::with_gil(|py| {
-            let rates = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "copy_rates_from")
-                .expect("Unable to find `copy_rates_from` method")
-                .call1(
-                    py,
-                    (
-                        symbol,
-                        timeframe as i64,
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        count,
-                    ),
-                )
-                .expect("Unable to call `copy_rates_from` method");
-
-            let pandas = py
-                .import_bound("pandas")
-                .expect("Unable to import `pandas`"); // replace this with polars in the future
-
-            let pandas_kw = PyDict::new_bound(py);
-
-            pandas_kw
-                .set_item("orient", "records")
-                .expect("Unable to set `orient`");
-
-            let rates = pandas
-                .getattr("DataFrame")
-                .expect("Unable to get `DataFrame`")
-                .call1((rates,))
-                .expect("Unable to call `DataFrame`")
-                .call_method("to_dict", (), Some(&pandas_kw))
-                .expect("Unable to call `to_dict`")
-                .extract()
-                .expect("Unable to extract `rates`");
-
-            Ok(rates)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to copy rates from"))
+        self.copy_array(
+            "copy_rates_from",
+            |py| self.rates_from_args(py, symbol, timeframe, date_from, count),
+            |rates| self.rates(rates),
+        )
     }
 
     fn copy_rates_from_pos(
@@ -494,125 +421,25 @@ impl SymbolRatesTrait for MT5PythonConnection {
         start_pos: i32,
         count: i32,
     ) -> MQLResult<Vec<crate::schemas::SymbolRates>> {
-        let result: PyResult<Vec<SymbolRates>> = Python::with_gil(|py| {
-            let rates = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "copy_rates_from_pos")
-                .expect("Unable to find `copy_rates_from_pos` method")
-                .call1(py, (symbol, timeframe as i64, start_pos, count))
-                .expect("Unable to call `copy_rates_from_pos` method");
-
-            let pandas = py
-                .import_bound("pandas")
-                .expect("Unable to import `pandas`"); // replace this with polars in the future
-
-            let pandas_kw = PyDict::new_bound(py);
-
-            pandas_kw
-                .set_item("orient", "records")
-                .expect("Unable to set `orient`");
-
-            let rates = pandas
-                .getattr("DataFrame")
-                .expect("Unable to get `DataFrame`")
-                .call1((rates,))
-                .expect("Unable to call `DataFrame`")
-                .call_method("to_dict", (), Some(&pandas_kw))
-                .expect("Unable to call `to_dict`")
-                .extract()
-                .expect("Unable to extract `rates`");
-
-            Ok(rates)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to copy rates from pos"))
+        self.copy_array(
+            "copy_rates_from_pos",
+            |py| Ok((symbol, timeframe as i64, start_pos, count).into_py(py)),
+            |rates| self.rates(rates),
+        )
     }
 
     fn copy_rates_range(
         &self,
         symbol: &str,
         timeframe: crate::enums::Timeframe,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<crate::schemas::SymbolRates>> {
-        let result: PyResult<Vec<SymbolRates>> = Python::with_gil(|py| {
-            let rates = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "copy_rates_range")
-                .expect("Unable to find `copy_rates_range` method")
-                .call1(
-                    py,
-                    (
-                        symbol,
-                        timeframe as i64,
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                    ),
-                )
-                .expect("Unable to call `copy_rates_range` method");
-
-            let pandas = py
-                .import_bound("pandas")
-                .expect("Unable to import `pandas`"); // replace this with polars in the future
-
-            let pandas_kw = PyDict::new_bound(py);
-
-            pandas_kw
-                .set_item("orient", "records")
-                .expect("Unable to set `orient`");
-
-            let rates = pandas
-                .getattr("DataFrame")
-                .expect("Unable to get `DataFrame`")
-                .call1((rates,))
-                .expect("Unable to call `DataFrame`")
-                .call_method("to_dict", (), Some(&pandas_kw))
-                .expect("Unable to call `to_dict`")
-                .extract()
-                .expect("Unable to extract `rates`");
-
-            Ok(rates)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to copy rates range"))
+        self.copy_array(
+            "copy_rates_range",
+            |py| self.rates_range_args(py, symbol, timeframe, date_from, date_to),
+            |rates| self.rates(rates),
+        )
     }
 }
 
@@ -620,203 +447,133 @@ impl SymbolTicksTrait for MT5PythonConnection {
     fn copy_ticks_from(
         &self,
         symbol: &str,
-        date_from: DateTime<Local>,
+        date_from: DateTime<Utc>,
         count: i32,
         flags: crate::enums::CopyTicksFlags,
     ) -> MQLResult<Vec<crate::schemas::SymbolTick>> {
-        let result: PyResult<Vec<SymbolTick>> = Python::with_gil(|py| {
-            let rates = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "copy_ticks_from")
-                .expect("Unable to find `copy_ticks_from` method")
-                .call1(
-                    py,
-                    (
-                        symbol,
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        count,
-                        flags as i64,
-                    ),
-                )
-                .expect("Unable to call `copy_ticks_from` method");
-
-            let pandas = py
-                .import_bound("pandas")
-                .expect("Unable to import `pandas`");
-
-            let pandas_kw = PyDict::new_bound(py);
-
-            pandas_kw
-                .set_item("orient", "records")
-                .expect("Unable to set `orient`");
-
-            let rates = pandas
-                .getattr("DataFrame")
-                .expect("Unable to get `DataFrame`")
-                .call1((rates,))
-                .expect("Unable to call `DataFrame`")
-                .call_method("to_dict", (), Some(&pandas_kw))
-                .expect("Unable to call `to_dict`")
-                .extract()
-                .expect("Unable to extract `rates`");
-
-            Ok(rates)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to copy ticks from"))
+        self.copy_array(
+            "copy_ticks_from",
+            |py| self.ticks_from_args(py, symbol, date_from, count, flags),
+            |ticks| self.ticks(ticks),
+        )
     }
     fn copy_ticks_range(
         &self,
         symbol: &str,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
         flags: crate::enums::CopyTicksFlags,
     ) -> MQLResult<Vec<crate::schemas::SymbolTick>> {
-        let result: PyResult<Vec<SymbolTick>> = Python::with_gil(|py| {
-            let rates = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .getattr(py, "copy_ticks_range")
-                .expect("Unable to find `copy_ticks_range` method")
-                .call1(
-                    py,
-                    (
-                        symbol,
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        flags as i64,
-                    ),
-                )
-                .expect("Unable to call `copy_ticks_range` method");
-
-            let pandas = py
-                .import_bound("pandas")
-                .expect("Unable to import `pandas`"); // replace this with polars in the future
-
-            let pandas_kw = PyDict::new_bound(py);
-
-            pandas_kw
-                .set_item("orient", "records")
-                .expect("Unable to set `orient`");
+        self.copy_array(
+            "copy_ticks_range",
+            |py| self.ticks_range_args(py, symbol, date_from, date_to, flags),
+            |ticks| self.ticks(ticks),
+        )
+    }
+}
 
-            let rates = pandas
-                .getattr("DataFrame")
-                .expect("Unable to get `DataFrame`")
-                .call1((rates,))
-                .expect("Unable to call `DataFrame`")
-                .call_method("to_dict", (), Some(&pandas_kw))
-                .expect("Unable to call `to_dict`")
-                .extract()
-                .expect("Unable to extract `rates`");
+#[cfg(feature = "arrow")]
+impl SymbolRatesArrowTrait for MT5PythonConnection {
+    fn copy_rates_from_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<RecordBatch> {
+        self.copy_array(
+            "copy_rates_from",
+            |py| self.rates_from_args(py, symbol, timeframe, date_from, count),
+            |rates| rates_batch(rates, &self.timezone),
+        )
+    }
 
-            Ok(rates)
-        });
+    fn copy_rates_from_pos_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<RecordBatch> {
+        self.copy_array(
+            "copy_rates_from_pos",
+            |py| Ok((symbol, timeframe as i64, start_pos, count).into_py(py)),
+            |rates| rates_batch(rates, &self.timezone),
+        )
+    }
 
-        let (code, message) = self.last_error();
+    fn copy_rates_range_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<RecordBatch> {
+        self.copy_array(
+            "copy_rates_range",
+            |py| self.rates_range_args(py, symbol, timeframe, date_from, date_to),
+            |rates| rates_batch(rates, &self.timezone),
+        )
+    }
+}
 
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
+#[cfg(feature = "arrow")]
+impl SymbolTicksArrowTrait for MT5PythonConnection {
+    fn copy_ticks_from_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<RecordBatch> {
+        self.copy_array(
+            "copy_ticks_from",
+            |py| self.ticks_from_args(py, symbol, date_from, count, flags),
+            |ticks| ticks_batch(ticks, &self.timezone),
+        )
+    }
 
-        Ok(result.expect("Unable to copy ticks range"))
+    fn copy_ticks_range_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<RecordBatch> {
+        self.copy_array(
+            "copy_ticks_range",
+            |py| self.ticks_range_args(py, symbol, date_from, date_to, flags),
+            |ticks| ticks_batch(ticks, &self.timezone),
+        )
     }
 }
 
 impl OrderTrait for MT5PythonConnection {
     fn orders_total(&self) -> MQLResult<i64> {
-        let result: PyResult<i64> = Python::with_gil(|py| {
-            let total_orders = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "orders_total")
-                .expect("Unable to call `orders_total` method")
-                .extract(py)
-                .expect("Unable to extract `orders_total` result");
-            Ok(total_orders)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get total orders"))
+        Python::with_gil(|py| {
+            let total_orders = self.invoke(py, "orders_total", (), None)?;
+            extract(&total_orders, "orders_total")
+        })
     }
     fn orders_get(&self) -> MQLResult<Vec<crate::schemas::Order>> {
-        let result: PyResult<Vec<Order>> = Python::with_gil(|py| {
-            let orders = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "orders_get")
-                .expect("Unable to call `orders_get` method");
-
-            let orders_kw = PyDict::new_bound(py);
-
-            orders_kw
-                .set_item("orders", orders)
-                .expect("Unable to set `orders`");
-
-            let orders = py
-                .eval_bound("[s._asdict() for s in orders]", Some(&orders_kw), None)
-                .expect("Unable to evaluate `orders`")
-                .extract()
-                .expect("Unable to extract `orders`");
-
-            Ok(orders)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
+        Python::with_gil(|py| {
+            let orders = self.invoke(py, "orders_get", (), None)?;
+            records(&orders, "orders_get").map(|values: Vec<Order>| values.into_utc(&self.timezone))
+        })
+    }
 
-        Ok(result.expect("Unable to get orders"))
+    fn orders_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Order>> {
+        Python::with_gil(|py| {
+            let kwargs = trade_filter_kwargs(py, filter)?;
+            let orders = self.invoke(py, "orders_get", (), Some(&kwargs))?;
+            records(&orders, "orders_get").map(|values: Vec<Order>| {
+                values
+                    .into_iter()
+                    .filter(|order| filter.matches_order(order))
+                    .collect::<Vec<_>>()
+                    .into_utc(&self.timezone)
+            })
+        })
     }
     fn order_calc_margin(
         &self,
@@ -825,27 +582,15 @@ impl OrderTrait for MT5PythonConnection {
         volume: f64,
         price: f64,
     ) -> MQLResult<f64> {
-        let result: PyResult<f64> = Python::with_gil(|py| {
-            let margin = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "order_calc_margin",
-                    (action as i64, symbol, volume, price),
-                )
-                .expect("Unable to call `order_calc_margin` method");
-            Ok(margin.extract(py).expect("Unable to extract `margin`"))
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to calculate margin"))
+        Python::with_gil(|py| {
+            let margin = self.invoke(
+                py,
+                "order_calc_margin",
+                (action.value(), symbol, volume, price),
+                None,
+            )?;
+            extract(&margin, "order_calc_margin")
+        })
     }
     fn order_calc_profit(
         &self,
@@ -855,433 +600,202 @@ impl OrderTrait for MT5PythonConnection {
         price_open: f64,
         price_close: f64,
     ) -> MQLResult<f64> {
-        let result: PyResult<f64> = Python::with_gil(|py| {
-            let profit = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "order_calc_profit",
-                    (action as i64, symbol, volume, price_open, price_close),
-                )
-                .expect("Unable to call `order_calc_profit` method");
-            Ok(profit.extract(py).expect("Unable to extract `profit`"))
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to calculate profit"))
+        Python::with_gil(|py| {
+            let profit = self.invoke(
+                py,
+                "order_calc_profit",
+                (action.value(), symbol, volume, price_open, price_close),
+                None,
+            )?;
+            extract(&profit, "order_calc_profit")
+        })
     }
 
     fn order_check(
         &self,
         request: &crate::schemas::TradeRequestBuilder,
     ) -> MQLResult<crate::schemas::CheckResult> {
-        let result: MQLResult<CheckResult> = Python::with_gil(|py| {
-            let trade_result: PyObject = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(py, "order_check", (request.clone(),))
-                .expect("Unable to call `order_check` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method");
-
-            let trade_request = trade_result
-                .getattr(py, "get")
-                .expect("Unable to get `get` attribute")
-                .call1(py, ("request",))
-                .expect("Unable to call `get` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method");
-
-            let update_trade_result_kw = PyDict::new_bound(py);
-
-            update_trade_result_kw
-                .set_item("request", trade_request)
-                .expect("Unable to set `request`");
-
-            trade_result
-                .getattr(py, "update")
-                .expect("Unable to get `update` attribute")
-                .call_bound(py, (), Some(&update_trade_result_kw))
-                .expect("Unable to call `update` method");
-
-            let (code, message) = self.last_error();
-
-            if (code as i64).is_negative() {
-                return Err((code, message));
-            }
-
-            Ok(trade_result
-                .extract(py)
-                .expect("Unable to extract `trade_result`"))
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to check order"))
+        Python::with_gil(|py| {
+            let request = request.clone().into_server_time(&self.timezone);
+            let check_result = self.invoke(py, "order_check", (request,), None)?;
+            extract(&trade_dict(&check_result)?, "order_check")
+                .map(|result: CheckResult| result.into_utc(&self.timezone))
+        })
     }
+
+    /// Sends a trade request to the trade server.
+    ///
+    /// Answers other than `DONE`, `PLACED` and `DonePartial` are returned as
+    /// [`FishingLineError::TradeRejected`] carrying the full [`TradeResult`].
     fn order_send(
         &self,
         request: crate::schemas::TradeRequestBuilder,
     ) -> MQLResult<crate::schemas::TradeResult> {
-        let result: PyResult<TradeResult> = Python::with_gil(|py| {
-            let trade_result: PyObject = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(py, "order_send", (request,))
-                .expect("Unable to call `order_send` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method");
-
-            let trade_request = trade_result
-                .getattr(py, "get")
-                .expect("Unable to get `get` attribute")
-                .call1(py, ("request",))
-                .expect("Unable to call `get` method")
-                .call_method0(py, "_asdict")
-                .expect("Unable to call `_asdict` method");
-
-            let update_trade_result_kw = PyDict::new_bound(py);
-
-            update_trade_result_kw
-                .set_item("request", trade_request)
-                .expect("Unable to set `request`");
-
-            trade_result
-                .getattr(py, "update")
-                .expect("Unable to get `update` attribute")
-                .call_bound(py, (), Some(&update_trade_result_kw))
-                .expect("Unable to call `update` method");
-
-            Ok(trade_result
-                .extract(py)
-                .expect("Unable to extract `trade_result`"))
-        });
+        let trade_result = Python::with_gil(|py| {
+            let request = request.into_server_time(&self.timezone);
+            let trade_result = self.invoke(py, "order_send", (request,), None)?;
+            extract(&trade_dict(&trade_result)?, "order_send")
+                .map(|result: TradeResult| result.into_utc(&self.timezone))
+        })?;
+        check_trade_result(trade_result)
+    }
+}
 
-        let (code, message) = self.last_error();
+impl MarketBookTrait for MT5PythonConnection {
+    fn market_book_add(&self, symbol: &str) -> MQLResult<bool> {
+        Python::with_gil(|py| {
+            let added = self.invoke(py, "market_book_add", (symbol,), None)?;
+            extract(&added, "market_book_add")
+        })
+    }
 
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
+    fn market_book_get(&self, symbol: &str) -> MQLResult<MarketBook> {
+        Python::with_gil(|py| {
+            let book = self.invoke(py, "market_book_get", (symbol,), None)?;
+            Ok(MarketBook {
+                entries: records(&book, "market_book_get")?,
+            })
+        })
+    }
 
-        Ok(result.expect("Unable to send order"))
+    fn market_book_release(&self, symbol: &str) -> MQLResult<bool> {
+        Python::with_gil(|py| {
+            let released = self.invoke(py, "market_book_release", (symbol,), None)?;
+            extract(&released, "market_book_release")
+        })
     }
 }
 
 impl PositionTrait for MT5PythonConnection {
     fn positions_total(&self) -> MQLResult<i64> {
-        let result: PyResult<i64> = Python::with_gil(|py| {
-            let total_positions = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "positions_total")
-                .expect("Unable to call `positions_total` method")
-                .extract(py)
-                .expect("Unable to extract `positions_total` result");
-            Ok(total_positions)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get total positions"))
+        Python::with_gil(|py| {
+            let total_positions = self.invoke(py, "positions_total", (), None)?;
+            extract(&total_positions, "positions_total")
+        })
     }
     fn positions_get(&self) -> MQLResult<Vec<crate::schemas::Position>> {
-        let result: PyResult<Vec<Position>> = Python::with_gil(|py| {
-            let positions = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method0(py, "positions_get")
-                .expect("Unable to call `positions_get` method");
-
-            let positions_kw = PyDict::new_bound(py);
-
-            positions_kw
-                .set_item("positions", positions)
-                .expect("Unable to set `positions`");
-
-            let positions = py
-                .eval_bound(
-                    "[s._asdict() for s in positions]",
-                    Some(&positions_kw),
-                    None,
-                )
-                .expect("Unable to evaluate `positions`")
-                .extract()
-                .expect("Unable to extract `positions`");
-            Ok(positions)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
+        Python::with_gil(|py| {
+            let positions = self.invoke(py, "positions_get", (), None)?;
+            records(&positions, "positions_get")
+                .map(|values: Vec<Position>| values.into_utc(&self.timezone))
+        })
+    }
 
-        Ok(result.expect("Unable to get positions"))
+    fn positions_get_filtered(&self, filter: &TradeFilter) -> MQLResult<Vec<Position>> {
+        Python::with_gil(|py| {
+            let kwargs = trade_filter_kwargs(py, filter)?;
+            let positions = self.invoke(py, "positions_get", (), Some(&kwargs))?;
+            records(&positions, "positions_get").map(|values: Vec<Position>| {
+                values
+                    .into_iter()
+                    .filter(|position| filter.matches_position(position))
+                    .collect::<Vec<_>>()
+                    .into_utc(&self.timezone)
+            })
+        })
     }
 }
 
 impl HistoryTrait for MT5PythonConnection {
     fn history_orders_total(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<i64> {
-        let result: PyResult<i64> = Python::with_gil(|py| {
-            let total_history_orders = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "history_orders_total",
-                    (
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                    ),
-                )
-                .expect("Unable to call `history_orders_total` method")
-                .extract(py)
-                .expect("Unable to extract `total_history_orders`");
-            Ok(total_history_orders)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get total history orders"))
+        Python::with_gil(|py| {
+            let total_history_orders = self.invoke(
+                py,
+                "history_orders_total",
+                (
+                    self.server_datetime(py, date_from)?,
+                    self.server_datetime(py, date_to)?,
+                ),
+                None,
+            )?;
+            extract(&total_history_orders, "history_orders_total")
+        })
     }
     fn history_orders_get(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<Order>> {
-        let result: PyResult<Vec<Order>> = Python::with_gil(|py| {
-            let orders = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "history_orders_get",
-                    (
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                    ),
-                )
-                .expect("Unable to call `history_orders_get` method");
-
-            let orders_kw = PyDict::new_bound(py);
-
-            orders_kw
-                .set_item("orders", orders)
-                .expect("Unable to set `orders`");
-
-            let orders = py
-                .eval_bound("[s._asdict() for s in orders]", Some(&orders_kw), None)
-                .expect("Unable to evaluate `orders`")
-                .extract()
-                .expect("Unable to extract `orders`");
-            Ok(orders)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get history orders"))
+        Python::with_gil(|py| {
+            let orders = self.invoke(
+                py,
+                "history_orders_get",
+                (
+                    self.server_datetime(py, date_from)?,
+                    self.server_datetime(py, date_to)?,
+                ),
+                None,
+            )?;
+            records(&orders, "history_orders_get")
+                .map(|values: Vec<Order>| values.into_utc(&self.timezone))
+        })
     }
     fn history_deals_total(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<i64> {
-        let result: PyResult<i64> = Python::with_gil(|py| {
-            let total_history_deals = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "history_deals_total",
-                    (
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                    ),
-                )
-                .expect("Unable to call `history_deals_total` method")
-                .extract(py)
-                .expect("Unable to extract `total_history_deals");
-            Ok(total_history_deals)
-        });
-
-        let (code, message) = self.last_error();
-
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
-
-        Ok(result.expect("Unable to get total history deals"))
+        Python::with_gil(|py| {
+            let total_history_deals = self.invoke(
+                py,
+                "history_deals_total",
+                (
+                    self.server_datetime(py, date_from)?,
+                    self.server_datetime(py, date_to)?,
+                ),
+                None,
+            )?;
+            extract(&total_history_deals, "history_deals_total")
+        })
     }
     fn history_deals_get(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<crate::schemas::Deals>> {
-        let result: PyResult<Vec<Deals>> = Python::with_gil(|py| {
-            let deals = self
-                .runtime
-                .as_ref()
-                .expect("Unable to find `MetaTrader5` module")
-                .call_method1(
-                    py,
-                    "history_deals_get",
-                    (
-                        PyDateTime::new_bound(
-                            py,
-                            date_from.year(),
-                            date_from.month() as u8,
-                            date_from.day() as u8,
-                            date_from.hour() as u8,
-                            date_from.minute() as u8,
-                            date_from.second() as u8,
-                            date_from.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                        PyDateTime::new_bound(
-                            py,
-                            date_to.year(),
-                            date_to.month() as u8,
-                            date_to.day() as u8,
-                            date_to.hour() as u8,
-                            date_to.minute() as u8,
-                            date_to.second() as u8,
-                            date_to.timestamp_subsec_micros(),
-                            None,
-                        )
-                        .expect("Unable to create `PyDateTime`"),
-                    ),
-                )
-                .expect("Unable to call `history_deals_get` method");
-
-            let deals_kw = PyDict::new_bound(py);
-
-            deals_kw
-                .set_item("deals", deals)
-                .expect("Unable to set `deals`");
-
-            let deals = py
-                .eval_bound("[s._asdict() for s in deals]", Some(&deals_kw), None)
-                .expect("Unable to evaluate `deals`")
-                .extract()
-                .expect("Unable to extract `deals`");
-
-            Ok(deals)
-        });
-
-        let (code, message) = self.last_error();
+        Python::with_gil(|py| {
+            let deals = self.invoke(
+                py,
+                "history_deals_get",
+                (
+                    self.server_datetime(py, date_from)?,
+                    self.server_datetime(py, date_to)?,
+                ),
+                None,
+            )?;
+            records(&deals, "history_deals_get")
+                .map(|values: Vec<Deals>| values.into_utc(&self.timezone))
+        })
+    }
 
-        if (code as i64).is_negative() {
-            return Err((code, message));
-        }
+    fn history_orders_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Order>> {
+        Python::with_gil(|py| {
+            let (args, kwargs) = self.history_args(py, query)?;
+            let orders =
+                self.invoke(py, "history_orders_get", args.into_bound(py), Some(&kwargs))?;
+            records(&orders, "history_orders_get")
+                .map(|values: Vec<Order>| values.into_utc(&self.timezone))
+        })
+    }
 
-        Ok(result.expect("Unable to get history deals"))
+    fn history_deals_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Deals>> {
+        Python::with_gil(|py| {
+            let (args, kwargs) = self.history_args(py, query)?;
+            let deals = self.invoke(py, "history_deals_get", args.into_bound(py), Some(&kwargs))?;
+            records(&deals, "history_deals_get")
+                .map(|values: Vec<Deals>| values.into_utc(&self.timezone))
+        })
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
-    use chrono::{Local, TimeZone};
+    use chrono::{TimeZone, Utc};
 
     use crate::prelude::*;
 
@@ -1289,7 +803,8 @@ mod test {
     fn test_connection() {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
-        let runtime = MT5PythonConnection::new().initialize(terminal_path.as_str());
+        let runtime = MT5PythonConnection::new()
+            .and_then(|connection| connection.initialize(terminal_path.as_str()));
         assert_eq!(runtime.is_ok(), true, "Unable to connect to terminal");
     }
 
@@ -1308,12 +823,14 @@ mod test {
                 .expect("Unable to find `TERMINAL_ACCOUNT_SERVER` in .env file"),
         };
 
-        let runtime = MT5PythonConnection::new().initialize_with_credentials(
-            terminal_path.as_str(),
-            account_credentials,
-            1000,
-            None,
-        );
+        let runtime = MT5PythonConnection::new().and_then(|connection| {
+            connection.initialize_with_credentials(
+                terminal_path.as_str(),
+                account_credentials,
+                1000,
+                None,
+            )
+        });
         assert_eq!(runtime.is_ok(), true, "Unable to connect to terminal");
     }
 
@@ -1322,7 +839,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let version = runtime.version();
         assert_eq!(version.is_ok(), true, "Unable to get terminal version");
@@ -1332,7 +849,7 @@ mod test {
     fn test_terminal_info() {
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let terminal_info = runtime.terminal_info();
         assert_eq!(terminal_info.is_ok(), true, "Unable to get terminal info");
@@ -1343,7 +860,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let account_info = runtime.account_info();
         assert_eq!(account_info.is_ok(), true, "Unable to get account info");
@@ -1354,7 +871,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let symbols_total = runtime.symbols_total();
         assert_eq!(symbols_total.is_ok(), true, "Unable to get symbols total");
@@ -1365,7 +882,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let symbols_get_all = runtime.symbols_get(None);
         assert_eq!(
@@ -1386,7 +903,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let symbol_info = runtime.symbol_info("BTCUSD");
         assert_eq!(symbol_info.is_ok(), true, "Unable to get symbol info");
@@ -1397,7 +914,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let symbol_info_tick = runtime.symbol_info_tick("BTCUSD");
         assert_eq!(
@@ -1412,7 +929,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let symbol_select = runtime.symbol_select("BTCUSD", None);
         assert_eq!(symbol_select.is_ok(), true, "Unable to select symbol");
@@ -1423,9 +940,9 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
-        let copy_rates_from = runtime.copy_rates_from("BTCUSD", Timeframe::H1, Local::now(), 20);
+        let copy_rates_from = runtime.copy_rates_from("BTCUSD", Timeframe::H1, Utc::now(), 20);
         assert_eq!(copy_rates_from.is_ok(), true, "Unable to get symbol rates");
     }
 
@@ -1434,7 +951,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let copy_rates_from_pos = runtime.copy_rates_from_pos("BTCUSD", Timeframe::H1, 0, 20);
         assert_eq!(
@@ -1449,13 +966,13 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let copy_rates_range = runtime.copy_rates_range(
             "BTCUSD",
             Timeframe::H1,
-            Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap(),
-            Local::now(),
+            Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap(),
+            Utc::now(),
         );
         assert_eq!(copy_rates_range.is_ok(), true, "Unable to get symbol rates");
     }
@@ -1465,11 +982,11 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let copy_ticks_from = runtime.copy_ticks_from(
             "BTCUSD",
-            Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap(),
             20,
             CopyTicksFlags::ALL,
         );
@@ -1481,12 +998,12 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let copy_ticks_range = runtime.copy_ticks_range(
             "BTCUSD",
-            Local::now() - chrono::Duration::minutes(20),
-            Local::now(),
+            Utc::now() - chrono::Duration::minutes(20),
+            Utc::now(),
             CopyTicksFlags::ALL,
         );
         assert_eq!(copy_ticks_range.is_ok(), true, "Unable to get symbol rates");
@@ -1497,7 +1014,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let orders_total = runtime.orders_total();
         assert_eq!(orders_total.is_ok(), true, "Unable to get total orders");
@@ -1508,7 +1025,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let orders_get = runtime.orders_get();
         assert_eq!(orders_get.is_ok(), true, "Unable to get total orders");
@@ -1519,7 +1036,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
         let current_symbol = runtime.symbol_info("BTCUSD").unwrap();
@@ -1558,7 +1075,11 @@ mod test {
 
         assert_eq!(check_order.is_ok(), true, "Unable to check order");
 
-        assert_eq!(check_order.unwrap().retcode as u64, 0, "Order is not valid");
+        assert_eq!(
+            check_order.unwrap().retcode.value(),
+            0,
+            "Order is not valid"
+        );
     }
 
     #[test]
@@ -1566,7 +1087,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
         let current_symbol = runtime.symbol_info("BTCUSD").unwrap();
@@ -1632,7 +1153,7 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let positions_total = runtime.positions_total();
         assert_eq!(
@@ -1647,22 +1168,33 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
         let positions_get = runtime.positions_get();
         assert_eq!(positions_get.is_ok(), true, "Unable to get total positions");
     }
 
+    #[test]
+    fn test_positions_get_filtered() {
+        dotenv::dotenv().ok();
+        let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+        let runtime = MT5PythonConnection::new()
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
+            .expect("Unable to connect to terminal");
+        let positions_get = runtime.positions_get_filtered(&TradeFilter::new().group("*USD*"));
+        assert!(positions_get.is_ok(), "Unable to get filtered positions");
+    }
+
     #[test]
     fn test_history_orders_total() {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
-        let date_from = Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
-        let date_to = Local::now();
+        let date_from = Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
+        let date_to = Utc::now();
 
         let history_orders_total = runtime.history_orders_total(date_from, date_to);
         assert_eq!(
@@ -1677,11 +1209,11 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
-        let date_from = Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
-        let date_to = Local::now();
+        let date_from = Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
+        let date_to = Utc::now();
 
         let history_orders_get = runtime.history_orders_get(date_from, date_to);
         assert_eq!(
@@ -1691,16 +1223,32 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_history_orders_query() {
+        dotenv::dotenv().ok();
+        let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+        let runtime = MT5PythonConnection::new()
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
+            .expect("Unable to connect to terminal");
+        let date_from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let history_orders = runtime.history_orders_query(&HistoryQuery::range_with_group(
+            date_from,
+            Utc::now(),
+            "*USD*",
+        ));
+        assert!(history_orders.is_ok(), "Unable to query history orders");
+    }
+
     #[test]
     fn test_history_deals_total() {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
-        let date_from = Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
-        let date_to = Local::now();
+        let date_from = Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
+        let date_to = Utc::now();
 
         let history_deals_total = runtime.history_deals_total(date_from, date_to);
         assert_eq!(
@@ -1715,11 +1263,11 @@ mod test {
         dotenv::dotenv().ok();
         let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
         let runtime = MT5PythonConnection::new()
-            .initialize(terminal_path.as_str())
+            .and_then(|connection| connection.initialize(terminal_path.as_str()))
             .expect("Unable to connect to terminal");
 
-        let date_from = Local.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
-        let date_to = Local::now();
+        let date_from = Utc.with_ymd_and_hms(2024, 7, 7, 0, 0, 0).unwrap();
+        let date_to = Utc::now();
 
         let history_deals_get = runtime.history_deals_get(date_from, date_to);
         assert_eq!(
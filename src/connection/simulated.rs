@@ -0,0 +1,2091 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+use crate::connection::mock::{rates_from, rates_from_pos, rates_range, ticks_from, ticks_range};
+use crate::error::check_trade_result;
+use crate::prelude::*;
+use crate::schemas::group_matches;
+
+const EPSILON: f64 = 1e-8;
+
+/// Outcome of a request that the simulated server accepted.
+struct Execution {
+    retcode: ReturnCode,
+    order: usize,
+    deal: usize,
+    volume: f64,
+    price: f64,
+}
+
+/// A trade that is about to be filled, either from `order_send` or a triggered pending order.
+struct Fill {
+    symbol: String,
+    side: PositionType,
+    volume: f64,
+    price: f64,
+    order: isize,
+    position: Option<isize>,
+    sl: f64,
+    tp: f64,
+    magic: isize,
+    comment: String,
+    reason: DealReason,
+}
+
+#[derive(Clone)]
+struct BrokerState {
+    account: AccountInfo,
+    terminal_info: TerminalInfo,
+    version: TerminalVersion,
+    symbols: Vec<SymbolInfo>,
+    ticks: Vec<(String, SymbolTick)>,
+    closed_markets: HashSet<String>,
+    orders: Vec<Order>,
+    positions: Vec<Position>,
+    history_orders: Vec<Order>,
+    deals: Vec<Deals>,
+    next_ticket: isize,
+    time_msc: i64,
+}
+
+/// Ticks fed to the broker and bars served by `copy_rates_*`, kept apart from
+/// [`BrokerState`] so `order_check` dry runs do not copy them.
+#[derive(Default)]
+struct PriceHistory {
+    ticks: HashMap<String, Vec<SymbolTick>>,
+    rates: HashMap<(String, i64), Vec<SymbolRates>>,
+}
+
+impl PriceHistory {
+    fn rates(&self, symbol: &str, timeframe: Timeframe) -> MQLResult<&[SymbolRates]> {
+        self.rates
+            .get(&(symbol.to_string(), timeframe as i64))
+            .map(Vec::as_slice)
+            .ok_or_else(|| {
+                FishingLineError::runtime(
+                    RuntimeError::NotFound,
+                    format!("No rates fed for `{}`", symbol),
+                )
+            })
+    }
+
+    fn ticks(&self, symbol: &str) -> MQLResult<&[SymbolTick]> {
+        self.ticks.get(symbol).map(Vec::as_slice).ok_or_else(|| {
+            FishingLineError::runtime(RuntimeError::NotFound, format!("No tick for `{}`", symbol))
+        })
+    }
+}
+
+fn opposite(side: PositionType) -> PositionType {
+    match side {
+        PositionType::BUY => PositionType::SELL,
+        _ => PositionType::BUY,
+    }
+}
+
+fn side_of(order_type: OrderType) -> Option<PositionType> {
+    match order_type {
+        OrderType::BUY | OrderType::BuyLimit | OrderType::BuyStop | OrderType::BuyStopLimit => {
+            Some(PositionType::BUY)
+        }
+        OrderType::SELL | OrderType::SellLimit | OrderType::SellStop | OrderType::SellStopLimit => {
+            Some(PositionType::SELL)
+        }
+        OrderType::CloseBy | OrderType::Unknown(_) => None,
+    }
+}
+
+fn market_order_type(side: PositionType) -> OrderType {
+    match side {
+        PositionType::BUY => OrderType::BUY,
+        _ => OrderType::SELL,
+    }
+}
+
+fn deal_type(side: PositionType) -> DealType {
+    match side {
+        PositionType::BUY => DealType::BUY,
+        _ => DealType::SELL,
+    }
+}
+
+fn is_set(value: f64) -> bool {
+    value.abs() > EPSILON
+}
+
+impl BrokerState {
+    fn hedging(&self) -> bool {
+        self.account.margin_mode == AccountMarginMode::RetailHedging
+    }
+
+    fn symbol(&self, symbol: &str) -> Result<SymbolInfo, ReturnCode> {
+        self.symbols
+            .iter()
+            .find(|info| info.name == symbol)
+            .cloned()
+            .ok_or(ReturnCode::INVALID)
+    }
+
+    fn tick(&self, symbol: &str) -> Result<SymbolTick, ReturnCode> {
+        self.ticks
+            .iter()
+            .find(|(name, _)| name == symbol)
+            .map(|(_, tick)| tick.clone())
+            .ok_or(ReturnCode::PriceOff)
+    }
+
+    fn next_ticket(&mut self) -> isize {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        ticket
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.time_msc).unwrap_or_default()
+    }
+
+    /// Margin in account currency, following the symbol's `trade_calc_mode`.
+    fn margin(&self, info: &SymbolInfo, volume: f64, price: f64) -> f64 {
+        let leverage = self.account.leverage.max(1) as f64;
+        let contract_size = if info.margin_initial > 0.0 {
+            info.margin_initial
+        } else {
+            info.trade_contract_size
+        };
+        match info.trade_calc_mode {
+            SymbolCalcMode::FOREX => {
+                self.to_account_currency(info, volume * contract_size / leverage, price)
+            }
+            SymbolCalcMode::ForexNoLeverage => {
+                self.to_account_currency(info, volume * contract_size, price)
+            }
+            SymbolCalcMode::CFD | SymbolCalcMode::CFDINDEX | SymbolCalcMode::ExchStocks => {
+                volume * info.trade_contract_size * price
+            }
+            SymbolCalcMode::CFDLEVERAGE => volume * info.trade_contract_size * price / leverage,
+            SymbolCalcMode::FUTURES | SymbolCalcMode::ExchFutures => volume * info.margin_initial,
+            _ => volume * info.trade_contract_size * price / leverage,
+        }
+    }
+
+    /// Converts a margin-currency amount into the account currency using the symbol's own quote.
+    fn to_account_currency(&self, info: &SymbolInfo, amount: f64, price: f64) -> f64 {
+        if info.currency_margin == self.account.currency
+            || info.currency_profit != self.account.currency
+        {
+            amount
+        } else {
+            amount * price
+        }
+    }
+
+    /// Profit in account currency of `volume` lots held in `side` from `price_open` to `price_close`.
+    fn profit(
+        &self,
+        info: &SymbolInfo,
+        side: PositionType,
+        volume: f64,
+        price_open: f64,
+        price_close: f64,
+    ) -> f64 {
+        let movement = match side {
+            PositionType::BUY => price_close - price_open,
+            _ => price_open - price_close,
+        };
+        if info.trade_tick_size > 0.0 && info.trade_tick_value > 0.0 {
+            return movement / info.trade_tick_size * info.trade_tick_value * volume;
+        }
+        let profit = movement * volume * info.trade_contract_size;
+        if info.currency_profit == self.account.currency || price_close == 0.0 {
+            profit
+        } else {
+            profit / price_close
+        }
+    }
+
+    fn check_volume(info: &SymbolInfo, volume: f64) -> Result<(), ReturnCode> {
+        if volume <= 0.0
+            || volume + EPSILON < info.volume_min
+            || (info.volume_max > 0.0 && volume > info.volume_max + EPSILON)
+        {
+            return Err(ReturnCode::InvalidVolume);
+        }
+        if info.volume_step > 0.0 {
+            let steps = volume / info.volume_step;
+            if (steps - steps.round()).abs() > 1e-6 {
+                return Err(ReturnCode::InvalidVolume);
+            }
+        }
+        Ok(())
+    }
+
+    /// SL/TP of a position held in `side` must sit beyond the stops level from `reference`.
+    fn check_stops(
+        info: &SymbolInfo,
+        side: PositionType,
+        reference: f64,
+        sl: f64,
+        tp: f64,
+    ) -> Result<(), ReturnCode> {
+        let level = info.trade_stops_level as f64 * info.point;
+        let valid = match side {
+            PositionType::BUY => {
+                (!is_set(sl) || sl <= reference - level + EPSILON)
+                    && (!is_set(tp) || tp >= reference + level - EPSILON)
+            }
+            _ => {
+                (!is_set(sl) || sl >= reference + level - EPSILON)
+                    && (!is_set(tp) || tp <= reference - level + EPSILON)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(ReturnCode::InvalidStops)
+        }
+    }
+
+    fn check_trade_mode(
+        info: &SymbolInfo,
+        side: PositionType,
+        opening: bool,
+    ) -> Result<(), ReturnCode> {
+        match (info.trade_mode, side) {
+            (SymbolTradeMode::SymbolTradeModeDisabled, _) => Err(ReturnCode::TradeDisabled),
+            (SymbolTradeMode::SymbolTradeModeCloseonly, _) if opening => Err(ReturnCode::CloseOnly),
+            (SymbolTradeMode::SymbolTradeModeLongonly, PositionType::SELL) if opening => {
+                Err(ReturnCode::LongOnly)
+            }
+            (SymbolTradeMode::SymbolTradeModeShortonly, PositionType::BUY) if opening => {
+                Err(ReturnCode::ShortOnly)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pending order prices must sit on the correct side of the market, beyond the stops level.
+    fn check_pending_price(
+        info: &SymbolInfo,
+        tick: &SymbolTick,
+        order_type: OrderType,
+        price: f64,
+        stoplimit: f64,
+    ) -> Result<(), ReturnCode> {
+        let level = info.trade_stops_level as f64 * info.point;
+        let valid = match order_type {
+            OrderType::BuyLimit => price <= tick.ask - level + EPSILON,
+            OrderType::SellLimit => price >= tick.bid + level - EPSILON,
+            OrderType::BuyStop => price >= tick.ask + level - EPSILON,
+            OrderType::SellStop => price <= tick.bid - level + EPSILON,
+            OrderType::BuyStopLimit => {
+                price >= tick.ask + level - EPSILON && is_set(stoplimit) && stoplimit < price
+            }
+            OrderType::SellStopLimit => {
+                price <= tick.bid - level + EPSILON && is_set(stoplimit) && stoplimit > price
+            }
+            _ => false,
+        };
+        if is_set(price) && valid {
+            Ok(())
+        } else {
+            Err(ReturnCode::InvalidPrice)
+        }
+    }
+
+    fn check_frozen(info: &SymbolInfo, market: f64, level_price: f64) -> Result<(), ReturnCode> {
+        let freeze = info.trade_freeze_level as f64 * info.point;
+        if is_set(level_price) && freeze > 0.0 && (market - level_price).abs() < freeze - EPSILON {
+            Err(ReturnCode::FROZEN)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn record_order(&mut self, fill: &Fill, order_type: OrderType, reason: OrderReason) {
+        let time_msc = self.time_msc;
+        self.history_orders.push(Order {
+            ticket: fill.order,
+            time_setup: self.time(),
+            r#type: order_type,
+            state: OrderState::FILLED,
+            time_done: self.time(),
+            time_setup_msc: time_msc as isize,
+            time_done_msc: time_msc as isize,
+            type_filling: OrderTypeFilling::FOK,
+            type_time: OrderTypeTime::GTC,
+            magic: fill.magic,
+            reason,
+            position_id: fill.position.unwrap_or(fill.order),
+            volume_initial: fill.volume,
+            volume_current: 0.0,
+            price_open: fill.price,
+            sl: fill.sl,
+            tp: fill.tp,
+            price_current: fill.price,
+            symbol: fill.symbol.clone(),
+            comment: fill.comment.clone(),
+            ..Default::default()
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_deal(
+        &mut self,
+        fill: &Fill,
+        side: PositionType,
+        entry: DealEntry,
+        volume: f64,
+        price: f64,
+        position_id: isize,
+        profit: f64,
+    ) -> isize {
+        let ticket = self.next_ticket();
+        self.account.balance += profit;
+        self.deals.push(Deals {
+            ticket,
+            order: fill.order,
+            time: self.time(),
+            time_msc: self.time_msc,
+            r#type: deal_type(side),
+            entry,
+            magic: fill.magic,
+            reason: fill.reason,
+            position_id,
+            volume,
+            price,
+            profit,
+            symbol: fill.symbol.clone(),
+            comment: fill.comment.clone(),
+            ..Default::default()
+        });
+        ticket
+    }
+
+    fn open_position(&mut self, fill: &Fill, side: PositionType, volume: f64, identifier: isize) {
+        let position_reason = match fill.reason {
+            DealReason::CLIENT => PositionReason::CLIENT,
+            DealReason::MOBILE => PositionReason::MOBILE,
+            DealReason::WEB => PositionReason::WEB,
+            _ => PositionReason::EXPERT,
+        };
+        let time_msc = self.time_msc;
+        self.positions.push(Position {
+            ticket: identifier,
+            time: self.time(),
+            time_msc: time_msc as isize,
+            time_update: self.time(),
+            time_update_msc: time_msc as isize,
+            r#type: side,
+            magic: fill.magic,
+            identifier,
+            reason: position_reason,
+            volume,
+            price_open: fill.price,
+            sl: fill.sl,
+            tp: fill.tp,
+            price_current: fill.price,
+            symbol: fill.symbol.clone(),
+            comment: fill.comment.clone(),
+            ..Default::default()
+        });
+    }
+
+    /// Closes `volume` lots of the position at `index` and returns the deal ticket.
+    fn close_volume(
+        &mut self,
+        fill: &Fill,
+        info: &SymbolInfo,
+        index: usize,
+        volume: f64,
+        price: f64,
+        entry: DealEntry,
+    ) -> isize {
+        let position = self.positions[index].clone();
+        let profit = self.profit(info, position.r#type, volume, position.price_open, price);
+        let deal = self.add_deal(
+            fill,
+            opposite(position.r#type),
+            entry,
+            volume,
+            price,
+            position.identifier,
+            profit,
+        );
+        if volume + EPSILON >= position.volume {
+            self.positions.remove(index);
+        } else {
+            let time = self.time();
+            let position = &mut self.positions[index];
+            position.volume -= volume;
+            position.time_update = time;
+            position.time_update_msc = self.time_msc as isize;
+        }
+        deal
+    }
+
+    fn require_margin(&self, info: &SymbolInfo, volume: f64, price: f64) -> Result<(), ReturnCode> {
+        if self.margin(info, volume, price) > self.account.margin_free + EPSILON {
+            Err(ReturnCode::NoMoney)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Records the market order behind `fill` unless its order is already tracked, as
+    /// with a triggered pending order that is moved to the history by the caller.
+    fn record_fill(&mut self, fill: &Fill, record_order: bool) {
+        if record_order {
+            self.record_order(fill, market_order_type(fill.side), OrderReason::EXPERT);
+        }
+    }
+
+    /// Fills a market trade, honouring hedging or netting and explicit position closes.
+    ///
+    /// `record_order` adds the market order to the order history; pending orders pass
+    /// `false` since they are moved to the history with their final state instead.
+    fn fill(&mut self, fill: Fill, record_order: bool) -> Result<isize, ReturnCode> {
+        let info = self.symbol(&fill.symbol)?;
+        let close_price = match (self.tick(&fill.symbol), fill.side) {
+            (Ok(tick), PositionType::BUY) => tick.bid,
+            (Ok(tick), _) => tick.ask,
+            (Err(_), _) => fill.price,
+        };
+
+        if let Some(ticket) = fill.position {
+            let index = self
+                .positions
+                .iter()
+                .position(|position| position.ticket == ticket)
+                .ok_or(ReturnCode::PostionClosed)?;
+            let position = &self.positions[index];
+            if position.symbol != fill.symbol || position.r#type == fill.side {
+                return Err(ReturnCode::INVALID);
+            }
+            if fill.volume > position.volume + EPSILON {
+                return Err(ReturnCode::InvalideCloseVolume);
+            }
+            Self::check_trade_mode(&info, fill.side, false)?;
+            self.record_fill(&fill, record_order);
+            return Ok(self.close_volume(
+                &fill,
+                &info,
+                index,
+                fill.volume,
+                fill.price,
+                DealEntry::OUT,
+            ));
+        }
+
+        let netting_index = if self.hedging() {
+            None
+        } else {
+            self.positions
+                .iter()
+                .position(|position| position.symbol == fill.symbol)
+        };
+
+        let Some(index) = netting_index else {
+            Self::check_trade_mode(&info, fill.side, true)?;
+            Self::check_stops(&info, fill.side, close_price, fill.sl, fill.tp)?;
+            self.require_margin(&info, fill.volume, fill.price)?;
+            self.record_fill(&fill, record_order);
+            let deal = self.add_deal(
+                &fill,
+                fill.side,
+                DealEntry::IN,
+                fill.volume,
+                fill.price,
+                fill.order,
+                0.0,
+            );
+            self.open_position(&fill, fill.side, fill.volume, fill.order);
+            return Ok(deal);
+        };
+
+        let position = self.positions[index].clone();
+        if position.r#type == fill.side {
+            Self::check_trade_mode(&info, fill.side, true)?;
+            self.require_margin(&info, fill.volume, fill.price)?;
+            self.record_fill(&fill, record_order);
+            let deal = self.add_deal(
+                &fill,
+                fill.side,
+                DealEntry::IN,
+                fill.volume,
+                fill.price,
+                position.identifier,
+                0.0,
+            );
+            let (time, time_msc) = (self.time(), self.time_msc);
+            let position = &mut self.positions[index];
+            let volume = position.volume + fill.volume;
+            position.price_open =
+                (position.price_open * position.volume + fill.price * fill.volume) / volume;
+            position.volume = volume;
+            if is_set(fill.sl) {
+                position.sl = fill.sl;
+            }
+            if is_set(fill.tp) {
+                position.tp = fill.tp;
+            }
+            position.time_update = time;
+            position.time_update_msc = time_msc as isize;
+            return Ok(deal);
+        }
+
+        let reversal = fill.volume - position.volume;
+        if reversal <= EPSILON {
+            Self::check_trade_mode(&info, fill.side, false)?;
+            self.record_fill(&fill, record_order);
+            return Ok(self.close_volume(
+                &fill,
+                &info,
+                index,
+                fill.volume,
+                fill.price,
+                DealEntry::OUT,
+            ));
+        }
+
+        Self::check_trade_mode(&info, fill.side, true)?;
+        Self::check_stops(&info, fill.side, close_price, fill.sl, fill.tp)?;
+        self.require_margin(&info, reversal, fill.price)?;
+        self.record_fill(&fill, record_order);
+        let deal = self.close_volume(
+            &fill,
+            &info,
+            index,
+            position.volume,
+            fill.price,
+            DealEntry::INOUT,
+        );
+        self.deals
+            .iter_mut()
+            .filter(|existing| existing.ticket == deal)
+            .for_each(|existing| existing.volume = fill.volume);
+        self.open_position(&fill, fill.side, reversal, position.identifier);
+        Ok(deal)
+    }
+
+    fn execute(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        match request.action.ok_or(ReturnCode::INVALID)? {
+            TradeActionRequest::DEAL => self.execute_deal(request),
+            TradeActionRequest::PENDING => self.execute_pending(request),
+            TradeActionRequest::SLTP => self.execute_sltp(request),
+            TradeActionRequest::MODIFY => self.execute_modify(request),
+            TradeActionRequest::REMOVE => self.execute_remove(request),
+            TradeActionRequest::CloseBy => self.execute_close_by(request),
+            TradeActionRequest::Unknown(_) => Err(ReturnCode::INVALID),
+        }
+    }
+
+    fn market(
+        &self,
+        request: &TradeRequestBuilder,
+    ) -> Result<(SymbolInfo, SymbolTick), ReturnCode> {
+        let symbol = request.symbol.as_deref().ok_or(ReturnCode::INVALID)?;
+        let info = self.symbol(symbol)?;
+        if self.closed_markets.contains(symbol) {
+            return Err(ReturnCode::MarketClosed);
+        }
+        Ok((info, self.tick(symbol)?))
+    }
+
+    fn execute_deal(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        let (info, tick) = self.market(request)?;
+        let side = match request.r#type.ok_or(ReturnCode::INVALID)? {
+            OrderType::BUY => PositionType::BUY,
+            OrderType::SELL => PositionType::SELL,
+            _ => return Err(ReturnCode::INVALID),
+        };
+        let volume = request.volume.unwrap_or_default();
+        Self::check_volume(&info, volume)?;
+
+        let price = match side {
+            PositionType::BUY => tick.ask,
+            _ => tick.bid,
+        };
+        if info.trade_exemode == SymbolTradeExecution::Instant {
+            if let Some(requested) = request.price {
+                let deviation = request.deviation.unwrap_or_default() as f64 * info.point;
+                if (requested - price).abs() > deviation + EPSILON {
+                    return Err(ReturnCode::REQUOTE);
+                }
+            }
+        }
+
+        let order = self.next_ticket();
+        let deal = self.fill(
+            Fill {
+                symbol: info.name.clone(),
+                side,
+                volume,
+                price,
+                order,
+                position: request
+                    .position
+                    .filter(|ticket| *ticket > 0)
+                    .map(|ticket| ticket as isize),
+                sl: request.sl.unwrap_or_default(),
+                tp: request.tp.unwrap_or_default(),
+                magic: request.magic.unwrap_or_default() as isize,
+                comment: request.comment.clone().unwrap_or_default(),
+                reason: DealReason::EXPERT,
+            },
+            true,
+        )?;
+
+        Ok(Execution {
+            retcode: ReturnCode::DONE,
+            order: order as usize,
+            deal: deal as usize,
+            volume,
+            price,
+        })
+    }
+
+    fn execute_pending(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        let (info, tick) = self.market(request)?;
+        let order_type = request.r#type.ok_or(ReturnCode::INVALID)?;
+        let side = match order_type {
+            OrderType::BUY | OrderType::SELL | OrderType::CloseBy => {
+                return Err(ReturnCode::INVALID)
+            }
+            order_type => side_of(order_type).ok_or(ReturnCode::INVALID)?,
+        };
+        let volume = request.volume.unwrap_or_default();
+        Self::check_volume(&info, volume)?;
+        Self::check_trade_mode(&info, side, true)?;
+
+        let price = request.price.unwrap_or_default();
+        let stoplimit = request.stoplimit.unwrap_or_default();
+        Self::check_pending_price(&info, &tick, order_type, price, stoplimit)?;
+
+        let open_price = if is_set(stoplimit) { stoplimit } else { price };
+        let sl = request.sl.unwrap_or_default();
+        let tp = request.tp.unwrap_or_default();
+        Self::check_stops(&info, side, open_price, sl, tp)?;
+
+        let type_time = request.type_time.unwrap_or(OrderTypeTime::GTC);
+        let expiration = request.expiration.unwrap_or_default();
+        if matches!(
+            type_time,
+            OrderTypeTime::SPECIFIED | OrderTypeTime::SpecifiedDay
+        ) && expiration <= self.time()
+        {
+            return Err(ReturnCode::InvalidExpiration);
+        }
+
+        let ticket = self.next_ticket();
+        let time_msc = self.time_msc;
+        self.orders.push(Order {
+            ticket,
+            time_setup: self.time(),
+            r#type: order_type,
+            state: OrderState::PLACED,
+            time_expiration: expiration,
+            time_setup_msc: time_msc as isize,
+            type_filling: request.type_filling.unwrap_or(OrderTypeFilling::RETURN),
+            type_time,
+            magic: request.magic.unwrap_or_default() as isize,
+            reason: OrderReason::EXPERT,
+            volume_initial: volume,
+            volume_current: volume,
+            price_open: price,
+            sl,
+            tp,
+            price_current: match side {
+                PositionType::BUY => tick.ask,
+                _ => tick.bid,
+            },
+            price_stoplimit: stoplimit,
+            symbol: info.name.clone(),
+            comment: request.comment.clone().unwrap_or_default(),
+            ..Default::default()
+        });
+
+        Ok(Execution {
+            retcode: ReturnCode::PLACED,
+            order: ticket as usize,
+            deal: 0,
+            volume,
+            price,
+        })
+    }
+
+    fn execute_sltp(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        let ticket = request.position.ok_or(ReturnCode::INVALID)? as isize;
+        let index = self
+            .positions
+            .iter()
+            .position(|position| position.ticket == ticket)
+            .ok_or(ReturnCode::PostionClosed)?;
+        let position = self.positions[index].clone();
+        let info = self.symbol(&position.symbol)?;
+        let tick = self.tick(&position.symbol)?;
+        if self.closed_markets.contains(&position.symbol) {
+            return Err(ReturnCode::MarketClosed);
+        }
+
+        let sl = request.sl.unwrap_or_default();
+        let tp = request.tp.unwrap_or_default();
+        if (sl - position.sl).abs() < EPSILON && (tp - position.tp).abs() < EPSILON {
+            return Err(ReturnCode::NoChanges);
+        }
+
+        let market = match position.r#type {
+            PositionType::BUY => tick.bid,
+            _ => tick.ask,
+        };
+        Self::check_frozen(&info, market, position.sl)?;
+        Self::check_frozen(&info, market, position.tp)?;
+        Self::check_stops(&info, position.r#type, market, sl, tp)?;
+
+        let (time, time_msc) = (self.time(), self.time_msc);
+        let position = &mut self.positions[index];
+        position.sl = sl;
+        position.tp = tp;
+        position.time_update = time;
+        position.time_update_msc = time_msc as isize;
+
+        Ok(Execution {
+            retcode: ReturnCode::DONE,
+            order: 0,
+            deal: 0,
+            volume: position.volume,
+            price: position.price_open,
+        })
+    }
+
+    fn pending_index(&self, request: &TradeRequestBuilder) -> Result<usize, ReturnCode> {
+        let ticket = request.order.ok_or(ReturnCode::INVALID)? as isize;
+        self.orders
+            .iter()
+            .position(|order| order.ticket == ticket)
+            .ok_or(ReturnCode::INVALID)
+    }
+
+    fn execute_modify(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        let index = self.pending_index(request)?;
+        let order = self.orders[index].clone();
+        let info = self.symbol(&order.symbol)?;
+        let tick = self.tick(&order.symbol)?;
+        if self.closed_markets.contains(&order.symbol) {
+            return Err(ReturnCode::MarketClosed);
+        }
+
+        let price = request.price.unwrap_or(order.price_open);
+        let stoplimit = request.stoplimit.unwrap_or(order.price_stoplimit);
+        let sl = request.sl.unwrap_or_default();
+        let tp = request.tp.unwrap_or_default();
+        let expiration = request.expiration.unwrap_or(order.time_expiration);
+        let type_time = request.type_time.unwrap_or(order.type_time);
+        if (price - order.price_open).abs() < EPSILON
+            && (stoplimit - order.price_stoplimit).abs() < EPSILON
+            && (sl - order.sl).abs() < EPSILON
+            && (tp - order.tp).abs() < EPSILON
+            && expiration == order.time_expiration
+        {
+            return Err(ReturnCode::NoChanges);
+        }
+
+        let side = side_of(order.r#type).ok_or(ReturnCode::INVALID)?;
+        let market = match side {
+            PositionType::BUY => tick.ask,
+            _ => tick.bid,
+        };
+        Self::check_frozen(&info, market, order.price_open)?;
+        Self::check_pending_price(&info, &tick, order.r#type, price, stoplimit)?;
+        let open_price = if is_set(stoplimit) { stoplimit } else { price };
+        Self::check_stops(&info, side, open_price, sl, tp)?;
+        if matches!(
+            type_time,
+            OrderTypeTime::SPECIFIED | OrderTypeTime::SpecifiedDay
+        ) && expiration <= self.time()
+        {
+            return Err(ReturnCode::InvalidExpiration);
+        }
+
+        let order = &mut self.orders[index];
+        order.price_open = price;
+        order.price_stoplimit = stoplimit;
+        order.sl = sl;
+        order.tp = tp;
+        order.time_expiration = expiration;
+        order.type_time = type_time;
+
+        Ok(Execution {
+            retcode: ReturnCode::DONE,
+            order: order.ticket as usize,
+            deal: 0,
+            volume: order.volume_current,
+            price,
+        })
+    }
+
+    fn execute_remove(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        let index = self.pending_index(request)?;
+        let order = self.orders[index].clone();
+        let info = self.symbol(&order.symbol)?;
+        let tick = self.tick(&order.symbol)?;
+        let market = match side_of(order.r#type) {
+            Some(PositionType::SELL) => tick.bid,
+            _ => tick.ask,
+        };
+        Self::check_frozen(&info, market, order.price_open)?;
+        self.finish_order(index, OrderState::CANCELED);
+
+        Ok(Execution {
+            retcode: ReturnCode::DONE,
+            order: order.ticket as usize,
+            deal: 0,
+            volume: order.volume_current,
+            price: order.price_open,
+        })
+    }
+
+    fn execute_close_by(&mut self, request: &TradeRequestBuilder) -> Result<Execution, ReturnCode> {
+        if !self.hedging() {
+            return Err(ReturnCode::INVALID);
+        }
+        let find = |ticket: Option<usize>| -> Result<Position, ReturnCode> {
+            let ticket = ticket.ok_or(ReturnCode::INVALID)? as isize;
+            self.positions
+                .iter()
+                .find(|position| position.ticket == ticket)
+                .cloned()
+                .ok_or(ReturnCode::PostionClosed)
+        };
+        let position = find(request.position)?;
+        let position_by = find(request.position_by)?;
+        if position.symbol != position_by.symbol || position.r#type == position_by.r#type {
+            return Err(ReturnCode::INVALID);
+        }
+        let info = self.symbol(&position.symbol)?;
+        if self.closed_markets.contains(&position.symbol) {
+            return Err(ReturnCode::MarketClosed);
+        }
+
+        let volume = position.volume.min(position_by.volume);
+        let order = self.next_ticket();
+        let fill = Fill {
+            symbol: position.symbol.clone(),
+            side: opposite(position.r#type),
+            volume,
+            price: position_by.price_open,
+            order,
+            position: Some(position.ticket),
+            sl: 0.0,
+            tp: 0.0,
+            magic: request.magic.unwrap_or_default() as isize,
+            comment: request.comment.clone().unwrap_or_default(),
+            reason: DealReason::EXPERT,
+        };
+        self.record_order(&fill, OrderType::CloseBy, OrderReason::EXPERT);
+
+        let index = self
+            .positions
+            .iter()
+            .position(|existing| existing.ticket == position.ticket)
+            .ok_or(ReturnCode::PostionClosed)?;
+        let deal = self.close_volume(
+            &fill,
+            &info,
+            index,
+            volume,
+            position_by.price_open,
+            DealEntry::OutBy,
+        );
+        let index = self
+            .positions
+            .iter()
+            .position(|existing| existing.ticket == position_by.ticket)
+            .ok_or(ReturnCode::PostionClosed)?;
+        self.close_volume(
+            &fill,
+            &info,
+            index,
+            volume,
+            position.price_open,
+            DealEntry::OutBy,
+        );
+
+        Ok(Execution {
+            retcode: ReturnCode::DONE,
+            order: order as usize,
+            deal: deal as usize,
+            volume,
+            price: position_by.price_open,
+        })
+    }
+
+    fn finish_order(&mut self, index: usize, state: OrderState) {
+        let mut order = self.orders.remove(index);
+        order.state = state;
+        order.time_done = self.time();
+        order.time_done_msc = self.time_msc as isize;
+        self.history_orders.push(order);
+    }
+
+    /// Expires, triggers and fills pending orders on `symbol` against its current tick.
+    fn process_orders(&mut self, symbol: &str) {
+        let Ok(tick) = self.tick(symbol) else {
+            return;
+        };
+        let mut index = 0;
+        while index < self.orders.len() {
+            let order = self.orders[index].clone();
+            if order.symbol != symbol {
+                index += 1;
+                continue;
+            }
+            if matches!(
+                order.type_time,
+                OrderTypeTime::SPECIFIED | OrderTypeTime::SpecifiedDay
+            ) && order.time_expiration > DateTime::UNIX_EPOCH
+                && order.time_expiration <= self.time()
+            {
+                self.finish_order(index, OrderState::EXPIRED);
+                continue;
+            }
+
+            let triggered = match order.r#type {
+                OrderType::BuyLimit => tick.ask <= order.price_open + EPSILON,
+                OrderType::SellLimit => tick.bid >= order.price_open - EPSILON,
+                OrderType::BuyStop | OrderType::BuyStopLimit => {
+                    tick.ask >= order.price_open - EPSILON
+                }
+                OrderType::SellStop | OrderType::SellStopLimit => {
+                    tick.bid <= order.price_open + EPSILON
+                }
+                _ => false,
+            };
+            if !triggered {
+                self.orders[index].price_current = match side_of(order.r#type) {
+                    Some(PositionType::SELL) => tick.bid,
+                    _ => tick.ask,
+                };
+                index += 1;
+                continue;
+            }
+
+            if matches!(
+                order.r#type,
+                OrderType::BuyStopLimit | OrderType::SellStopLimit
+            ) {
+                let pending = &mut self.orders[index];
+                pending.r#type = match order.r#type {
+                    OrderType::BuyStopLimit => OrderType::BuyLimit,
+                    _ => OrderType::SellLimit,
+                };
+                pending.price_open = order.price_stoplimit;
+                pending.price_stoplimit = 0.0;
+                continue;
+            }
+
+            let Some(side) = side_of(order.r#type) else {
+                index += 1;
+                continue;
+            };
+            let price = match order.r#type {
+                OrderType::BuyLimit | OrderType::SellLimit => order.price_open,
+                _ if side == PositionType::BUY => tick.ask,
+                _ => tick.bid,
+            };
+            self.orders.remove(index);
+            let fill = Fill {
+                symbol: order.symbol.clone(),
+                side,
+                volume: order.volume_current,
+                price,
+                order: order.ticket,
+                position: None,
+                sl: order.sl,
+                tp: order.tp,
+                magic: order.magic,
+                comment: order.comment.clone(),
+                reason: DealReason::EXPERT,
+            };
+            let state = match self.fill(fill, false) {
+                Ok(_) => OrderState::FILLED,
+                Err(_) => OrderState::REJECTED,
+            };
+            self.orders.insert(index, order);
+            self.finish_order(index, state);
+        }
+    }
+
+    /// Closes positions on `symbol` whose stop loss or take profit was hit.
+    fn process_stops(&mut self, symbol: &str) {
+        let Ok(tick) = self.tick(symbol) else {
+            return;
+        };
+        let Ok(info) = self.symbol(symbol) else {
+            return;
+        };
+        let mut index = 0;
+        while index < self.positions.len() {
+            let position = self.positions[index].clone();
+            if position.symbol != symbol {
+                index += 1;
+                continue;
+            }
+            let (market, hit_sl, hit_tp) = match position.r#type {
+                PositionType::BUY => (
+                    tick.bid,
+                    is_set(position.sl) && tick.bid <= position.sl + EPSILON,
+                    is_set(position.tp) && tick.bid >= position.tp - EPSILON,
+                ),
+                _ => (
+                    tick.ask,
+                    is_set(position.sl) && tick.ask >= position.sl - EPSILON,
+                    is_set(position.tp) && tick.ask <= position.tp + EPSILON,
+                ),
+            };
+            let (deal_reason, order_reason) = match (hit_sl, hit_tp) {
+                (true, _) => (DealReason::SL, OrderReason::SL),
+                (_, true) => (DealReason::TP, OrderReason::TP),
+                _ => {
+                    index += 1;
+                    continue;
+                }
+            };
+            let order = self.next_ticket();
+            let fill = Fill {
+                symbol: position.symbol.clone(),
+                side: opposite(position.r#type),
+                volume: position.volume,
+                price: market,
+                order,
+                position: Some(position.ticket),
+                sl: 0.0,
+                tp: 0.0,
+                magic: position.magic,
+                comment: position.comment.clone(),
+                reason: deal_reason,
+            };
+            self.record_order(&fill, market_order_type(fill.side), order_reason);
+            self.close_volume(&fill, &info, index, position.volume, market, DealEntry::OUT);
+        }
+    }
+
+    /// Revalues open positions and recomputes equity, margin and margin level.
+    fn refresh_account(&mut self) {
+        let mut profit = 0.0;
+        let mut margin = 0.0;
+        for index in 0..self.positions.len() {
+            let position = self.positions[index].clone();
+            let (Ok(info), Ok(tick)) = (self.symbol(&position.symbol), self.tick(&position.symbol))
+            else {
+                continue;
+            };
+            let price_current = match position.r#type {
+                PositionType::BUY => tick.bid,
+                _ => tick.ask,
+            };
+            let position_profit = self.profit(
+                &info,
+                position.r#type,
+                position.volume,
+                position.price_open,
+                price_current,
+            );
+            margin += self.margin(&info, position.volume, position.price_open);
+            profit += position_profit + position.swap;
+            let position = &mut self.positions[index];
+            position.price_current = price_current;
+            position.profit = position_profit;
+        }
+        let account = &mut self.account;
+        account.profit = profit;
+        account.equity = account.balance + account.credit + profit;
+        account.margin = margin;
+        account.margin_free = account.equity - margin;
+        account.margin_level = if margin > 0.0 {
+            account.equity / margin * 100.0
+        } else {
+            0.0
+        };
+    }
+
+    /// Closes the most losing position while the margin level is below the stop out level.
+    fn process_stop_out(&mut self) {
+        while self.account.margin_so_mode == AccountStopOutMode::PERCENT
+            && self.account.margin_so_so > 0.0
+            && self.account.margin > 0.0
+            && self.account.margin_level < self.account.margin_so_so
+        {
+            let Some(index) = self
+                .positions
+                .iter()
+                .enumerate()
+                .min_by(|(_, left), (_, right)| left.profit.total_cmp(&right.profit))
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            let position = self.positions[index].clone();
+            let Ok(info) = self.symbol(&position.symbol) else {
+                break;
+            };
+            let order = self.next_ticket();
+            let fill = Fill {
+                symbol: position.symbol.clone(),
+                side: opposite(position.r#type),
+                volume: position.volume,
+                price: position.price_current,
+                order,
+                position: Some(position.ticket),
+                sl: 0.0,
+                tp: 0.0,
+                magic: position.magic,
+                comment: "so".to_string(),
+                reason: DealReason::SO,
+            };
+            self.record_order(&fill, market_order_type(fill.side), OrderReason::SO);
+            self.close_volume(
+                &fill,
+                &info,
+                index,
+                position.volume,
+                position.price_current,
+                DealEntry::OUT,
+            );
+            self.refresh_account();
+        }
+    }
+}
+
+/// Simulated trade server for paper trading and backtesting.
+///
+/// `SimulatedBroker` executes [`TradeRequestBuilder`] requests the way a MetaTrader5
+/// trade server would: market orders fill at the current simulated bid/ask, pending
+/// orders (limit, stop and stop-limit) wait until a tick triggers them, stop loss and
+/// take profit levels close positions, and invalid requests are answered with the
+/// matching [`ReturnCode`] such as `NoMoney`, `InvalidStops` or `MarketClosed`.
+/// Hedging and netting follow the account's `margin_mode`.
+///
+/// Prices are driven with [`SimulatedBroker::update_tick`], which also advances the
+/// simulated clock and records the tick for `copy_ticks_*`. Bars for `copy_rates_*`
+/// are fed with [`SimulatedBroker::with_rates`] and [`SimulatedBroker::push_rate`].
+/// The broker implements [`Terminal`], so it can stand in for a live connection.
+/// Clones share the same state.
+///
+/// ```rust
+/// use fishing_line::prelude::*;
+///
+/// let broker = SimulatedBroker::new(AccountInfo {
+///     balance: 10_000.0,
+///     leverage: 100,
+///     currency: "USD".to_string(),
+///     margin_mode: AccountMarginMode::RetailHedging,
+///     ..Default::default()
+/// })
+/// .with_symbol(SymbolInfo {
+///     name: "EURUSD".to_string(),
+///     point: 0.00001,
+///     trade_contract_size: 100_000.0,
+///     volume_min: 0.01,
+///     volume_max: 100.0,
+///     volume_step: 0.01,
+///     currency_base: "EUR".to_string(),
+///     currency_margin: "EUR".to_string(),
+///     currency_profit: "USD".to_string(),
+///     ..Default::default()
+/// });
+///
+/// broker.update_tick("EURUSD", SymbolTick { bid: 1.1000, ask: 1.1002, ..Default::default() });
+///
+/// let result = broker
+///     .order_send(
+///         TradeRequestBuilder::new()
+///             .action(TradeActionRequest::DEAL)
+///             .symbol("EURUSD".to_string())
+///             .volume(0.1)
+///             .r#type(OrderType::BUY),
+///     )
+///     .unwrap();
+/// assert_eq!(result.retcode, ReturnCode::DONE);
+/// assert_eq!(broker.positions_total().unwrap(), 1);
+/// ```
+#[derive(Clone)]
+pub struct SimulatedBroker {
+    state: Arc<Mutex<BrokerState>>,
+    prices: Arc<Mutex<PriceHistory>>,
+}
+
+impl SimulatedBroker {
+    pub fn new(account: AccountInfo) -> Self {
+        let mut state = BrokerState {
+            account,
+            terminal_info: TerminalInfo {
+                connected: true,
+                trade_allowed: true,
+                name: "SimulatedBroker".to_string(),
+                ..Default::default()
+            },
+            version: TerminalVersion::default(),
+            symbols: Vec::new(),
+            ticks: Vec::new(),
+            closed_markets: HashSet::new(),
+            orders: Vec::new(),
+            positions: Vec::new(),
+            history_orders: Vec::new(),
+            deals: Vec::new(),
+            next_ticket: 1,
+            time_msc: 0,
+        };
+        state.refresh_account();
+        SimulatedBroker {
+            state: Arc::new(Mutex::new(state)),
+            prices: Arc::new(Mutex::new(PriceHistory::default())),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, BrokerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn prices(&self) -> MutexGuard<'_, PriceHistory> {
+        self.prices
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn with_symbol(self, symbol: SymbolInfo) -> Self {
+        self.add_symbol(symbol);
+        self
+    }
+
+    /// Replaces the connected, trade-allowed `TerminalInfo` reported by default.
+    pub fn with_terminal_info(self, terminal_info: TerminalInfo) -> Self {
+        self.state().terminal_info = terminal_info;
+        self
+    }
+
+    pub fn with_version(self, version: TerminalVersion) -> Self {
+        self.state().version = version;
+        self
+    }
+
+    /// Sets the bar history `copy_rates_*` serve for `symbol` and `timeframe`,
+    /// oldest first.
+    pub fn with_rates(self, symbol: &str, timeframe: Timeframe, rates: Vec<SymbolRates>) -> Self {
+        self.prices()
+            .rates
+            .insert((symbol.to_string(), timeframe as i64), rates);
+        self
+    }
+
+    /// Appends a bar to the history of `symbol` and `timeframe`.
+    pub fn push_rate(&self, symbol: &str, timeframe: Timeframe, rate: SymbolRates) {
+        self.prices()
+            .rates
+            .entry((symbol.to_string(), timeframe as i64))
+            .or_default()
+            .push(rate);
+    }
+
+    /// Adds a tradable symbol or replaces the one with the same name.
+    pub fn add_symbol(&self, symbol: SymbolInfo) {
+        let mut state = self.state();
+        match state
+            .symbols
+            .iter_mut()
+            .find(|info| info.name == symbol.name)
+        {
+            Some(existing) => *existing = symbol,
+            None => state.symbols.push(symbol),
+        }
+    }
+
+    /// Feeds a new quote, then triggers pending orders, SL/TP and stop out for the symbol.
+    ///
+    /// The simulated clock moves to the tick's `time_msc` (or `time`) when it is later
+    /// than the current time.
+    pub fn update_tick(&self, symbol: &str, tick: SymbolTick) {
+        let mut state = self.state();
+        let time_msc = if tick.time_msc != 0 {
+            tick.time_msc
+        } else {
+            tick.time * 1000
+        };
+        state.time_msc = state.time_msc.max(time_msc);
+        self.prices()
+            .ticks
+            .entry(symbol.to_string())
+            .or_default()
+            .push(SymbolTick {
+                time_msc,
+                ..tick.clone()
+            });
+        if let Some(info) = state.symbols.iter_mut().find(|info| info.name == symbol) {
+            info.bid = tick.bid;
+            info.ask = tick.ask;
+            info.last = tick.last;
+            info.time = tick.time;
+        }
+        match state.ticks.iter_mut().find(|(name, _)| name == symbol) {
+            Some((_, existing)) => *existing = tick,
+            None => state.ticks.push((symbol.to_string(), tick)),
+        }
+        state.process_orders(symbol);
+        state.process_stops(symbol);
+        state.refresh_account();
+        state.process_stop_out();
+    }
+
+    /// Opens or closes the market for `symbol`; closed markets reject with `MarketClosed`.
+    pub fn set_market_open(&self, symbol: &str, open: bool) {
+        let mut state = self.state();
+        if open {
+            state.closed_markets.remove(symbol);
+        } else {
+            state.closed_markets.insert(symbol.to_string());
+        }
+    }
+
+    fn respond(&self, request: &TradeRequestBuilder, dry_run: bool) -> (TradeResult, AccountInfo) {
+        let mut guard = self.state();
+        let mut scratch;
+        let state: &mut BrokerState = if dry_run {
+            scratch = guard.clone();
+            &mut scratch
+        } else {
+            &mut guard
+        };
+
+        let tick = request
+            .symbol
+            .as_deref()
+            .and_then(|symbol| state.tick(symbol).ok())
+            .unwrap_or_default();
+        let outcome = state.execute(request);
+        state.refresh_account();
+
+        let result = match outcome {
+            Ok(execution) => TradeResult {
+                retcode: execution.retcode,
+                deal: execution.deal,
+                order: execution.order,
+                volume: execution.volume,
+                price: execution.price,
+                bid: tick.bid,
+                ask: tick.ask,
+                comment: execution.retcode.to_string(),
+                request_id: state.next_ticket as u64,
+                retcode_external: 0,
+                request: TradeRequest::from(request.clone()),
+            },
+            Err(retcode) => TradeResult {
+                retcode,
+                bid: tick.bid,
+                ask: tick.ask,
+                comment: retcode.to_string(),
+                request_id: state.next_ticket as u64,
+                request: TradeRequest::from(request.clone()),
+                ..Default::default()
+            },
+        };
+        (result, state.account.clone())
+    }
+}
+
+impl ErrorTrait for SimulatedBroker {
+    fn last_error(&self) -> MQLResult<(RuntimeError, String)> {
+        Ok((RuntimeError::Ok, "Success".to_string()))
+    }
+}
+
+impl AccountInfoTrait for SimulatedBroker {
+    fn account_info(&self) -> MQLResult<AccountInfo> {
+        Ok(self.state().account.clone())
+    }
+}
+
+impl TerminalInfoTrait for SimulatedBroker {
+    fn terminal_info(&self) -> MQLResult<TerminalInfo> {
+        Ok(self.state().terminal_info.clone())
+    }
+
+    fn version(&self) -> MQLResult<TerminalVersion> {
+        Ok(self.state().version.clone())
+    }
+}
+
+impl SymbolInfoTrait for SimulatedBroker {
+    fn symbols_total(&self) -> MQLResult<i32> {
+        Ok(self.state().symbols.len() as i32)
+    }
+
+    fn symbols_get(&self, group: Option<&str>) -> MQLResult<Vec<SymbolInfo>> {
+        Ok(self
+            .state()
+            .symbols
+            .iter()
+            .filter(|info| group.is_none_or(|group| group_matches(group, &info.name)))
+            .cloned()
+            .collect())
+    }
+
+    fn symbol_info(&self, symbol: &str) -> MQLResult<SymbolInfo> {
+        self.state().symbol(symbol).map_err(|_| {
+            FishingLineError::runtime(
+                RuntimeError::NotFound,
+                format!("Symbol `{}` not found", symbol),
+            )
+        })
+    }
+
+    fn symbol_info_tick(&self, symbol: &str) -> MQLResult<SymbolTick> {
+        self.state().tick(symbol).map_err(|_| {
+            FishingLineError::runtime(RuntimeError::NotFound, format!("No tick for `{}`", symbol))
+        })
+    }
+
+    fn symbol_select(&self, symbol: &str, enable: Option<bool>) -> MQLResult<bool> {
+        let mut state = self.state();
+        let info = state
+            .symbols
+            .iter_mut()
+            .find(|info| info.name == symbol)
+            .ok_or_else(|| {
+                FishingLineError::runtime(
+                    RuntimeError::NotFound,
+                    format!("Symbol `{}` not found", symbol),
+                )
+            })?;
+        info.select = enable.unwrap_or(true);
+        info.visible = info.select;
+        Ok(true)
+    }
+}
+
+impl SymbolRatesTrait for SimulatedBroker {
+    fn copy_rates_from(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        Ok(rates_from(
+            self.prices().rates(symbol, timeframe)?,
+            date_from,
+            count,
+        ))
+    }
+
+    fn copy_rates_from_pos(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        Ok(rates_from_pos(
+            self.prices().rates(symbol, timeframe)?,
+            start_pos,
+            count,
+        ))
+    }
+
+    fn copy_rates_range(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<SymbolRates>> {
+        Ok(rates_range(
+            self.prices().rates(symbol, timeframe)?,
+            date_from,
+            date_to,
+        ))
+    }
+}
+
+impl SymbolTicksTrait for SimulatedBroker {
+    fn copy_ticks_from(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        Ok(ticks_from(
+            self.prices().ticks(symbol)?,
+            date_from,
+            count,
+            flags,
+        ))
+    }
+
+    fn copy_ticks_range(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: CopyTicksFlags,
+    ) -> MQLResult<Vec<SymbolTick>> {
+        Ok(ticks_range(
+            self.prices().ticks(symbol)?,
+            date_from,
+            date_to,
+            flags,
+        ))
+    }
+}
+
+impl OrderTrait for SimulatedBroker {
+    fn orders_total(&self) -> MQLResult<i64> {
+        Ok(self.state().orders.len() as i64)
+    }
+
+    fn orders_get(&self) -> MQLResult<Vec<Order>> {
+        Ok(self.state().orders.clone())
+    }
+
+    fn order_calc_margin(
+        &self,
+        _action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price: f64,
+    ) -> MQLResult<f64> {
+        let state = self.state();
+        let info = state.symbol(symbol).map_err(|_| {
+            FishingLineError::runtime(
+                RuntimeError::NotFound,
+                format!("Symbol `{}` not found", symbol),
+            )
+        })?;
+        Ok(state.margin(&info, volume, price))
+    }
+
+    /// `action` is the position direction: `0` for a buy and `1` for a sell, like the
+    /// `ORDER_TYPE_BUY`/`ORDER_TYPE_SELL` values MetaTrader5 takes here.
+    fn order_calc_profit(
+        &self,
+        action: TradeActionRequest,
+        symbol: &str,
+        volume: f64,
+        price_open: f64,
+        price_close: f64,
+    ) -> MQLResult<f64> {
+        let state = self.state();
+        let info = state.symbol(symbol).map_err(|_| {
+            FishingLineError::runtime(
+                RuntimeError::NotFound,
+                format!("Symbol `{}` not found", symbol),
+            )
+        })?;
+        let side = match action.value() {
+            0 => PositionType::BUY,
+            1 => PositionType::SELL,
+            value => {
+                return Err(FishingLineError::runtime(
+                    RuntimeError::InvalidParams,
+                    format!("Invalid position direction `{}`", value),
+                ))
+            }
+        };
+        Ok(state.profit(&info, side, volume, price_open, price_close))
+    }
+
+    fn order_check(&self, request: &TradeRequestBuilder) -> MQLResult<CheckResult> {
+        let (result, account) = self.respond(request, true);
+        let retcode = match result.retcode {
+            ReturnCode::DONE | ReturnCode::PLACED => ReturnCode::CHECKED,
+            retcode => retcode,
+        };
+        Ok(CheckResult {
+            retcode,
+            balance: account.balance,
+            equity: account.equity,
+            profit: account.profit,
+            margin: account.margin,
+            margin_free: account.margin_free,
+            margin_level: account.margin_level,
+            comment: retcode.to_string(),
+            request: result.request,
+        })
+    }
+
+    fn order_send(&self, request: TradeRequestBuilder) -> MQLResult<TradeResult> {
+        check_trade_result(self.respond(&request, false).0)
+    }
+}
+
+impl PositionTrait for SimulatedBroker {
+    fn positions_total(&self) -> MQLResult<i64> {
+        Ok(self.state().positions.len() as i64)
+    }
+
+    fn positions_get(&self) -> MQLResult<Vec<Position>> {
+        Ok(self.state().positions.clone())
+    }
+}
+
+impl HistoryTrait for SimulatedBroker {
+    fn history_orders_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        Ok(self.history_orders_get(date_from, date_to)?.len() as i64)
+    }
+
+    fn history_orders_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Order>> {
+        Ok(self
+            .state()
+            .history_orders
+            .iter()
+            .filter(|order| order.time_setup >= date_from && order.time_setup <= date_to)
+            .cloned()
+            .collect())
+    }
+
+    fn history_deals_total(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<i64> {
+        Ok(self.history_deals_get(date_from, date_to)?.len() as i64)
+    }
+
+    fn history_deals_get(
+        &self,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<Vec<Deals>> {
+        Ok(self
+            .state()
+            .deals
+            .iter()
+            .filter(|deal| deal.time >= date_from && deal.time <= date_to)
+            .cloned()
+            .collect())
+    }
+
+    fn history_orders_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Order>> {
+        Ok(self
+            .state()
+            .history_orders
+            .iter()
+            .filter(|order| query.matches_order(order))
+            .cloned()
+            .collect())
+    }
+
+    fn history_deals_query(&self, query: &HistoryQuery) -> MQLResult<Vec<Deals>> {
+        Ok(self
+            .state()
+            .deals
+            .iter()
+            .filter(|deal| query.matches_deal(deal))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::DateTime;
+
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    fn broker(margin_mode: AccountMarginMode) -> SimulatedBroker {
+        let broker = SimulatedBroker::new(AccountInfo {
+            balance: 10_000.0,
+            leverage: 100,
+            currency: "USD".to_string(),
+            margin_mode,
+            margin_so_mode: AccountStopOutMode::PERCENT,
+            margin_so_so: 50.0,
+            trade_allowed: true,
+            ..Default::default()
+        })
+        .with_symbol(eurusd());
+        quote(&broker, 1, 1.1000, 1.1002);
+        broker
+    }
+
+    fn quote(broker: &SimulatedBroker, time: i64, bid: f64, ask: f64) {
+        broker.update_tick(
+            "EURUSD",
+            SymbolTick {
+                time,
+                time_msc: time * 1000,
+                bid,
+                ask,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn market(order_type: OrderType, volume: f64) -> TradeRequestBuilder {
+        TradeRequestBuilder::new()
+            .action(TradeActionRequest::DEAL)
+            .symbol("EURUSD".to_string())
+            .volume(volume)
+            .r#type(order_type)
+    }
+
+    fn retcode(result: MQLResult<TradeResult>) -> ReturnCode {
+        match result {
+            Ok(result) => result.retcode,
+            Err(error) => error
+                .retcode()
+                .expect("Request failed without a return code"),
+        }
+    }
+
+    fn pending(order_type: OrderType, price: f64) -> TradeRequestBuilder {
+        TradeRequestBuilder::new()
+            .action(TradeActionRequest::PENDING)
+            .symbol("EURUSD".to_string())
+            .volume(0.1)
+            .price(price)
+            .r#type(order_type)
+    }
+
+    #[test]
+    fn test_simulated_market_round_trip() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+
+        let result = broker.order_send(market(OrderType::BUY, 1.0)).unwrap();
+        assert_eq!(result.retcode, ReturnCode::DONE);
+        assert!(
+            (result.price - 1.1002).abs() < 1e-9,
+            "Buy did not fill at ask"
+        );
+
+        let account = broker.account_info().unwrap();
+        assert!((account.margin - 1100.2).abs() < 1e-6, "Unexpected margin");
+
+        quote(&broker, 2, 1.1052, 1.1054);
+        let position = broker.positions_get().unwrap().remove(0);
+        assert!(
+            (position.profit - 500.0).abs() < 1e-6,
+            "Unexpected floating profit"
+        );
+        assert!((broker.account_info().unwrap().equity - 10_500.0).abs() < 1e-6);
+
+        let result = broker
+            .order_send(market(OrderType::SELL, 1.0).position(position.ticket as usize))
+            .unwrap();
+        assert_eq!(result.retcode, ReturnCode::DONE);
+        assert_eq!(broker.positions_total().unwrap(), 0);
+
+        let account = broker.account_info().unwrap();
+        assert!(
+            (account.balance - 10_500.0).abs() < 1e-6,
+            "Profit was not realised"
+        );
+        assert_eq!(account.margin, 0.0);
+
+        let deals = broker.state().deals.clone();
+        assert_eq!(deals.len(), 2);
+        assert_eq!(deals[0].entry, DealEntry::IN);
+        assert_eq!(deals[1].entry, DealEntry::OUT);
+        assert_eq!(deals[1].position_id, position.identifier);
+    }
+
+    #[test]
+    fn test_simulated_broker_is_a_terminal() {
+        let broker = broker(AccountMarginMode::RetailHedging).with_rates(
+            "EURUSD",
+            Timeframe::M1,
+            vec![SymbolRates {
+                close: 1.1001,
+                ..Default::default()
+            }],
+        );
+        quote(&broker, 2, 1.1001, 1.1003);
+        let terminal: Box<dyn Terminal> = Box::new(broker.clone());
+
+        assert!(terminal.terminal_info().unwrap().connected);
+        let ticks = terminal
+            .copy_ticks_from("EURUSD", DateTime::UNIX_EPOCH, 10, CopyTicksFlags::ALL)
+            .unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[1].time_msc, 2000);
+        let rates = terminal
+            .copy_rates_from_pos("EURUSD", Timeframe::M1, 0, 10)
+            .unwrap();
+        assert_eq!(rates.len(), 1);
+
+        let opened = terminal.order_send(market(OrderType::BUY, 0.5)).unwrap();
+        let reversal = terminal.reverse_position(opened.order).unwrap();
+        assert!(reversal.close.is_some());
+        let reversed = terminal.positions_get().unwrap();
+        assert_eq!(reversed.len(), 1);
+        assert_eq!(
+            (reversed[0].r#type, reversed[0].volume),
+            (PositionType::SELL, 0.5)
+        );
+
+        terminal
+            .modify_position_stops(reversed[0].ticket as usize, 1.1100, 1.0900)
+            .unwrap()
+            .unwrap();
+        assert_eq!(terminal.positions_get().unwrap()[0].sl, 1.11);
+
+        let results = terminal.close_all(&TradeFilter::new()).unwrap();
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(terminal.positions_total().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_simulated_rejections() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+
+        let retcode = |request| retcode(broker.order_send(request));
+        assert_eq!(retcode(market(OrderType::BUY, 10.0)), ReturnCode::NoMoney);
+        assert_eq!(
+            retcode(market(OrderType::BUY, 0.015)),
+            ReturnCode::InvalidVolume
+        );
+        assert_eq!(
+            retcode(market(OrderType::BUY, 0.1).sl(1.1000)),
+            ReturnCode::InvalidStops
+        );
+        assert_eq!(
+            retcode(market(OrderType::BUY, 0.1).position(99)),
+            ReturnCode::PostionClosed
+        );
+        assert_eq!(
+            retcode(pending(OrderType::BuyLimit, 1.1010)),
+            ReturnCode::InvalidPrice
+        );
+
+        broker.set_market_open("EURUSD", false);
+        assert_eq!(
+            retcode(market(OrderType::BUY, 0.1)),
+            ReturnCode::MarketClosed
+        );
+        broker.set_market_open("EURUSD", true);
+
+        broker.add_symbol(SymbolInfo {
+            trade_mode: SymbolTradeMode::SymbolTradeModeLongonly,
+            ..eurusd()
+        });
+        assert_eq!(retcode(market(OrderType::SELL, 0.1)), ReturnCode::LongOnly);
+
+        assert_eq!(broker.positions_total().unwrap(), 0);
+        assert_eq!(broker.state().deals.len(), 0);
+    }
+
+    #[test]
+    fn test_simulated_pending_orders() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+
+        let limit = broker
+            .order_send(pending(OrderType::BuyLimit, 1.0950).tp(1.1100))
+            .unwrap();
+        assert_eq!(limit.retcode, ReturnCode::PLACED);
+        let stop_limit = broker
+            .order_send(pending(OrderType::SellStopLimit, 1.0900).stoplimit(1.0920))
+            .unwrap();
+        assert_eq!(stop_limit.retcode, ReturnCode::PLACED);
+        assert_eq!(broker.orders_total().unwrap(), 2);
+
+        quote(&broker, 2, 1.0948, 1.0950);
+        let positions = broker.positions_get().unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].ticket, limit.order as isize);
+        assert!((positions[0].price_open - 1.0950).abs() < 1e-9);
+
+        quote(&broker, 3, 1.0899, 1.0901);
+        let orders = broker.orders_get().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].r#type, OrderType::SellLimit);
+        assert!((orders[0].price_open - 1.0920).abs() < 1e-9);
+
+        let removed = broker
+            .order_send(
+                TradeRequestBuilder::new()
+                    .action(TradeActionRequest::REMOVE)
+                    .order(stop_limit.order),
+            )
+            .unwrap();
+        assert_eq!(removed.retcode, ReturnCode::DONE);
+        assert_eq!(broker.orders_total().unwrap(), 0);
+
+        let states: Vec<OrderState> = broker
+            .state()
+            .history_orders
+            .iter()
+            .map(|order| order.state)
+            .collect();
+        assert_eq!(states, vec![OrderState::FILLED, OrderState::CANCELED]);
+    }
+
+    #[test]
+    fn test_simulated_stop_loss_and_take_profit() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+
+        broker
+            .order_send(market(OrderType::BUY, 0.1).sl(1.0950).tp(1.1100))
+            .unwrap();
+        broker
+            .order_send(market(OrderType::SELL, 0.1).sl(1.1100).tp(1.0950))
+            .unwrap();
+        assert_eq!(broker.positions_total().unwrap(), 2);
+
+        quote(&broker, 2, 1.0948, 1.0950);
+        let positions = broker.positions_get().unwrap();
+        assert_eq!(positions.len(), 0);
+
+        let reasons: Vec<DealReason> = broker
+            .state()
+            .deals
+            .iter()
+            .filter(|deal| deal.entry == DealEntry::OUT)
+            .map(|deal| deal.reason)
+            .collect();
+        assert_eq!(reasons, vec![DealReason::SL, DealReason::TP]);
+    }
+
+    #[test]
+    fn test_simulated_modify_stops() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+        let result = broker.order_send(market(OrderType::BUY, 0.1)).unwrap();
+
+        let sltp = |sl: f64, tp: f64| {
+            retcode(
+                broker.order_send(
+                    TradeRequestBuilder::new()
+                        .action(TradeActionRequest::SLTP)
+                        .position(result.order)
+                        .sl(sl)
+                        .tp(tp),
+                ),
+            )
+        };
+        assert_eq!(sltp(1.0900, 1.1100), ReturnCode::DONE);
+        assert_eq!(sltp(1.0900, 1.1100), ReturnCode::NoChanges);
+        assert_eq!(sltp(1.1005, 1.1100), ReturnCode::InvalidStops);
+
+        let position = broker.positions_get().unwrap().remove(0);
+        assert!((position.sl - 1.0900).abs() < 1e-9);
+        assert!((position.tp - 1.1100).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simulated_netting() {
+        let broker = broker(AccountMarginMode::RetailNetting);
+
+        broker.order_send(market(OrderType::BUY, 1.0)).unwrap();
+        quote(&broker, 2, 1.1010, 1.1012);
+        broker.order_send(market(OrderType::BUY, 1.0)).unwrap();
+
+        let positions = broker.positions_get().unwrap();
+        assert_eq!(positions.len(), 1);
+        assert!((positions[0].volume - 2.0).abs() < 1e-9);
+        assert!((positions[0].price_open - 1.1007).abs() < 1e-9);
+
+        broker.order_send(market(OrderType::SELL, 3.0)).unwrap();
+        let positions = broker.positions_get().unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].r#type, PositionType::SELL);
+        assert!((positions[0].volume - 1.0).abs() < 1e-9);
+
+        let account = broker.account_info().unwrap();
+        assert!(
+            (account.balance - 10_060.0).abs() < 1e-6,
+            "Unexpected balance"
+        );
+        assert_eq!(broker.state().deals[2].entry, DealEntry::INOUT);
+    }
+
+    #[test]
+    fn test_simulated_close_by() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+        let buy = broker.order_send(market(OrderType::BUY, 1.0)).unwrap();
+        quote(&broker, 2, 1.1050, 1.1052);
+        let sell = broker.order_send(market(OrderType::SELL, 0.5)).unwrap();
+
+        let result = broker
+            .order_send(
+                TradeRequestBuilder::new()
+                    .action(TradeActionRequest::CloseBy)
+                    .position(buy.order)
+                    .position_by(sell.order),
+            )
+            .unwrap();
+        assert_eq!(result.retcode, ReturnCode::DONE);
+
+        let positions = broker.positions_get().unwrap();
+        assert_eq!(positions.len(), 1);
+        assert!((positions[0].volume - 0.5).abs() < 1e-9);
+
+        let deals = broker.state().deals.clone();
+        assert_eq!(deals[2].position_id, buy.order as isize);
+        assert_eq!(deals[2].entry, DealEntry::OutBy);
+        assert!((deals[2].price - 1.1050).abs() < 1e-9);
+        assert!((deals[2].profit - 240.0).abs() < 1e-6);
+        assert_eq!(deals[3].position_id, sell.order as isize);
+        assert_eq!(deals[3].entry, DealEntry::OutBy);
+        assert!((deals[3].price - 1.1002).abs() < 1e-9);
+        assert!((deals[3].profit - 240.0).abs() < 1e-6);
+        assert!((broker.account_info().unwrap().balance - 10_480.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_simulated_order_check_is_dry_run() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+
+        let check = broker.order_check(&market(OrderType::BUY, 1.0)).unwrap();
+        assert_eq!(check.retcode, ReturnCode::CHECKED);
+        assert!((check.margin - 1100.2).abs() < 1e-6);
+        assert_eq!(broker.positions_total().unwrap(), 0);
+
+        let check = broker.order_check(&market(OrderType::BUY, 10.0)).unwrap();
+        assert_eq!(check.retcode, ReturnCode::NoMoney);
+    }
+
+    #[test]
+    fn test_simulated_order_calc_profit() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+        let profit = |action: i64| {
+            broker.order_calc_profit(
+                TradeActionRequest::from_value(action),
+                "EURUSD",
+                1.0,
+                1.1000,
+                1.1050,
+            )
+        };
+
+        assert!((profit(0).unwrap() - 500.0).abs() < 1e-6);
+        assert!((profit(1).unwrap() + 500.0).abs() < 1e-6);
+        assert!(matches!(
+            profit(5),
+            Err(FishingLineError::Runtime {
+                code: RuntimeError::InvalidParams,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_simulated_stop_out() {
+        let broker = broker(AccountMarginMode::RetailHedging);
+        broker.order_send(market(OrderType::BUY, 9.0)).unwrap();
+
+        quote(&broker, 2, 1.0000, 1.0002);
+        assert_eq!(broker.positions_total().unwrap(), 0);
+        assert_eq!(broker.state().deals[1].reason, DealReason::SO);
+    }
+}
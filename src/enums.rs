@@ -1,7 +1,109 @@
+use std::fmt;
+
 use pyo3::{types::PyAnyMethods, FromPyObject};
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::FishingLineError;
+
+/// Declares an enum mirroring an MQL5 integer constant.
+///
+/// Besides the enum itself this generates an `Unknown(i64)` variant for values this
+/// crate does not know yet, `value()`/`from_value()`, a `Default` of `Unknown(0)`,
+/// `From<Enum> for i64`,
+/// `TryFrom<i64>`/`TryFrom<u64>` (failing with [`FishingLineError::Conversion`] on
+/// unknown values), and `FromPyObject` and serde impls that use the integer value and
+/// fall back to `Unknown`.
+macro_rules! mql_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not known to this version of fishing-line.
+            Unknown(i64),
+        }
+
+        impl $name {
+            /// The MQL5 integer value of this constant.
+            pub fn value(self) -> i64 {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+
+            /// Maps an MQL5 integer value, falling back to `Unknown` for unrecognized values.
+            pub fn from_value(value: i64) -> Self {
+                match value {
+                    $(value if value == $value => $name::$variant,)*
+                    value => $name::Unknown(value),
+                }
+            }
+        }
 
-use crate::traits::InfoTrait;
+        /// `Unknown(0)`, which equals none of the named constants, so a defaulted
+        /// schema does not read as a real order type, action or return code.
+        impl Default for $name {
+            fn default() -> Self {
+                $name::Unknown(0)
+            }
+        }
+
+        impl From<$name> for i64 {
+            fn from(value: $name) -> Self {
+                value.value()
+            }
+        }
+
+        impl TryFrom<i64> for $name {
+            type Error = FishingLineError;
+
+            fn try_from(value: i64) -> Result<Self, Self::Error> {
+                match $name::from_value(value) {
+                    $name::Unknown(value) => Err(FishingLineError::Conversion {
+                        field: stringify!($name).to_string(),
+                        message: format!("Invalid {} value: {}", stringify!($name), value),
+                    }),
+                    known => Ok(known),
+                }
+            }
+        }
+
+        impl TryFrom<u64> for $name {
+            type Error = FishingLineError;
+
+            fn try_from(value: u64) -> Result<Self, Self::Error> {
+                let value = i64::try_from(value).map_err(|_| FishingLineError::Conversion {
+                    field: stringify!($name).to_string(),
+                    message: format!("Invalid {} value: {}", stringify!($name), value),
+                })?;
+                $name::try_from(value)
+            }
+        }
+
+        impl FromPyObject<'_> for $name {
+            fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
+                Ok($name::from_value(ob.extract()?))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_i64(self.value())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                i64::deserialize(deserializer).map($name::from_value)
+            }
+        }
+    };
+}
 
 /// Represents the timeframe for a trading operation.
 #[derive(Debug, Clone, Copy)]
@@ -69,626 +171,320 @@ pub enum TicksFlag {
     SELL = 0x40,
 }
 
-/// Represents the type of an order.
-#[derive(Debug, Clone, Copy, Deserialize)]
-pub enum OrderType {
-    BUY = 0,
-    SELL = 1,
-    BuyLimit = 2,
-    SellLimit = 3,
-    BuyStop = 4,
-    SellStop = 5,
-    BuyStopLimit = 6,
-    SellStopLimit = 7,
-    CloseBy = 8,
-}
-
-impl FromPyObject<'_> for OrderType {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: i64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(OrderType::BUY),
-            1 => Ok(OrderType::SELL),
-            2 => Ok(OrderType::BuyLimit),
-            3 => Ok(OrderType::SellLimit),
-            4 => Ok(OrderType::BuyStop),
-            5 => Ok(OrderType::SellStop),
-            6 => Ok(OrderType::BuyStopLimit),
-            7 => Ok(OrderType::SellStopLimit),
-            8 => Ok(OrderType::CloseBy),
-            _ => {
-                panic!("Invalid OrderType value: {}", value)
+mql_enum! {
+    /// Represents the type of an order.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OrderType {
+        BUY = 0,
+        SELL = 1,
+        BuyLimit = 2,
+        SellLimit = 3,
+        BuyStop = 4,
+        SellStop = 5,
+        BuyStopLimit = 6,
+        SellStopLimit = 7,
+        CloseBy = 8,
+    }
+}
+
+mql_enum! {
+    /// Represents a trade action request.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum TradeActionRequest {
+        DEAL = 1,
+        PENDING = 5,
+        SLTP = 6,
+        MODIFY = 7,
+        REMOVE = 8,
+        CloseBy = 10,
+    }
+}
+
+mql_enum! {
+    /// Represents the filling type for an order.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OrderTypeFilling {
+        FOK = 0,
+        IOC = 1,
+        RETURN = 2,
+    }
+}
+
+mql_enum! {
+    /// Represents the time type for an order.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OrderTypeTime {
+        GTC = 0,
+        DAY = 1,
+        SPECIFIED = 2,
+        SpecifiedDay = 3,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ReturnCode {
+        CHECKED = 0,
+        REQUOTE = 10004,
+        REJECT = 10006,
+        CANCELLED = 10007,
+        PLACED = 10008,
+        DONE = 10009,
+        DonePartial = 10010,
+        ERROR = 10011,
+        TIMEOUT = 10012,
+        INVALID = 10013,
+        InvalidVolume = 10014,
+        InvalidPrice = 10015,
+        InvalidStops = 10016,
+        TradeDisabled = 10017,
+        MarketClosed = 10018,
+        NoMoney = 10019,
+        PriceChanged = 10020,
+        PriceOff = 10021,
+        InvalidExpiration = 10022,
+        OrderChanged = 10023,
+        TooManyRequest = 10024,
+        NoChanges = 10025,
+        ServerDisablesAt = 10026,
+        ClientDisablesAt = 10027,
+        LOCKED = 10028,
+        FROZEN = 10029,
+        InvalidFill = 10030,
+        CONNECTION = 10031,
+        OnlyReal = 10032,
+        LimitOrders = 10033,
+        LimitVolumes = 10034,
+        InvalidOrders = 10035,
+        PostionClosed = 10036,
+        InvalideCloseVolume = 10038,
+        CloseOrderExist = 10039,
+        LimitPositions = 10040,
+        RejectCancel = 10041,
+        LongOnly = 10042,
+        ShortOnly = 10043,
+        CloseOnly = 10044,
+        FifoClose = 10045,
+        HedgeProhibited = 10046,
+    }
+}
+
+impl ReturnCode {
+    /// The request was executed or the order was placed, fully or partially.
+    ///
+    /// `CHECKED` is not a success here: it is only returned by `order_check`.
+    pub fn is_success(self) -> bool {
+        matches!(
+            self,
+            ReturnCode::DONE | ReturnCode::PLACED | ReturnCode::DonePartial
+        )
+    }
+
+    /// Only part of the requested volume was filled.
+    pub fn is_partial(self) -> bool {
+        self == ReturnCode::DonePartial
+    }
+
+    /// The request failed for a transient reason and may succeed if sent again,
+    /// usually after refreshing prices.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ReturnCode::REQUOTE
+                | ReturnCode::TIMEOUT
+                | ReturnCode::PriceChanged
+                | ReturnCode::PriceOff
+                | ReturnCode::OrderChanged
+                | ReturnCode::TooManyRequest
+                | ReturnCode::LOCKED
+                | ReturnCode::CONNECTION
+        )
+    }
+
+    /// The request was rejected and sending it again unchanged will not help.
+    pub fn is_fatal(self) -> bool {
+        self != ReturnCode::CHECKED && !self.is_success() && !self.is_retryable()
+    }
+}
+
+impl fmt::Display for ReturnCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            ReturnCode::CHECKED => "Request checked",
+            ReturnCode::REQUOTE => "Requote",
+            ReturnCode::REJECT => "Request rejected",
+            ReturnCode::CANCELLED => "Request canceled by trader",
+            ReturnCode::PLACED => "Order placed",
+            ReturnCode::DONE => "Request completed",
+            ReturnCode::DonePartial => "Only part of the request was completed",
+            ReturnCode::ERROR => "Request processing error",
+            ReturnCode::TIMEOUT => "Request canceled by timeout",
+            ReturnCode::INVALID => "Invalid request",
+            ReturnCode::InvalidVolume => "Invalid volume in the request",
+            ReturnCode::InvalidPrice => "Invalid price in the request",
+            ReturnCode::InvalidStops => "Invalid stops in the request",
+            ReturnCode::TradeDisabled => "Trade is disabled",
+            ReturnCode::MarketClosed => "Market is closed",
+            ReturnCode::NoMoney => "There is not enough money to complete the request",
+            ReturnCode::PriceChanged => "Prices changed",
+            ReturnCode::PriceOff => "There are no quotes to process the request",
+            ReturnCode::InvalidExpiration => "Invalid order expiration date in the request",
+            ReturnCode::OrderChanged => "Order state changed",
+            ReturnCode::TooManyRequest => "Too frequent requests",
+            ReturnCode::NoChanges => "No changes in request",
+            ReturnCode::ServerDisablesAt => "Autotrading disabled by server",
+            ReturnCode::ClientDisablesAt => "Autotrading disabled by client terminal",
+            ReturnCode::LOCKED => "Request locked for processing",
+            ReturnCode::FROZEN => "Order or position frozen",
+            ReturnCode::InvalidFill => "Invalid order filling type",
+            ReturnCode::CONNECTION => "No connection with the trade server",
+            ReturnCode::OnlyReal => "Operation is allowed only for live accounts",
+            ReturnCode::LimitOrders => "The number of pending orders has reached the limit",
+            ReturnCode::LimitVolumes => {
+                "The volume of orders and positions for the symbol has reached the limit"
             }
-        }
-    }
-}
-
-impl From<i64> for OrderType {
-    /// Converts an `i64` value to an `OrderType`.
-    fn from(value: i64) -> Self {
-        match value {
-            0 => OrderType::BUY,
-            1 => OrderType::SELL,
-            2 => OrderType::BuyLimit,
-            3 => OrderType::SellLimit,
-            4 => OrderType::BuyStop,
-            5 => OrderType::SellStop,
-            6 => OrderType::BuyStopLimit,
-            7 => OrderType::SellStopLimit,
-            8 => OrderType::CloseBy,
-            _ => panic!("Invalid OrderType value: {}", value),
-        }
-    }
-}
-
-/// Represents a trade action request.
-#[derive(Debug, Clone, Copy, Deserialize)]
-pub enum TradeActionRequest {
-    DEAL = 1,
-    PENDING = 5,
-    SLTP = 6,
-    MODIFY = 7,
-    REMOVE = 8,
-    CloseBy = 10,
-}
-
-impl FromPyObject<'_> for TradeActionRequest {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: i64 = ob.extract().unwrap();
-        match value {
-            1 => Ok(TradeActionRequest::DEAL),
-            5 => Ok(TradeActionRequest::PENDING),
-            6 => Ok(TradeActionRequest::SLTP),
-            7 => Ok(TradeActionRequest::MODIFY),
-            8 => Ok(TradeActionRequest::REMOVE),
-            10 => Ok(TradeActionRequest::CloseBy),
-            _ => {
-                panic!("Invalid TradeActionRequest value");
+            ReturnCode::InvalidOrders => "Incorrect or prohibited order type",
+            ReturnCode::PostionClosed => {
+                "Position with the specified POSITION_IDENTIFIER has already been closed"
             }
-        }
-    }
-}
-
-impl From<i64> for TradeActionRequest {
-    /// Converts an `i64` value to a `TradeActionRequest`.
-    fn from(value: i64) -> Self {
-        match value {
-            1 => TradeActionRequest::DEAL,
-            5 => TradeActionRequest::PENDING,
-            6 => TradeActionRequest::SLTP,
-            7 => TradeActionRequest::MODIFY,
-            8 => TradeActionRequest::REMOVE,
-            10 => TradeActionRequest::CloseBy,
-            _ => panic!("Invalid TradeActionRequest value: {}", value),
-        }
-    }
-}
-
-/// Represents the filling type for an order.
-#[derive(Debug, Clone, Copy, Deserialize)]
-pub enum OrderTypeFilling {
-    FOK = 0,
-    IOC = 1,
-    RETURN = 2,
-}
-
-impl From<i64> for OrderTypeFilling {
-    /// Converts an `i64` value to an `OrderTypeFilling`.
-    fn from(value: i64) -> Self {
-        match value {
-            0 => OrderTypeFilling::FOK,
-            1 => OrderTypeFilling::IOC,
-            2 => OrderTypeFilling::RETURN,
-            _ => panic!("Invalid OrderTypeFilling value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for OrderTypeFilling {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: i64 = ob.extract().unwrap();
-
-        match value {
-            0 => Ok(OrderTypeFilling::FOK),
-            1 => Ok(OrderTypeFilling::IOC),
-            2 => Ok(OrderTypeFilling::RETURN),
-            _ => panic!("Invalid OrderTypeFilling value: {}", value),
-        }
-    }
-}
-
-/// Represents the time type for an order.
-#[derive(Debug, Clone, Copy, Deserialize)]
-pub enum OrderTypeTime {
-    GTC = 0,
-    DAY = 1,
-    SPECIFIED = 2,
-    SpecifiedDay = 3,
-}
-
-impl From<i64> for OrderTypeTime {
-    /// Converts an `i64` value to an `OrderTypeTime`.
-    fn from(value: i64) -> Self {
-        match value {
-            0 => OrderTypeTime::GTC,
-            1 => OrderTypeTime::DAY,
-            2 => OrderTypeTime::SPECIFIED,
-            3 => OrderTypeTime::SpecifiedDay,
-            _ => panic!("Invalid OrderTypeTime value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for OrderTypeTime {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: i64 = ob.extract().unwrap();
-
-        match value {
-            0 => Ok(OrderTypeTime::GTC),
-            1 => Ok(OrderTypeTime::DAY),
-            2 => Ok(OrderTypeTime::SPECIFIED),
-            3 => Ok(OrderTypeTime::SpecifiedDay),
-            _ => panic!("Invalid OrderTypeTime value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum ReturnCode {
-    CHECKED = 0,
-    REQUOTE = 10004,
-    REJECT = 10006,
-    CANCELLED = 10007,
-    PLACED = 10008,
-    DONE = 10009,
-    DonePartial = 10010,
-    ERROR = 10011,
-    TIMEOUT = 10012,
-    INVALID = 10013,
-    InvalidVolume = 10014,
-    InvalidPrice = 10015,
-    InvalidStops = 10016,
-    TradeDisabled = 10017,
-    MarketClosed = 10018,
-    NoMoney = 10019,
-    PriceChanged = 10020,
-    PriceOff = 10021,
-    InvalidExpiration = 10022,
-    OrderChanged = 10023,
-    TooManyRequest = 10024,
-    NoChanges = 10025,
-    ServerDisablesAt = 10026,
-    ClientDisablesAt = 10027,
-    LOCKED = 10028,
-    FROZEN = 10029,
-    InvalidFill = 10030,
-    CONNECTION = 10031,
-    OnlyReal = 10032,
-    LimitOrders = 10033,
-    LimitVolumes = 10034,
-    InvalidOrders = 10035,
-    PostionClosed = 10036,
-    InvalideCloseVolume = 10038,
-    CloseOrderExist = 10039,
-    LimitPositions = 10040,
-    RejectCancel = 10041,
-    LongOnly = 10042,
-    ShortOnly = 10043,
-    CloseOnly = 10044,
-    FifoClose = 10045,
-    HedgeProhibited = 10046,
-}
-
-impl From<u64> for ReturnCode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => ReturnCode::CHECKED,
-            10004 => ReturnCode::REQUOTE,
-            10006 => ReturnCode::REJECT,
-            10007 => ReturnCode::CANCELLED,
-            10008 => ReturnCode::PLACED,
-            10009 => ReturnCode::DONE,
-            10010 => ReturnCode::DonePartial,
-            10011 => ReturnCode::ERROR,
-            10012 => ReturnCode::TIMEOUT,
-            10013 => ReturnCode::INVALID,
-            10014 => ReturnCode::InvalidVolume,
-            10015 => ReturnCode::InvalidPrice,
-            10016 => ReturnCode::InvalidStops,
-            10017 => ReturnCode::TradeDisabled,
-            10018 => ReturnCode::MarketClosed,
-            10019 => ReturnCode::NoMoney,
-            10020 => ReturnCode::PriceChanged,
-            10021 => ReturnCode::PriceOff,
-            10022 => ReturnCode::InvalidExpiration,
-            10023 => ReturnCode::OrderChanged,
-            10024 => ReturnCode::TooManyRequest,
-            10025 => ReturnCode::NoChanges,
-            10026 => ReturnCode::ServerDisablesAt,
-            10027 => ReturnCode::ClientDisablesAt,
-            10028 => ReturnCode::LOCKED,
-            10029 => ReturnCode::FROZEN,
-            10030 => ReturnCode::InvalidFill,
-            10031 => ReturnCode::CONNECTION,
-            10032 => ReturnCode::OnlyReal,
-            10033 => ReturnCode::LimitOrders,
-            10034 => ReturnCode::LimitVolumes,
-            10035 => ReturnCode::InvalidOrders,
-            10036 => ReturnCode::PostionClosed,
-            10038 => ReturnCode::InvalideCloseVolume,
-            10039 => ReturnCode::CloseOrderExist,
-            10040 => ReturnCode::LimitPositions,
-            10041 => ReturnCode::RejectCancel,
-            10042 => ReturnCode::LongOnly,
-            10043 => ReturnCode::ShortOnly,
-            10044 => ReturnCode::CloseOnly,
-            10045 => ReturnCode::FifoClose,
-            10046 => ReturnCode::HedgeProhibited,
-            _ => panic!("Invalid ReturnCode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for ReturnCode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(ReturnCode::CHECKED),
-            10004 => Ok(ReturnCode::REQUOTE),
-            10006 => Ok(ReturnCode::REJECT),
-            10007 => Ok(ReturnCode::CANCELLED),
-            10008 => Ok(ReturnCode::PLACED),
-            10009 => Ok(ReturnCode::DONE),
-            10010 => Ok(ReturnCode::DonePartial),
-            10011 => Ok(ReturnCode::ERROR),
-            10012 => Ok(ReturnCode::TIMEOUT),
-            10013 => Ok(ReturnCode::INVALID),
-            10014 => Ok(ReturnCode::InvalidVolume),
-            10015 => Ok(ReturnCode::InvalidPrice),
-            10016 => Ok(ReturnCode::InvalidStops),
-            10017 => Ok(ReturnCode::TradeDisabled),
-            10018 => Ok(ReturnCode::MarketClosed),
-            10019 => Ok(ReturnCode::NoMoney),
-            10020 => Ok(ReturnCode::PriceChanged),
-            10021 => Ok(ReturnCode::PriceOff),
-            10022 => Ok(ReturnCode::InvalidExpiration),
-            10023 => Ok(ReturnCode::OrderChanged),
-            10024 => Ok(ReturnCode::TooManyRequest),
-            10025 => Ok(ReturnCode::NoChanges),
-            10026 => Ok(ReturnCode::ServerDisablesAt),
-            10027 => Ok(ReturnCode::ClientDisablesAt),
-            10028 => Ok(ReturnCode::LOCKED),
-            10029 => Ok(ReturnCode::FROZEN),
-            10030 => Ok(ReturnCode::InvalidFill),
-            10031 => Ok(ReturnCode::CONNECTION),
-            10032 => Ok(ReturnCode::OnlyReal),
-            10033 => Ok(ReturnCode::LimitOrders),
-            10034 => Ok(ReturnCode::LimitVolumes),
-            10035 => Ok(ReturnCode::InvalidOrders),
-            10036 => Ok(ReturnCode::PostionClosed),
-            10038 => Ok(ReturnCode::InvalideCloseVolume),
-            10039 => Ok(ReturnCode::CloseOrderExist),
-            10040 => Ok(ReturnCode::LimitPositions),
-            10041 => Ok(ReturnCode::RejectCancel),
-            10042 => Ok(ReturnCode::LongOnly),
-            10043 => Ok(ReturnCode::ShortOnly),
-            10044 => Ok(ReturnCode::CloseOnly),
-            10045 => Ok(ReturnCode::FifoClose),
-            10046 => Ok(ReturnCode::HedgeProhibited),
-            _ => panic!("Invalid ReturnCode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum PositionType {
-    BUY = 0,
-    SELL = 1,
-}
-
-impl From<u64> for PositionType {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => PositionType::BUY,
-            1 => PositionType::SELL,
-            _ => panic!("Invalid PositionType value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for PositionType {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(PositionType::BUY),
-            1 => Ok(PositionType::SELL),
-            _ => panic!("Invalid PositionType value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum PositionReason {
-    CLIENT = 0,
-    MOBILE = 1,
-    WEB = 2,
-    EXPERT = 3,
-}
-
-impl From<u64> for PositionReason {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => PositionReason::CLIENT,
-            1 => PositionReason::MOBILE,
-            2 => PositionReason::WEB,
-            3 => PositionReason::EXPERT,
-            _ => panic!("Invalid PositionReason value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for PositionReason {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(PositionReason::CLIENT),
-            1 => Ok(PositionReason::MOBILE),
-            2 => Ok(PositionReason::WEB),
-            3 => Ok(PositionReason::EXPERT),
-            _ => panic!("Invalid PositionReason value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum DealType {
-    BUY = 0,
-    SELL = 1,
-    BALANCE = 2,
-    CREDIT = 3,
-    CHARGE = 4,
-    CORRECTION = 5,
-    BONUS = 6,
-    COMMISSION = 7,
-    CommissionDaily = 8,
-    CommissionMontly = 9,
-    ComissionAgentDaily = 10,
-    CommisionAgentMontly = 11,
-    INTEREST = 12,
-    BuyCanceled = 13,
-    SellCanceled = 14,
-    DIVIDEND = 15,
-    DividentFranked = 16,
-    TAX = 17,
-}
-
-impl From<u64> for DealType {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => DealType::BUY,
-            1 => DealType::SELL,
-            2 => DealType::BALANCE,
-            3 => DealType::CREDIT,
-            4 => DealType::CHARGE,
-            5 => DealType::CORRECTION,
-            6 => DealType::BONUS,
-            7 => DealType::COMMISSION,
-            8 => DealType::CommissionDaily,
-            9 => DealType::CommissionMontly,
-            10 => DealType::ComissionAgentDaily,
-            11 => DealType::CommisionAgentMontly,
-            12 => DealType::INTEREST,
-            13 => DealType::BuyCanceled,
-            14 => DealType::SellCanceled,
-            15 => DealType::DIVIDEND,
-            16 => DealType::DividentFranked,
-            17 => DealType::TAX,
-            _ => panic!("Invalid DealType value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for DealType {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(DealType::BUY),
-            1 => Ok(DealType::SELL),
-            2 => Ok(DealType::BALANCE),
-            3 => Ok(DealType::CREDIT),
-            4 => Ok(DealType::CHARGE),
-            5 => Ok(DealType::CORRECTION),
-            6 => Ok(DealType::BONUS),
-            7 => Ok(DealType::COMMISSION),
-            8 => Ok(DealType::CommissionDaily),
-            9 => Ok(DealType::CommissionMontly),
-            10 => Ok(DealType::ComissionAgentDaily),
-            11 => Ok(DealType::CommisionAgentMontly),
-            12 => Ok(DealType::INTEREST),
-            13 => Ok(DealType::BuyCanceled),
-            14 => Ok(DealType::SellCanceled),
-            15 => Ok(DealType::DIVIDEND),
-            16 => Ok(DealType::DividentFranked),
-            17 => Ok(DealType::TAX),
-            _ => panic!("Invalid DealType value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum DealEntry {
-    IN = 0,
-    OUT = 1,
-    INOUT = 2,
-    OutBy = 3,
-}
-
-impl From<u64> for DealEntry {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => DealEntry::IN,
-            1 => DealEntry::OUT,
-            2 => DealEntry::INOUT,
-            3 => DealEntry::OutBy,
-            _ => panic!("Invalid DealEntry value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for DealEntry {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(DealEntry::IN),
-            1 => Ok(DealEntry::OUT),
-            2 => Ok(DealEntry::INOUT),
-            3 => Ok(DealEntry::OutBy),
-            _ => panic!("Invalid DealEntry value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum DealReason {
-    CLIENT = 0,
-    MOBILE = 1,
-    WEB = 2,
-    EXPERT = 3,
-    SL = 4,
-    TP = 5,
-    SO = 6,
-    ROLLOVER = 7,
-    VMARGIN = 8,
-    SPLIT = 9,
-}
-
-impl From<u64> for DealReason {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => DealReason::CLIENT,
-            1 => DealReason::MOBILE,
-            2 => DealReason::WEB,
-            3 => DealReason::EXPERT,
-            4 => DealReason::SL,
-            5 => DealReason::TP,
-            6 => DealReason::SO,
-            7 => DealReason::ROLLOVER,
-            8 => DealReason::VMARGIN,
-            9 => DealReason::SPLIT,
-            _ => panic!("Invalid DealReason value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for DealReason {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(DealReason::CLIENT),
-            1 => Ok(DealReason::MOBILE),
-            2 => Ok(DealReason::WEB),
-            3 => Ok(DealReason::EXPERT),
-            4 => Ok(DealReason::SL),
-            5 => Ok(DealReason::TP),
-            6 => Ok(DealReason::SO),
-            7 => Ok(DealReason::ROLLOVER),
-            8 => Ok(DealReason::VMARGIN),
-            9 => Ok(DealReason::SPLIT),
-            _ => panic!("Invalid DealReason value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum OrderState {
-    STARTED = 0,
-    PLACED = 1,
-    CANCELED = 2,
-    PARTIAL = 3,
-    FILLED = 4,
-    REJECTED = 5,
-    EXPIRED = 6,
-    RequestAdd = 7,
-    RequestModify = 8,
-    RequestCancel = 9,
-}
-
-impl From<u64> for OrderState {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => OrderState::STARTED,
-            1 => OrderState::PLACED,
-            2 => OrderState::CANCELED,
-            3 => OrderState::PARTIAL,
-            4 => OrderState::FILLED,
-            5 => OrderState::REJECTED,
-            6 => OrderState::EXPIRED,
-            7 => OrderState::RequestAdd,
-            8 => OrderState::RequestModify,
-            9 => OrderState::RequestCancel,
-            _ => panic!("Invalid OrderState value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for OrderState {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(OrderState::STARTED),
-            1 => Ok(OrderState::PLACED),
-            2 => Ok(OrderState::CANCELED),
-            3 => Ok(OrderState::PARTIAL),
-            4 => Ok(OrderState::FILLED),
-            5 => Ok(OrderState::REJECTED),
-            6 => Ok(OrderState::EXPIRED),
-            7 => Ok(OrderState::RequestAdd),
-            8 => Ok(OrderState::RequestModify),
-            9 => Ok(OrderState::RequestCancel),
-            _ => panic!("Invalid OrderState value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum OrderReason {
-    CLIENT = 0,
-    MOBILE = 1,
-    WEB = 2,
-    EXPERT = 3,
-    SL = 4,
-    TP = 5,
-    SO = 6,
-}
-
-impl From<u64> for OrderReason {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => OrderReason::CLIENT,
-            1 => OrderReason::MOBILE,
-            2 => OrderReason::WEB,
-            3 => OrderReason::EXPERT,
-            4 => OrderReason::SL,
-            5 => OrderReason::TP,
-            6 => OrderReason::SO,
-            _ => panic!("Invalid OrderReason value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for OrderReason {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(OrderReason::CLIENT),
-            1 => Ok(OrderReason::MOBILE),
-            2 => Ok(OrderReason::WEB),
-            3 => Ok(OrderReason::EXPERT),
-            4 => Ok(OrderReason::SL),
-            5 => Ok(OrderReason::TP),
-            6 => Ok(OrderReason::SO),
-            _ => panic!("Invalid OrderReason value: {}", value),
-        }
+            ReturnCode::InvalideCloseVolume => {
+                "A close volume exceeds the current position volume"
+            }
+            ReturnCode::CloseOrderExist => {
+                "A close order already exists for a specified position"
+            }
+            ReturnCode::LimitPositions => {
+                "The number of open positions simultaneously present on an account has reached the limit"
+            }
+            ReturnCode::RejectCancel => {
+                "The pending order activation request is rejected, the order is canceled"
+            }
+            ReturnCode::LongOnly => {
+                "The request is rejected, because the \"Only long positions are allowed\" rule is set for the symbol"
+            }
+            ReturnCode::ShortOnly => {
+                "The request is rejected, because the \"Only short positions are allowed\" rule is set for the symbol"
+            }
+            ReturnCode::CloseOnly => {
+                "The request is rejected, because the \"Only position closing is allowed\" rule is set for the symbol"
+            }
+            ReturnCode::FifoClose => {
+                "The request is rejected, because \"Position closing is allowed only by FIFO rule\" flag is set for the trading account"
+            }
+            ReturnCode::HedgeProhibited => {
+                "The request is rejected, because the \"Opposite positions on a single symbol are disabled\" rule is set for the trading account"
+            }
+            ReturnCode::Unknown(value) => return write!(f, "Unknown return code {}", value),
+        };
+        f.write_str(description)
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PositionType {
+        BUY = 0,
+        SELL = 1,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PositionReason {
+        CLIENT = 0,
+        MOBILE = 1,
+        WEB = 2,
+        EXPERT = 3,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DealType {
+        BUY = 0,
+        SELL = 1,
+        BALANCE = 2,
+        CREDIT = 3,
+        CHARGE = 4,
+        CORRECTION = 5,
+        BONUS = 6,
+        COMMISSION = 7,
+        CommissionDaily = 8,
+        CommissionMontly = 9,
+        ComissionAgentDaily = 10,
+        CommisionAgentMontly = 11,
+        INTEREST = 12,
+        BuyCanceled = 13,
+        SellCanceled = 14,
+        DIVIDEND = 15,
+        DividentFranked = 16,
+        TAX = 17,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DealEntry {
+        IN = 0,
+        OUT = 1,
+        INOUT = 2,
+        OutBy = 3,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DealReason {
+        CLIENT = 0,
+        MOBILE = 1,
+        WEB = 2,
+        EXPERT = 3,
+        SL = 4,
+        TP = 5,
+        SO = 6,
+        ROLLOVER = 7,
+        VMARGIN = 8,
+        SPLIT = 9,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OrderState {
+        STARTED = 0,
+        PLACED = 1,
+        CANCELED = 2,
+        PARTIAL = 3,
+        FILLED = 4,
+        REJECTED = 5,
+        EXPIRED = 6,
+        RequestAdd = 7,
+        RequestModify = 8,
+        RequestCancel = 9,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OrderReason {
+        CLIENT = 0,
+        MOBILE = 1,
+        WEB = 2,
+        EXPERT = 3,
+        SL = 4,
+        TP = 5,
+        SO = 6,
+    }
+}
+
+mql_enum! {
+    /// Side of a market depth entry.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum BookType {
+        SELL = 1,
+        BUY = 2,
+        SellMarket = 3,
+        BuyMarket = 4,
     }
 }
 
@@ -847,599 +643,285 @@ pub enum SymbolInfoProperty {
     Path,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum AccountTradeMode {
-    Demo = 0,
-    Contest = 1,
-    Real = 2,
-}
-
-impl From<u64> for AccountTradeMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => AccountTradeMode::Demo,
-            1 => AccountTradeMode::Contest,
-            2 => AccountTradeMode::Real,
-            _ => panic!("Invalid AccountTradeMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for AccountTradeMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(AccountTradeMode::Demo),
-            1 => Ok(AccountTradeMode::Contest),
-            2 => Ok(AccountTradeMode::Real),
-            _ => panic!("Invalid AccountTradeMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum AccountStopOutMode {
-    PERCENT = 0,
-    MONEY = 1,
-}
-
-impl From<u64> for AccountStopOutMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => AccountStopOutMode::PERCENT,
-            1 => AccountStopOutMode::MONEY,
-            _ => panic!("Invalid AccountStopOutMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for AccountStopOutMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(AccountStopOutMode::PERCENT),
-            1 => Ok(AccountStopOutMode::MONEY),
-            _ => panic!("Invalid AccountStopOutMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum AccountMarginMode {
-    RetailNetting = 0,
-    Exchange = 1,
-    RetailHedging = 2,
-}
-
-impl From<u64> for AccountMarginMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => AccountMarginMode::RetailNetting,
-            1 => AccountMarginMode::Exchange,
-            2 => AccountMarginMode::RetailHedging,
-            _ => panic!("Invalid AccountMarginMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for AccountMarginMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(AccountMarginMode::RetailNetting),
-            1 => Ok(AccountMarginMode::Exchange),
-            2 => Ok(AccountMarginMode::RetailHedging),
-            _ => panic!("Invalid AccountMarginMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolChartMode {
-    Bid = 0,
-    Last = 1,
-}
-
-impl From<u64> for SymbolChartMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolChartMode::Bid,
-            1 => SymbolChartMode::Last,
-            _ => panic!("Invalid SymbolChartMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolChartMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolChartMode::Bid),
-            1 => Ok(SymbolChartMode::Last),
-            _ => panic!("Invalid SymbolChartMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolCalcMode {
-    FOREX = 0,
-    FUTURES = 1,
-    CFD = 2,
-    CFDINDEX = 3,
-    CFDLEVERAGE = 4,
-    ForexNoLeverage = 5,
-    ExchStocks = 32,
-    ExchFutures = 33,
-    ExchOptions = 34,
-    ExchOptionsMargin = 36,
-    ExchBonds = 37,
-    ExchStocksMoex = 38,
-    ExchBondsMoex = 39,
-    ServCollateral = 64,
-}
-
-impl From<u64> for SymbolCalcMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolCalcMode::FOREX,
-            1 => SymbolCalcMode::FUTURES,
-            2 => SymbolCalcMode::CFD,
-            3 => SymbolCalcMode::CFDINDEX,
-            4 => SymbolCalcMode::CFDLEVERAGE,
-            5 => SymbolCalcMode::ForexNoLeverage,
-            32 => SymbolCalcMode::ExchStocks,
-            33 => SymbolCalcMode::ExchFutures,
-            34 => SymbolCalcMode::ExchOptions,
-            36 => SymbolCalcMode::ExchOptionsMargin,
-            37 => SymbolCalcMode::ExchBonds,
-            38 => SymbolCalcMode::ExchStocksMoex,
-            39 => SymbolCalcMode::ExchBondsMoex,
-            64 => SymbolCalcMode::ServCollateral,
-            _ => panic!("Invalid SymbolCalcMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolCalcMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolCalcMode::FOREX),
-            1 => Ok(SymbolCalcMode::FUTURES),
-            2 => Ok(SymbolCalcMode::CFD),
-            3 => Ok(SymbolCalcMode::CFDINDEX),
-            4 => Ok(SymbolCalcMode::CFDLEVERAGE),
-            5 => Ok(SymbolCalcMode::ForexNoLeverage),
-            32 => Ok(SymbolCalcMode::ExchStocks),
-            33 => Ok(SymbolCalcMode::ExchFutures),
-            34 => Ok(SymbolCalcMode::ExchOptions),
-            36 => Ok(SymbolCalcMode::ExchOptionsMargin),
-            37 => Ok(SymbolCalcMode::ExchBonds),
-            38 => Ok(SymbolCalcMode::ExchStocksMoex),
-            39 => Ok(SymbolCalcMode::ExchBondsMoex),
-            64 => Ok(SymbolCalcMode::ServCollateral),
-            _ => panic!("Invalid SymbolCalcMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolTradeMode {
-    SymbolTradeModeDisabled = 0,
-    SymbolTradeModeLongonly = 1,
-    SymbolTradeModeShortonly = 2,
-    SymbolTradeModeCloseonly = 3,
-    SymbolTradeModeFull = 4,
-}
-
-impl From<u64> for SymbolTradeMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolTradeMode::SymbolTradeModeDisabled,
-            1 => SymbolTradeMode::SymbolTradeModeLongonly,
-            2 => SymbolTradeMode::SymbolTradeModeShortonly,
-            3 => SymbolTradeMode::SymbolTradeModeCloseonly,
-            4 => SymbolTradeMode::SymbolTradeModeFull,
-            _ => panic!("Invalid SymbolTradeMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolTradeMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolTradeMode::SymbolTradeModeDisabled),
-            1 => Ok(SymbolTradeMode::SymbolTradeModeLongonly),
-            2 => Ok(SymbolTradeMode::SymbolTradeModeShortonly),
-            3 => Ok(SymbolTradeMode::SymbolTradeModeCloseonly),
-            4 => Ok(SymbolTradeMode::SymbolTradeModeFull),
-            _ => panic!("Invalid SymbolTradeMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolTradeExecution {
-    Request = 0,
-    Instant = 1,
-    Market = 2,
-    Exchange = 3,
-}
-
-impl From<u64> for SymbolTradeExecution {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolTradeExecution::Request,
-            1 => SymbolTradeExecution::Instant,
-            2 => SymbolTradeExecution::Market,
-            3 => SymbolTradeExecution::Exchange,
-            _ => panic!("Invalid SymbolTradeExecution value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolTradeExecution {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolTradeExecution::Request),
-            1 => Ok(SymbolTradeExecution::Instant),
-            2 => Ok(SymbolTradeExecution::Market),
-            3 => Ok(SymbolTradeExecution::Exchange),
-            _ => panic!("Invalid SymbolTradeExecution value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolSwapMode {
-    Disabled = 0,
-    Points = 1,
-    CurrencySymbol = 2,
-    CurrencyMargin = 3,
-    CurrencyDeposit = 4,
-    InterestCurrent = 5,
-    InterestOpen = 6,
-    ReopenCurrent = 7,
-    ReopenBid = 8,
-}
-
-impl From<u64> for SymbolSwapMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolSwapMode::Disabled,
-            1 => SymbolSwapMode::Points,
-            2 => SymbolSwapMode::CurrencySymbol,
-            3 => SymbolSwapMode::CurrencyMargin,
-            4 => SymbolSwapMode::CurrencyDeposit,
-            5 => SymbolSwapMode::InterestCurrent,
-            6 => SymbolSwapMode::InterestOpen,
-            7 => SymbolSwapMode::ReopenCurrent,
-            8 => SymbolSwapMode::ReopenBid,
-            _ => panic!("Invalid SymbolSwapMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolSwapMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolSwapMode::Disabled),
-            1 => Ok(SymbolSwapMode::Points),
-            2 => Ok(SymbolSwapMode::CurrencySymbol),
-            3 => Ok(SymbolSwapMode::CurrencyMargin),
-            4 => Ok(SymbolSwapMode::CurrencyDeposit),
-            5 => Ok(SymbolSwapMode::InterestCurrent),
-            6 => Ok(SymbolSwapMode::InterestOpen),
-            7 => Ok(SymbolSwapMode::ReopenCurrent),
-            8 => Ok(SymbolSwapMode::ReopenBid),
-            _ => panic!("Invalid SymbolSwapMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum DayOfWeek {
-    Sunday = 0,
-    Monday = 1,
-    Tuesday = 2,
-    Wednesday = 3,
-    Thursday = 4,
-    Friday = 5,
-    Saturday = 6,
-    All = 7,
-}
-
-impl From<u64> for DayOfWeek {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => DayOfWeek::Sunday,
-            1 => DayOfWeek::Monday,
-            2 => DayOfWeek::Tuesday,
-            3 => DayOfWeek::Wednesday,
-            4 => DayOfWeek::Thursday,
-            5 => DayOfWeek::Friday,
-            6 => DayOfWeek::Saturday,
-            7 => DayOfWeek::All,
-            _ => panic!("Invalid DayOfWeek value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for DayOfWeek {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(DayOfWeek::Sunday),
-            1 => Ok(DayOfWeek::Monday),
-            2 => Ok(DayOfWeek::Tuesday),
-            3 => Ok(DayOfWeek::Wednesday),
-            4 => Ok(DayOfWeek::Thursday),
-            5 => Ok(DayOfWeek::Friday),
-            6 => Ok(DayOfWeek::Saturday),
-            7 => Ok(DayOfWeek::All),
-            _ => panic!("Invalid DayOfWeek value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolOrderGtcMode {
-    Gtc = 0,
-    Daily = 1,
-    DailyNoStops = 2,
-}
-
-impl From<u64> for SymbolOrderGtcMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolOrderGtcMode::Gtc,
-            1 => SymbolOrderGtcMode::Daily,
-            2 => SymbolOrderGtcMode::DailyNoStops,
-            _ => panic!("Invalid SymbolOrderGtcMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolOrderGtcMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolOrderGtcMode::Gtc),
-            1 => Ok(SymbolOrderGtcMode::Daily),
-            2 => Ok(SymbolOrderGtcMode::DailyNoStops),
-            _ => panic!("Invalid SymbolOrderGtcMode value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolOptionRight {
-    Call = 0,
-    Put = 1,
-}
-
-impl From<u64> for SymbolOptionRight {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolOptionRight::Call,
-            1 => SymbolOptionRight::Put,
-            _ => panic!("Invalid SymbolOptionRight value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolOptionRight {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolOptionRight::Call),
-            1 => Ok(SymbolOptionRight::Put),
-            _ => panic!("Invalid SymbolOptionRight value: {}", value),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolOptionMode {
-    European = 0,
-    American = 1,
-}
-
-impl From<u64> for SymbolOptionMode {
-    fn from(value: u64) -> Self {
-        match value {
-            0 => SymbolOptionMode::European,
-            1 => SymbolOptionMode::American,
-            _ => panic!("Invalid SymbolOptionMode value: {}", value),
-        }
-    }
-}
-
-impl FromPyObject<'_> for SymbolOptionMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            0 => Ok(SymbolOptionMode::European),
-            1 => Ok(SymbolOptionMode::American),
-            _ => panic!("Invalid SymbolOptionMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AccountTradeMode {
+        Demo = 0,
+        Contest = 1,
+        Real = 2,
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolExpirationMode {
-    Gtc = 1,
-    Day = 2,
-    Specified = 4,
-    SpecifiedDay = 8,
-    All = 15,
-}
-
-impl From<u64> for SymbolExpirationMode {
-    fn from(value: u64) -> Self {
-        match value {
-            1 => SymbolExpirationMode::Gtc,
-            2 => SymbolExpirationMode::Day,
-            4 => SymbolExpirationMode::Specified,
-            8 => SymbolExpirationMode::SpecifiedDay,
-            15 => SymbolExpirationMode::All,
-            _ => panic!("Invalid SymbolExpirationMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AccountStopOutMode {
+        PERCENT = 0,
+        MONEY = 1,
     }
 }
 
-impl FromPyObject<'_> for SymbolExpirationMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            1 => Ok(SymbolExpirationMode::Gtc),
-            2 => Ok(SymbolExpirationMode::Day),
-            4 => Ok(SymbolExpirationMode::Specified),
-            8 => Ok(SymbolExpirationMode::SpecifiedDay),
-            15 => Ok(SymbolExpirationMode::All),
-            _ => panic!("Invalid SymbolExpirationMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AccountMarginMode {
+        RetailNetting = 0,
+        Exchange = 1,
+        RetailHedging = 2,
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolFillingMode {
-    Fok = 1,
-    Ioc = 2,
-    Boc = 3,
-}
-
-impl From<u64> for SymbolFillingMode {
-    fn from(value: u64) -> Self {
-        match value {
-            1 => SymbolFillingMode::Fok,
-            2 => SymbolFillingMode::Ioc,
-            3 => SymbolFillingMode::Boc,
-            _ => panic!("Invalid SymbolFillingMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolChartMode {
+        Bid = 0,
+        Last = 1,
     }
 }
 
-impl FromPyObject<'_> for SymbolFillingMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            1 => Ok(SymbolFillingMode::Fok),
-            2 => Ok(SymbolFillingMode::Ioc),
-            3 => Ok(SymbolFillingMode::Boc),
-            _ => panic!("Invalid SymbolFillingMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolCalcMode {
+        FOREX = 0,
+        FUTURES = 1,
+        CFD = 2,
+        CFDINDEX = 3,
+        CFDLEVERAGE = 4,
+        ForexNoLeverage = 5,
+        ExchStocks = 32,
+        ExchFutures = 33,
+        ExchOptions = 34,
+        ExchOptionsMargin = 36,
+        ExchBonds = 37,
+        ExchStocksMoex = 38,
+        ExchBondsMoex = 39,
+        ServCollateral = 64,
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum SymbolOrderMode {
-    Market = 1,
-    Limit = 2,
-    Stop = 4,
-    StopLimit = 8,
-    Sl = 16,
-    Tp = 32,
-    CloseBy = 64,
-    All = 127,
-}
-
-impl From<u64> for SymbolOrderMode {
-    fn from(value: u64) -> Self {
-        match value {
-            1 => SymbolOrderMode::Market,
-            2 => SymbolOrderMode::Limit,
-            4 => SymbolOrderMode::Stop,
-            8 => SymbolOrderMode::StopLimit,
-            16 => SymbolOrderMode::Sl,
-            32 => SymbolOrderMode::Tp,
-            64 => SymbolOrderMode::CloseBy,
-            127 => SymbolOrderMode::All,
-            _ => panic!("Invalid SymbolOrderMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolTradeMode {
+        SymbolTradeModeDisabled = 0,
+        SymbolTradeModeLongonly = 1,
+        SymbolTradeModeShortonly = 2,
+        SymbolTradeModeCloseonly = 3,
+        SymbolTradeModeFull = 4,
     }
 }
 
-impl FromPyObject<'_> for SymbolOrderMode {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: u64 = ob.extract().unwrap();
-        match value {
-            1 => Ok(SymbolOrderMode::Market),
-            2 => Ok(SymbolOrderMode::Limit),
-            4 => Ok(SymbolOrderMode::Stop),
-            8 => Ok(SymbolOrderMode::StopLimit),
-            16 => Ok(SymbolOrderMode::Sl),
-            32 => Ok(SymbolOrderMode::Tp),
-            64 => Ok(SymbolOrderMode::CloseBy),
-            127 => Ok(SymbolOrderMode::All),
-            _ => panic!("Invalid SymbolOrderMode value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolTradeExecution {
+        Request = 0,
+        Instant = 1,
+        Market = 2,
+        Exchange = 3,
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
-pub enum RuntimeError {
-    Ok = 1,
-    Fail = -1,
-    InvalidParams = -2,
-    NoMemory = -3,
-    NotFound = -4,
-    InvalidVersion = -5,
-    AuthFailed = -6,
-    Unsupported = -7,
-    AutoTradingDisabled = -8,
-    InternalFail = -10000,
-    InternalFailSend = -10001,
-    InternalFailReceive = -10002,
-    InternalFailInit = -10003,
-    InternalFailTimeout = -10005,
-}
-
-impl From<i64> for RuntimeError {
-    fn from(value: i64) -> Self {
-        match value {
-            1 => RuntimeError::Ok,
-            -1 => RuntimeError::Fail,
-            -2 => RuntimeError::InvalidParams,
-            -3 => RuntimeError::NoMemory,
-            -4 => RuntimeError::NotFound,
-            -5 => RuntimeError::InvalidVersion,
-            -6 => RuntimeError::AuthFailed,
-            -7 => RuntimeError::Unsupported,
-            -8 => RuntimeError::AutoTradingDisabled,
-            -10000 => RuntimeError::InternalFail,
-            -10001 => RuntimeError::InternalFailSend,
-            -10002 => RuntimeError::InternalFailReceive,
-            -10003 => RuntimeError::InternalFailInit,
-            -10005 => RuntimeError::InternalFailTimeout,
-            _ => panic!("Invalid RuntimeError value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolSwapMode {
+        Disabled = 0,
+        Points = 1,
+        CurrencySymbol = 2,
+        CurrencyMargin = 3,
+        CurrencyDeposit = 4,
+        InterestCurrent = 5,
+        InterestOpen = 6,
+        ReopenCurrent = 7,
+        ReopenBid = 8,
     }
 }
 
-impl FromPyObject<'_> for RuntimeError {
-    fn extract_bound(ob: &pyo3::Bound<'_, pyo3::PyAny>) -> pyo3::PyResult<Self> {
-        let value: i64 = ob.extract().unwrap();
-        match value {
-            1 => Ok(RuntimeError::Ok),
-            -1 => Ok(RuntimeError::Fail),
-            -2 => Ok(RuntimeError::InvalidParams),
-            -3 => Ok(RuntimeError::NoMemory),
-            -4 => Ok(RuntimeError::NotFound),
-            -5 => Ok(RuntimeError::InvalidVersion),
-            -6 => Ok(RuntimeError::AuthFailed),
-            -7 => Ok(RuntimeError::Unsupported),
-            -8 => Ok(RuntimeError::AutoTradingDisabled),
-            -10000 => Ok(RuntimeError::InternalFail),
-            -10001 => Ok(RuntimeError::InternalFailSend),
-            -10002 => Ok(RuntimeError::InternalFailReceive),
-            -10003 => Ok(RuntimeError::InternalFailInit),
-            -10005 => Ok(RuntimeError::InternalFailTimeout),
-            _ => panic!("Invalid RuntimeError value: {}", value),
-        }
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum DayOfWeek {
+        Sunday = 0,
+        Monday = 1,
+        Tuesday = 2,
+        Wednesday = 3,
+        Thursday = 4,
+        Friday = 5,
+        Saturday = 6,
+        All = 7,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolOrderGtcMode {
+        Gtc = 0,
+        Daily = 1,
+        DailyNoStops = 2,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolOptionRight {
+        Call = 0,
+        Put = 1,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolOptionMode {
+        European = 0,
+        American = 1,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolExpirationMode {
+        Gtc = 1,
+        Day = 2,
+        Specified = 4,
+        SpecifiedDay = 8,
+        All = 15,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolFillingMode {
+        Fok = 1,
+        Ioc = 2,
+        Boc = 3,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum SymbolOrderMode {
+        Market = 1,
+        Limit = 2,
+        Stop = 4,
+        StopLimit = 8,
+        Sl = 16,
+        Tp = 32,
+        CloseBy = 64,
+        All = 127,
+    }
+}
+
+mql_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum RuntimeError {
+        Ok = 1,
+        Fail = -1,
+        InvalidParams = -2,
+        NoMemory = -3,
+        NotFound = -4,
+        InvalidVersion = -5,
+        AuthFailed = -6,
+        Unsupported = -7,
+        AutoTradingDisabled = -8,
+        InternalFail = -10000,
+        InternalFailSend = -10001,
+        InternalFailReceive = -10002,
+        InternalFailInit = -10003,
+        InternalFailConnect = -10004,
+        InternalFailTimeout = -10005,
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            RuntimeError::Ok => "Generic success",
+            RuntimeError::Fail => "Generic fail",
+            RuntimeError::InvalidParams => "Invalid arguments/parameters",
+            RuntimeError::NoMemory => "No memory condition",
+            RuntimeError::NotFound => "No history",
+            RuntimeError::InvalidVersion => "Invalid version",
+            RuntimeError::AuthFailed => "Authorization failed",
+            RuntimeError::Unsupported => "Unsupported method",
+            RuntimeError::AutoTradingDisabled => "Auto-trading disabled",
+            RuntimeError::InternalFail => "Internal IPC general error",
+            RuntimeError::InternalFailSend => "Internal IPC send failed",
+            RuntimeError::InternalFailReceive => "Internal IPC recv failed",
+            RuntimeError::InternalFailInit => "Internal IPC initialization fail",
+            RuntimeError::InternalFailConnect => "Internal IPC no ipc",
+            RuntimeError::InternalFailTimeout => "Internal timeout",
+            RuntimeError::Unknown(value) => return write!(f, "Unknown runtime error {}", value),
+        };
+        f.write_str(description)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_unknown_value_round_trip() {
+        assert_eq!(ReturnCode::from_value(10009), ReturnCode::DONE);
+        assert_eq!(ReturnCode::from_value(10099), ReturnCode::Unknown(10099));
+        assert_eq!(ReturnCode::Unknown(10099).value(), 10099);
+        assert_eq!(i64::from(OrderType::SellStopLimit), 7);
+    }
+
+    #[test]
+    fn test_default_is_unknown() {
+        assert_eq!(OrderType::default(), OrderType::Unknown(0));
+        assert_ne!(OrderType::default(), OrderType::BUY);
+        assert!(!ReturnCode::default().is_success());
+        let result = TradeResult::default();
+        assert_eq!(result.request.action, TradeActionRequest::Unknown(0));
+    }
+
+    #[test]
+    fn test_try_from_rejects_unknown() {
+        assert_eq!(OrderType::try_from(2i64).ok(), Some(OrderType::BuyLimit));
+        match OrderType::try_from(42u64) {
+            Err(FishingLineError::Conversion { field, .. }) => assert_eq!(field, "OrderType"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert!(
+            OrderType::try_from(u64::MAX).is_err(),
+            "Overflow was accepted"
+        );
+    }
+
+    #[test]
+    fn test_return_code_classification() {
+        assert!(ReturnCode::DONE.is_success(), "DONE is a success");
+        assert!(
+            ReturnCode::DonePartial.is_partial(),
+            "DonePartial is partial"
+        );
+        assert!(ReturnCode::REQUOTE.is_retryable(), "REQUOTE is retryable");
+        assert!(ReturnCode::NoMoney.is_fatal(), "NoMoney is fatal");
+        assert!(!ReturnCode::CHECKED.is_fatal(), "CHECKED is not fatal");
+        assert!(
+            ReturnCode::Unknown(10099).is_fatal(),
+            "Unknown codes are fatal"
+        );
+    }
+
+    #[test]
+    fn test_descriptions() {
+        assert_eq!(ReturnCode::DONE.to_string(), "Request completed");
+        assert_eq!(
+            ReturnCode::Unknown(10099).to_string(),
+            "Unknown return code 10099"
+        );
+        assert_eq!(
+            RuntimeError::from_value(-10004).to_string(),
+            "Internal IPC no ipc"
+        );
+    }
+
+    #[test]
+    fn test_extract_falls_back_to_unknown() {
+        Python::with_gil(|py| {
+            let value = 10099i64.into_py(py);
+            let code: ReturnCode = value.extract(py).unwrap();
+            assert_eq!(code, ReturnCode::Unknown(10099));
+        });
     }
 }
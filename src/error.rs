@@ -0,0 +1,198 @@
+use std::fmt;
+
+use pyo3::prelude::*;
+
+use crate::enums::{ReturnCode, RuntimeError};
+use crate::schemas::TradeResult;
+use crate::validation::TradeViolation;
+
+/// Error returned by every fallible fishing-line call.
+#[derive(Debug, Clone)]
+pub enum FishingLineError {
+    /// The terminal reported a failure through `last_error`.
+    Runtime { code: RuntimeError, message: String },
+    /// The trade server answered a request with a non-success return code.
+    TradeRejected {
+        retcode: ReturnCode,
+        result: Box<TradeResult>,
+    },
+    /// A Python exception was raised while calling into the MetaTrader5 module.
+    Python {
+        message: String,
+        traceback: Option<String>,
+    },
+    /// A value returned by the terminal could not be converted into its Rust type.
+    Conversion { field: String, message: String },
+    /// The Python environment or terminal setup is not usable.
+    Environment(String),
+    /// A Python module required by the connection could not be imported.
+    ModuleImport { module: String, message: String },
+    /// A trade request was found invalid before being sent.
+    InvalidRequest(Vec<TradeViolation>),
+}
+
+impl FishingLineError {
+    pub fn runtime(code: RuntimeError, message: impl Into<String>) -> Self {
+        FishingLineError::Runtime {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Terminal runtime code, if this is a [`FishingLineError::Runtime`] error.
+    pub fn code(&self) -> Option<RuntimeError> {
+        match self {
+            FishingLineError::Runtime { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Trade server return code, if this is a [`FishingLineError::TradeRejected`] error.
+    pub fn retcode(&self) -> Option<ReturnCode> {
+        match self {
+            FishingLineError::TradeRejected { retcode, .. } => Some(*retcode),
+            _ => None,
+        }
+    }
+
+    /// Builds a conversion error from a failed PyO3 extraction, naming the field PyO3 reports.
+    pub(crate) fn conversion(context: &str, error: PyErr) -> Self {
+        let message = error.to_string();
+        let field = message
+            .split("failed to extract field ")
+            .nth(1)
+            .and_then(|rest| rest.split(':').next())
+            .unwrap_or(context)
+            .trim()
+            .to_string();
+        FishingLineError::Conversion { field, message }
+    }
+}
+
+impl fmt::Display for FishingLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FishingLineError::Runtime { code, message } => {
+                write!(
+                    f,
+                    "terminal error {:?} ({}): {}",
+                    code,
+                    code.value(),
+                    message
+                )
+            }
+            FishingLineError::TradeRejected { retcode, result } => write!(
+                f,
+                "trade request rejected with {:?} ({}): {}",
+                retcode,
+                retcode.value(),
+                result.comment
+            ),
+            FishingLineError::Python { message, traceback } => match traceback {
+                Some(traceback) => write!(f, "python error: {}\n{}", message, traceback),
+                None => write!(f, "python error: {}", message),
+            },
+            FishingLineError::Conversion { field, message } => {
+                write!(f, "unable to convert `{}`: {}", field, message)
+            }
+            FishingLineError::Environment(message) => write!(f, "environment error: {}", message),
+            FishingLineError::ModuleImport { module, message } => {
+                write!(f, "unable to import `{}`: {}", module, message)
+            }
+            FishingLineError::InvalidRequest(violations) => write!(
+                f,
+                "invalid trade request: {}",
+                violations
+                    .iter()
+                    .map(TradeViolation::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FishingLineError {}
+
+impl From<PyErr> for FishingLineError {
+    fn from(error: PyErr) -> Self {
+        Python::with_gil(|py| FishingLineError::Python {
+            message: error.to_string(),
+            traceback: error
+                .traceback_bound(py)
+                .and_then(|traceback| traceback.format().ok()),
+        })
+    }
+}
+
+/// Turns a non-success trade server answer into [`FishingLineError::TradeRejected`].
+pub(crate) fn check_trade_result(result: TradeResult) -> Result<TradeResult, FishingLineError> {
+    if result.retcode.is_success() {
+        Ok(result)
+    } else {
+        Err(FishingLineError::TradeRejected {
+            retcode: result.retcode,
+            result: Box::new(result),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::prelude::*;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_python_error_keeps_traceback() {
+        let error: FishingLineError = Python::with_gil(|py| {
+            py.run_bound(
+                "def fail():\n    raise ValueError('boom')\nfail()",
+                None,
+                None,
+            )
+            .unwrap_err()
+        })
+        .into();
+        match &error {
+            FishingLineError::Python { message, traceback } => {
+                assert!(message.contains("boom"), "Message was lost");
+                assert!(
+                    traceback
+                        .as_deref()
+                        .is_some_and(|traceback| traceback.contains("fail")),
+                    "Traceback was lost"
+                );
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_conversion_error_names_field() {
+        let error = Python::with_gil(|py| {
+            let dict = pyo3::types::PyDict::new_bound(py);
+            dict.set_item("time", "not a number").unwrap();
+            dict.extract::<SymbolTick>().unwrap_err()
+        });
+        match FishingLineError::conversion("symbol_info_tick", error) {
+            FishingLineError::Conversion { field, .. } => assert_eq!(field, "SymbolTick.time"),
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_trade_result_rejection() {
+        let rejected = crate::error::check_trade_result(TradeResult {
+            retcode: ReturnCode::NoMoney,
+            ..Default::default()
+        });
+        assert_eq!(rejected.unwrap_err().retcode(), Some(ReturnCode::NoMoney));
+
+        let placed = crate::error::check_trade_result(TradeResult {
+            retcode: ReturnCode::PLACED,
+            ..Default::default()
+        });
+        assert!(placed.is_ok(), "Placed order was rejected");
+    }
+}
@@ -17,7 +17,8 @@
 //! use fishing_line::prelude::*;
 //! dotenv::dotenv().ok();
 //! let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
-//! let connection = MT5PythonConnection::new().initialize(&terminal_path);
+//! let connection = MT5PythonConnection::new()
+//!     .and_then(|connection| connection.initialize(&terminal_path));
 //! ```
 //!
 //! or you can use your account credentials to communicate with the terminal.
@@ -37,12 +38,14 @@
 //!         .expect("Unable to find `TERMINAL_ACCOUNT_SERVER` in .env file"),
 //! };
 //!
-//! let connection = MT5PythonConnection::new().initialize_with_credentials(
-//!     &terminal_path, // terminal path
-//!     account_credentials, // account credentials
-//!     1000, // timeout
-//!     None, // portable mode
-//! );
+//! let connection = MT5PythonConnection::new().and_then(|connection| {
+//!     connection.initialize_with_credentials(
+//!         &terminal_path, // terminal path
+//!         account_credentials, // account credentials
+//!         1000, // timeout
+//!         None, // portable mode
+//!     )
+//! });
 //! ```
 //! ## Installation
 //! currently, this project is under active development. to install the latest version of this project.
@@ -65,6 +68,15 @@
 //! POETRY_ENVIRONMENT="/path/to/poetry/virtualenv"
 //! ```
 //!
+//! `MT5PythonConnection::new()` uses the environment found in `VIRTUAL_ENV`, `POETRY_ENVIRONMENT` or `CONDA_PREFIX`, in that order, and falls back to the packages of the system Python. To pick the environment explicitly, pass a `PythonEnvConfig` to `MT5PythonConnection::with_config`. It accepts venv, poetry, conda and uv environments as well as plain `site-packages` directories, and resolves both the Windows (`Lib/site-packages`) and POSIX (`lib/pythonX.Y/site-packages`) layouts, so the Windows Python can be used under Wine.
+//!
+//! ```rust,no_run
+//! use fishing_line::prelude::*;
+//!
+//! let config = PythonEnvConfig::new().venv("/home/trader/.wine/drive_c/mt5-venv");
+//! let connection = MT5PythonConnection::with_config(&config);
+//! ```
+//!
 //! 2. **Data Exchange**: Fishing-Rod uses the Python native runtime and PyO3 to send and receive data between the MQL terminal and Rust.
 //!
 //! ```rust
@@ -72,7 +84,7 @@
 //! dotenv::dotenv().ok();
 //! let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
 //! let connection = MT5PythonConnection::new()
-//!     .initialize(terminal_path.as_str())
+//!     .and_then(|connection| connection.initialize(terminal_path.as_str()))
 //!     .expect("Unable to connect to terminal");
 //! let version = connection.version().expect("Unable to get terminal version");
 //! println!("terminal version: {:?}",version.terminal_version);
@@ -88,7 +100,7 @@
 //! dotenv::dotenv().ok();
 //! let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
 //! let connection = MT5PythonConnection::new()
-//!     .initialize(terminal_path.as_str())
+//!     .and_then(|connection| connection.initialize(terminal_path.as_str()))
 //!     .expect("Unable to connect to terminal");
 //!
 //! let current_symbol = connection.symbol_info("BTCUSD").unwrap();
@@ -150,8 +162,15 @@
 //!
 //! Fishing-Rod opens up new possibilities for developers and traders in the financial market, combining the strengths of MQL, Rust, and Python in a unique and powerful way.
 
+pub mod async_terminal;
 pub mod connection;
 pub mod enums;
+pub mod error;
+pub mod orders;
 pub mod prelude;
 pub mod schemas;
+pub mod stream;
+pub mod time;
+pub mod trading;
 pub mod traits;
+pub mod validation;
@@ -0,0 +1,637 @@
+//! Typed trade requests.
+//!
+//! [`TradeRequestBuilder`] accepts any combination of fields, including ones the
+//! trade server rejects, such as a `PENDING` action with `OrderType::BUY`. The types
+//! here only take the fields their request needs, fill in the matching
+//! `TradeActionRequest` and `OrderType`, and convert into the builder:
+//!
+//! ```rust,no_run
+//! use fishing_line::prelude::*;
+//!
+//! # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+//! let buy = MarketOrder::buy("EURUSD", 0.1).sl(1.0950).tp(1.1100).deviation(10);
+//! terminal.order_send(buy.into())?;
+//!
+//! let limit = LimitOrder::sell("EURUSD", 0.1, 1.1150).comment("fade the high");
+//! terminal.order_send(limit.into())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::prelude::*;
+
+/// Direction of an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+
+    /// Side of the market order that opens a position of `position_type`.
+    pub fn of_position(position_type: PositionType) -> Option<Self> {
+        match position_type {
+            PositionType::BUY => Some(OrderSide::Buy),
+            PositionType::SELL => Some(OrderSide::Sell),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn market(self) -> OrderType {
+        match self {
+            OrderSide::Buy => OrderType::BUY,
+            OrderSide::Sell => OrderType::SELL,
+        }
+    }
+}
+
+/// Fields shared by every request opening or closing volume.
+#[derive(Debug, Clone, Default)]
+struct Common {
+    sl: Option<f64>,
+    tp: Option<f64>,
+    magic: Option<i64>,
+    comment: Option<String>,
+    type_filling: Option<OrderTypeFilling>,
+}
+
+impl Common {
+    fn apply(self, mut builder: TradeRequestBuilder) -> TradeRequestBuilder {
+        if let Some(sl) = self.sl {
+            builder = builder.sl(sl);
+        }
+        if let Some(tp) = self.tp {
+            builder = builder.tp(tp);
+        }
+        if let Some(magic) = self.magic {
+            builder = builder.magic(magic);
+        }
+        if let Some(comment) = self.comment {
+            builder = builder.comment(comment);
+        }
+        if let Some(type_filling) = self.type_filling {
+            builder = builder.type_filling(type_filling);
+        }
+        builder
+    }
+}
+
+/// Setters for the [`Common`] fields.
+macro_rules! common_setters {
+    ($name:ident, $($common:ident).+) => {
+        impl $name {
+            /// Stop loss price.
+            pub fn sl(mut self, sl: f64) -> Self {
+                self.$($common).+.sl = Some(sl);
+                self
+            }
+
+            /// Take profit price.
+            pub fn tp(mut self, tp: f64) -> Self {
+                self.$($common).+.tp = Some(tp);
+                self
+            }
+
+            pub fn magic(mut self, magic: i64) -> Self {
+                self.$($common).+.magic = Some(magic);
+                self
+            }
+
+            pub fn comment(mut self, comment: impl Into<String>) -> Self {
+                self.$($common).+.comment = Some(comment.into());
+                self
+            }
+
+            pub fn type_filling(mut self, type_filling: OrderTypeFilling) -> Self {
+                self.$($common).+.type_filling = Some(type_filling);
+                self
+            }
+        }
+    };
+}
+
+/// Buys or sells at the current price (`TradeActionRequest::DEAL`).
+///
+/// Symbols with instant or request execution (`SymbolInfo::trade_exemode`) fill at
+/// the requested price, so the caller must set it with [`MarketOrder::price`], the
+/// ask to buy and the bid to sell; [`validate`] reports a missing one. Market and
+/// exchange execution fill at the market price and ignore it.
+#[derive(Debug, Clone)]
+pub struct MarketOrder {
+    symbol: String,
+    side: OrderSide,
+    volume: f64,
+    price: Option<f64>,
+    deviation: Option<usize>,
+    position: Option<usize>,
+    common: Common,
+}
+
+impl MarketOrder {
+    pub fn new(symbol: impl Into<String>, side: OrderSide, volume: f64) -> Self {
+        MarketOrder {
+            symbol: symbol.into(),
+            side,
+            volume,
+            price: None,
+            deviation: None,
+            position: None,
+            common: Common::default(),
+        }
+    }
+
+    pub fn buy(symbol: impl Into<String>, volume: f64) -> Self {
+        MarketOrder::new(symbol, OrderSide::Buy, volume)
+    }
+
+    pub fn sell(symbol: impl Into<String>, volume: f64) -> Self {
+        MarketOrder::new(symbol, OrderSide::Sell, volume)
+    }
+
+    /// Requested price, required under instant and request execution.
+    pub fn price(mut self, price: f64) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// Largest accepted slippage, in points.
+    pub fn deviation(mut self, deviation: usize) -> Self {
+        self.deviation = Some(deviation);
+        self
+    }
+
+    /// Closes `volume` of the position with this ticket instead of opening one, as
+    /// required on hedging accounts.
+    pub fn position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn side(&self) -> OrderSide {
+        self.side
+    }
+}
+
+common_setters!(MarketOrder, common);
+
+impl From<MarketOrder> for TradeRequestBuilder {
+    fn from(order: MarketOrder) -> Self {
+        let mut builder = TradeRequestBuilder::new()
+            .action(TradeActionRequest::DEAL)
+            .symbol(order.symbol)
+            .volume(order.volume)
+            .r#type(order.side.market());
+        if let Some(price) = order.price {
+            builder = builder.price(price);
+        }
+        if let Some(deviation) = order.deviation {
+            builder = builder.deviation(deviation);
+        }
+        if let Some(position) = order.position {
+            builder = builder.position(position);
+        }
+        order.common.apply(builder)
+    }
+}
+
+/// Fields shared by pending orders.
+#[derive(Debug, Clone)]
+struct Pending {
+    symbol: String,
+    side: OrderSide,
+    volume: f64,
+    price: f64,
+    stoplimit: Option<f64>,
+    type_time: Option<OrderTypeTime>,
+    expiration: Option<DateTime<Utc>>,
+    common: Common,
+}
+
+impl Pending {
+    fn new(symbol: impl Into<String>, side: OrderSide, volume: f64, price: f64) -> Self {
+        Pending {
+            symbol: symbol.into(),
+            side,
+            volume,
+            price,
+            stoplimit: None,
+            type_time: None,
+            expiration: None,
+            common: Common::default(),
+        }
+    }
+
+    fn into_builder(self, r#type: OrderType) -> TradeRequestBuilder {
+        let mut builder = TradeRequestBuilder::new()
+            .action(TradeActionRequest::PENDING)
+            .symbol(self.symbol)
+            .volume(self.volume)
+            .price(self.price)
+            .r#type(r#type);
+        if let Some(stoplimit) = self.stoplimit {
+            builder = builder.stoplimit(stoplimit);
+        }
+        if let Some(type_time) = self.type_time {
+            builder = builder.type_time(type_time);
+        }
+        if let Some(expiration) = self.expiration {
+            builder = builder.expiration(expiration);
+        }
+        self.common.apply(builder)
+    }
+}
+
+/// Generates a pending order type with its setters and builder conversion.
+macro_rules! pending_order {
+    ($(#[$meta:meta])* $name:ident, $buy:ident, $sell:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            pending: Pending,
+        }
+
+        impl $name {
+            pub fn side(&self) -> OrderSide {
+                self.pending.side
+            }
+
+            /// Lifetime of the order, good till canceled when unset.
+            pub fn type_time(mut self, type_time: OrderTypeTime) -> Self {
+                self.pending.type_time = Some(type_time);
+                self
+            }
+
+            /// Cancels the order at `expiration`, with `OrderTypeTime::SPECIFIED`
+            /// unless another lifetime was set.
+            pub fn expiration<Tz: TimeZone>(mut self, expiration: DateTime<Tz>) -> Self {
+                self.pending.expiration = Some(expiration.with_timezone(&Utc));
+                self.pending
+                    .type_time
+                    .get_or_insert(OrderTypeTime::SPECIFIED);
+                self
+            }
+        }
+
+        common_setters!($name, pending.common);
+
+        impl From<$name> for TradeRequestBuilder {
+            fn from(order: $name) -> Self {
+                let r#type = match order.pending.side {
+                    OrderSide::Buy => OrderType::$buy,
+                    OrderSide::Sell => OrderType::$sell,
+                };
+                order.pending.into_builder(r#type)
+            }
+        }
+    };
+}
+
+pending_order!(
+    /// Buys below or sells above the market at `price` (`BuyLimit`/`SellLimit`).
+    LimitOrder,
+    BuyLimit,
+    SellLimit
+);
+
+pending_order!(
+    /// Buys above or sells below the market once it reaches `price`
+    /// (`BuyStop`/`SellStop`).
+    StopOrder,
+    BuyStop,
+    SellStop
+);
+
+pending_order!(
+    /// Places a limit order at `stoplimit` once the market reaches `price`
+    /// (`BuyStopLimit`/`SellStopLimit`).
+    StopLimitOrder,
+    BuyStopLimit,
+    SellStopLimit
+);
+
+impl LimitOrder {
+    pub fn new(symbol: impl Into<String>, side: OrderSide, volume: f64, price: f64) -> Self {
+        LimitOrder {
+            pending: Pending::new(symbol, side, volume, price),
+        }
+    }
+
+    pub fn buy(symbol: impl Into<String>, volume: f64, price: f64) -> Self {
+        LimitOrder::new(symbol, OrderSide::Buy, volume, price)
+    }
+
+    pub fn sell(symbol: impl Into<String>, volume: f64, price: f64) -> Self {
+        LimitOrder::new(symbol, OrderSide::Sell, volume, price)
+    }
+}
+
+impl StopOrder {
+    pub fn new(symbol: impl Into<String>, side: OrderSide, volume: f64, price: f64) -> Self {
+        StopOrder {
+            pending: Pending::new(symbol, side, volume, price),
+        }
+    }
+
+    pub fn buy(symbol: impl Into<String>, volume: f64, price: f64) -> Self {
+        StopOrder::new(symbol, OrderSide::Buy, volume, price)
+    }
+
+    pub fn sell(symbol: impl Into<String>, volume: f64, price: f64) -> Self {
+        StopOrder::new(symbol, OrderSide::Sell, volume, price)
+    }
+}
+
+impl StopLimitOrder {
+    pub fn new(
+        symbol: impl Into<String>,
+        side: OrderSide,
+        volume: f64,
+        price: f64,
+        stoplimit: f64,
+    ) -> Self {
+        let mut pending = Pending::new(symbol, side, volume, price);
+        pending.stoplimit = Some(stoplimit);
+        StopLimitOrder { pending }
+    }
+
+    pub fn buy(symbol: impl Into<String>, volume: f64, price: f64, stoplimit: f64) -> Self {
+        StopLimitOrder::new(symbol, OrderSide::Buy, volume, price, stoplimit)
+    }
+
+    pub fn sell(symbol: impl Into<String>, volume: f64, price: f64, stoplimit: f64) -> Self {
+        StopLimitOrder::new(symbol, OrderSide::Sell, volume, price, stoplimit)
+    }
+}
+
+/// Moves the stop loss and take profit of an open position (`TradeActionRequest::SLTP`).
+///
+/// Levels left unset are sent as `0.0`, which removes them.
+#[derive(Debug, Clone)]
+pub struct ModifyStops {
+    symbol: String,
+    position: usize,
+    sl: Option<f64>,
+    tp: Option<f64>,
+}
+
+impl ModifyStops {
+    pub fn new(symbol: impl Into<String>, position: usize) -> Self {
+        ModifyStops {
+            symbol: symbol.into(),
+            position,
+            sl: None,
+            tp: None,
+        }
+    }
+
+    pub fn sl(mut self, sl: f64) -> Self {
+        self.sl = Some(sl);
+        self
+    }
+
+    pub fn tp(mut self, tp: f64) -> Self {
+        self.tp = Some(tp);
+        self
+    }
+}
+
+impl From<ModifyStops> for TradeRequestBuilder {
+    fn from(modify: ModifyStops) -> Self {
+        TradeRequestBuilder::new()
+            .action(TradeActionRequest::SLTP)
+            .symbol(modify.symbol)
+            .position(modify.position)
+            .sl(modify.sl.unwrap_or_default())
+            .tp(modify.tp.unwrap_or_default())
+    }
+}
+
+/// Changes the price, stops or lifetime of a pending order
+/// (`TradeActionRequest::MODIFY`).
+///
+/// The request replaces every level: stop loss and take profit left unset are
+/// sent as `0.0`, which removes them.
+#[derive(Debug, Clone)]
+pub struct ModifyPending {
+    order: usize,
+    price: f64,
+    stoplimit: Option<f64>,
+    sl: Option<f64>,
+    tp: Option<f64>,
+    type_time: Option<OrderTypeTime>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl ModifyPending {
+    pub fn new(order: usize, price: f64) -> Self {
+        ModifyPending {
+            order,
+            price,
+            stoplimit: None,
+            sl: None,
+            tp: None,
+            type_time: None,
+            expiration: None,
+        }
+    }
+
+    /// Limit price of a stop limit order.
+    pub fn stoplimit(mut self, stoplimit: f64) -> Self {
+        self.stoplimit = Some(stoplimit);
+        self
+    }
+
+    pub fn sl(mut self, sl: f64) -> Self {
+        self.sl = Some(sl);
+        self
+    }
+
+    pub fn tp(mut self, tp: f64) -> Self {
+        self.tp = Some(tp);
+        self
+    }
+
+    pub fn type_time(mut self, type_time: OrderTypeTime) -> Self {
+        self.type_time = Some(type_time);
+        self
+    }
+
+    /// Cancels the order at `expiration`, with `OrderTypeTime::SPECIFIED` unless
+    /// another lifetime was set.
+    pub fn expiration<Tz: TimeZone>(mut self, expiration: DateTime<Tz>) -> Self {
+        self.expiration = Some(expiration.with_timezone(&Utc));
+        self.type_time.get_or_insert(OrderTypeTime::SPECIFIED);
+        self
+    }
+}
+
+impl From<ModifyPending> for TradeRequestBuilder {
+    fn from(modify: ModifyPending) -> Self {
+        let mut builder = TradeRequestBuilder::new()
+            .action(TradeActionRequest::MODIFY)
+            .order(modify.order)
+            .price(modify.price)
+            .sl(modify.sl.unwrap_or_default())
+            .tp(modify.tp.unwrap_or_default());
+        if let Some(stoplimit) = modify.stoplimit {
+            builder = builder.stoplimit(stoplimit);
+        }
+        if let Some(type_time) = modify.type_time {
+            builder = builder.type_time(type_time);
+        }
+        if let Some(expiration) = modify.expiration {
+            builder = builder.expiration(expiration);
+        }
+        builder
+    }
+}
+
+/// Deletes a pending order (`TradeActionRequest::REMOVE`).
+#[derive(Debug, Clone)]
+pub struct CancelPending {
+    order: usize,
+}
+
+impl CancelPending {
+    pub fn new(order: usize) -> Self {
+        CancelPending { order }
+    }
+}
+
+impl From<CancelPending> for TradeRequestBuilder {
+    fn from(cancel: CancelPending) -> Self {
+        TradeRequestBuilder::new()
+            .action(TradeActionRequest::REMOVE)
+            .order(cancel.order)
+    }
+}
+
+/// Closes a position with an opposite one of the same symbol
+/// (`TradeActionRequest::CloseBy`), on hedging accounts only.
+#[derive(Debug, Clone)]
+pub struct CloseBy {
+    position: usize,
+    position_by: usize,
+    magic: Option<i64>,
+    comment: Option<String>,
+}
+
+impl CloseBy {
+    pub fn new(position: usize, position_by: usize) -> Self {
+        CloseBy {
+            position,
+            position_by,
+            magic: None,
+            comment: None,
+        }
+    }
+
+    pub fn magic(mut self, magic: i64) -> Self {
+        self.magic = Some(magic);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+}
+
+impl From<CloseBy> for TradeRequestBuilder {
+    fn from(close: CloseBy) -> Self {
+        let mut builder = TradeRequestBuilder::new()
+            .action(TradeActionRequest::CloseBy)
+            .r#type(OrderType::CloseBy)
+            .position(close.position)
+            .position_by(close.position_by);
+        if let Some(magic) = close.magic {
+            builder = builder.magic(magic);
+        }
+        if let Some(comment) = close.comment {
+            builder = builder.comment(comment);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Utc};
+
+    use crate::prelude::*;
+
+    #[test]
+    fn test_market_order_into_builder() {
+        let request: TradeRequestBuilder = MarketOrder::sell("EURUSD", 0.2)
+            .sl(1.1050)
+            .deviation(10)
+            .position(42)
+            .comment("close")
+            .into();
+        assert_eq!(request.action, Some(TradeActionRequest::DEAL));
+        assert_eq!(request.r#type, Some(OrderType::SELL));
+        assert_eq!(request.symbol.as_deref(), Some("EURUSD"));
+        assert_eq!(request.volume, Some(0.2));
+        assert_eq!(request.sl, Some(1.1050));
+        assert_eq!(request.position, Some(42));
+        assert_eq!(request.price, None);
+        assert_eq!(request.tp, None);
+    }
+
+    #[test]
+    fn test_pending_orders_into_builder() {
+        let expiration: DateTime<Utc> = DateTime::from_timestamp(1_721_606_400, 0).unwrap();
+        let limit: TradeRequestBuilder = LimitOrder::buy("EURUSD", 0.1, 1.0900)
+            .tp(1.1000)
+            .expiration(expiration)
+            .into();
+        assert_eq!(limit.action, Some(TradeActionRequest::PENDING));
+        assert_eq!(limit.r#type, Some(OrderType::BuyLimit));
+        assert_eq!(limit.price, Some(1.0900));
+        assert_eq!(limit.tp, Some(1.1000));
+        assert_eq!(limit.type_time, Some(OrderTypeTime::SPECIFIED));
+        assert_eq!(limit.expiration, Some(expiration));
+
+        let stop: TradeRequestBuilder = StopOrder::sell("EURUSD", 0.1, 1.0800).into();
+        assert_eq!(stop.r#type, Some(OrderType::SellStop));
+        assert_eq!(stop.type_time, None);
+
+        let stop_limit: TradeRequestBuilder =
+            StopLimitOrder::buy("EURUSD", 0.1, 1.1100, 1.1080).into();
+        assert_eq!(stop_limit.r#type, Some(OrderType::BuyStopLimit));
+        assert_eq!(stop_limit.stoplimit, Some(1.1080));
+    }
+
+    #[test]
+    fn test_modifications_into_builder() {
+        let stops: TradeRequestBuilder = ModifyStops::new("EURUSD", 7).tp(1.1200).into();
+        assert_eq!(stops.action, Some(TradeActionRequest::SLTP));
+        assert_eq!(stops.position, Some(7));
+        assert_eq!((stops.sl, stops.tp), (Some(0.0), Some(1.1200)));
+        assert_eq!(stops.volume, None);
+
+        let modify: TradeRequestBuilder = ModifyPending::new(8, 1.0950).sl(1.0900).into();
+        assert_eq!(modify.action, Some(TradeActionRequest::MODIFY));
+        assert_eq!((modify.order, modify.price), (Some(8), Some(1.0950)));
+
+        let cancel: TradeRequestBuilder = CancelPending::new(8).into();
+        assert_eq!(cancel.action, Some(TradeActionRequest::REMOVE));
+        assert_eq!(cancel.order, Some(8));
+
+        let close_by: TradeRequestBuilder = CloseBy::new(7, 9).into();
+        assert_eq!(close_by.action, Some(TradeActionRequest::CloseBy));
+        assert_eq!(
+            (close_by.position, close_by.position_by),
+            (Some(7), Some(9))
+        );
+    }
+}
@@ -1,7 +1,24 @@
 pub type MQLResult<T> = Result<T, MQLError>;
-pub type MQLError = (RuntimeError, String);
+pub type MQLError = FishingLineError;
+pub use crate::async_terminal::AsyncTerminal;
+pub use crate::connection::bridge::MT5BridgeConnection;
+pub use crate::connection::environment::{EnvLayout, PythonEnvConfig};
+pub use crate::connection::mock::MockTerminal;
 pub use crate::connection::python::MT5PythonConnection;
+pub use crate::connection::simulated::SimulatedBroker;
 pub use crate::enums::*;
+pub use crate::error::FishingLineError;
+pub use crate::orders::{
+    CancelPending, CloseBy, LimitOrder, MarketOrder, ModifyPending, ModifyStops, OrderSide,
+    StopLimitOrder, StopOrder,
+};
 pub use crate::schemas::*;
+pub use crate::stream::{
+    AccountAlert, AccountEvent, AccountMonitor, AccountWatcher, ClosedBar, StreamOptions,
+    SubscribeTrait, Subscription, TradeEvent, TradeEventMonitor,
+};
+pub use crate::time::{DstRule, ServerTimezone};
+pub use crate::trading::{Reversal, TradingTrait};
 pub use crate::traits::*;
+pub use crate::validation::{validate, validate_with_type, TradeViolation};
 pub use struct_iterable::Iterable;
@@ -1,53 +1,74 @@
-use std::any::TypeId;
-
+use chrono::{DateTime, TimeZone, Utc};
 use pyo3;
+use pyo3::exceptions::PyValueError;
 use pyo3::Python;
 use pyo3::{prelude::*, types::PyDict, FromPyObject, IntoPy, PyObject};
 use serde::{Deserialize, Serialize};
 use struct_iterable::Iterable;
 
-use crate::enums::{self, AccountInfoProperty, RuntimeError, TerminalInfoProperty};
+use crate::enums::{self, AccountInfoProperty, TerminalInfoProperty};
 use crate::prelude::{
-    AccountMarginMode, AccountStopOutMode, AccountTradeMode, DayOfWeek, DealEntry, DealReason,
-    DealType, MQLResult, OrderReason, OrderState, OrderType, OrderTypeFilling, OrderTypeTime,
-    PositionReason, PositionType, ReturnCode, SymbolCalcMode, SymbolChartMode,
-    SymbolExpirationMode, SymbolFillingMode, SymbolOptionMode, SymbolOptionRight,
+    AccountMarginMode, AccountStopOutMode, AccountTradeMode, BookType, DayOfWeek, DealEntry,
+    DealReason, DealType, FishingLineError, MQLResult, OrderReason, OrderState, OrderType,
+    OrderTypeFilling, OrderTypeTime, PositionReason, PositionType, ReturnCode, SymbolCalcMode,
+    SymbolChartMode, SymbolExpirationMode, SymbolFillingMode, SymbolOptionMode, SymbolOptionRight,
     SymbolOrderGtcMode, SymbolOrderMode, SymbolSwapMode, SymbolTradeExecution, SymbolTradeMode,
     TradeActionRequest,
 };
-use crate::traits::{AccountInfoTrait, InfoProperties, InfoTrait};
+use crate::traits::{InfoProperties, InfoTrait};
+
+fn property_not_found(kind: &str) -> FishingLineError {
+    FishingLineError::Conversion {
+        field: "info_property".to_string(),
+        message: format!("Property has no {} value", kind),
+    }
+}
+
+/// Reads an epoch-seconds timestamp as returned by the terminal.
+fn datetime_from_seconds(ob: &Bound<'_, PyAny>) -> PyResult<DateTime<Utc>> {
+    let seconds: i64 = ob.extract()?;
+    DateTime::from_timestamp(seconds, 0)
+        .ok_or_else(|| PyValueError::new_err(format!("timestamp out of range: {}", seconds)))
+}
 
-#[derive(Serialize, Deserialize, FromPyObject, Debug, Iterable)]
+/// Reads an epoch-milliseconds timestamp from one of the terminal's `*_msc` fields.
+fn datetime_from_millis(ob: &Bound<'_, PyAny>) -> PyResult<DateTime<Utc>> {
+    let millis: i64 = ob.extract()?;
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| PyValueError::new_err(format!("timestamp out of range: {}", millis)))
+}
+
+#[derive(Serialize, Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
 pub struct TerminalVersion {
     pub terminal_version: i64,
     pub build: i64,
     pub build_date: String,
 }
 
-#[derive(Serialize, Deserialize, FromPyObject, Debug, Iterable)]
+#[derive(Serialize, Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
 #[pyo3(from_item_all)]
 pub struct TerminalInfo {
-    community_account: bool,
-    community_connection: bool,
-    connected: bool,
-    dlls_allowed: bool,
-    trade_allowed: bool,
-    email_enabled: bool,
-    ftp_enabled: bool,
-    notifications_enabled: bool,
-    mqid: bool,
-    build: i64,
-    maxbars: i64,
-    codepage: i64,
-    ping_last: i64,
-    community_balance: f64,
-    retransmission: f64,
-    company: String,
-    name: String,
-    language: String,
-    path: String,
-    data_path: String,
-    commondata_path: String,
+    pub community_account: bool,
+    pub community_connection: bool,
+    pub connected: bool,
+    pub dlls_allowed: bool,
+    pub trade_allowed: bool,
+    pub email_enabled: bool,
+    pub ftp_enabled: bool,
+    pub notifications_enabled: bool,
+    pub mqid: bool,
+    pub build: i64,
+    pub maxbars: i64,
+    pub codepage: i64,
+    pub ping_last: i64,
+    pub community_balance: f64,
+    pub retransmission: f64,
+    pub company: String,
+    pub name: String,
+    pub language: String,
+    pub path: String,
+    pub data_path: String,
+    pub commondata_path: String,
 }
 
 impl InfoTrait for TerminalInfo {
@@ -61,150 +82,150 @@ impl InfoTrait for TerminalInfo {
                 TerminalInfoProperty::DataPath => self.data_path.clone(),
                 TerminalInfoProperty::CommonDataPath => self.commondata_path.clone(),
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("string"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("string")),
         };
         Ok(value)
     }
     fn get_info_integer(&self, info_property: InfoProperties) -> MQLResult<i64> {
         let value = match info_property {
             InfoProperties::TerminalInfoProperty(property) => match property {
-                TerminalInfoProperty::Build => self.build.clone(),
-                TerminalInfoProperty::MaxBars => self.maxbars.clone(),
-                TerminalInfoProperty::CodePage => self.codepage.clone(),
-                TerminalInfoProperty::PingLast => self.ping_last.clone(),
+                TerminalInfoProperty::Build => self.build,
+                TerminalInfoProperty::MaxBars => self.maxbars,
+                TerminalInfoProperty::CodePage => self.codepage,
+                TerminalInfoProperty::PingLast => self.ping_last,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("integer"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("integer")),
         };
         Ok(value)
     }
     fn get_info_float(&self, info_property: InfoProperties) -> MQLResult<f64> {
         let value = match info_property {
             InfoProperties::TerminalInfoProperty(property) => match property {
-                TerminalInfoProperty::CommunityBalance => self.community_balance.clone(),
-                TerminalInfoProperty::Retransmission => self.retransmission.clone(),
+                TerminalInfoProperty::CommunityBalance => self.community_balance,
+                TerminalInfoProperty::Retransmission => self.retransmission,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("float"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("float")),
         };
         Ok(value)
     }
     fn get_info_boolean(&self, info_property: InfoProperties) -> MQLResult<bool> {
         let value = match info_property {
             InfoProperties::TerminalInfoProperty(property) => match property {
-                TerminalInfoProperty::CommunityAccount => self.community_account.clone(),
-                TerminalInfoProperty::CommunityConnection => self.community_connection.clone(),
-                TerminalInfoProperty::Connected => self.connected.clone(),
-                TerminalInfoProperty::DllsAllowed => self.dlls_allowed.clone(),
-                TerminalInfoProperty::TradeAllowed => self.trade_allowed.clone(),
-                TerminalInfoProperty::EmailEnabled => self.email_enabled.clone(),
-                TerminalInfoProperty::FtpEnabled => self.ftp_enabled.clone(),
-                TerminalInfoProperty::NotificationsEnabled => self.notifications_enabled.clone(),
+                TerminalInfoProperty::CommunityAccount => self.community_account,
+                TerminalInfoProperty::CommunityConnection => self.community_connection,
+                TerminalInfoProperty::Connected => self.connected,
+                TerminalInfoProperty::DllsAllowed => self.dlls_allowed,
+                TerminalInfoProperty::TradeAllowed => self.trade_allowed,
+                TerminalInfoProperty::EmailEnabled => self.email_enabled,
+                TerminalInfoProperty::FtpEnabled => self.ftp_enabled,
+                TerminalInfoProperty::NotificationsEnabled => self.notifications_enabled,
                 TerminalInfoProperty::MqId => self.mqid,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("boolean"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("boolean")),
         };
         Ok(value)
     }
 }
 
-#[derive(Deserialize, FromPyObject, Debug, Iterable)]
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
 #[pyo3(from_item_all)]
 pub struct AccountInfo {
-    login: i64,
-    trade_mode: AccountTradeMode,
-    leverage: i64,
-    limit_orders: i64,
-    margin_so_mode: AccountStopOutMode,
-    trade_allowed: bool,
-    trade_expert: bool,
-    margin_mode: AccountMarginMode,
-    currency_digits: i64,
-    fifo_close: bool,
-    balance: f64,
-    credit: f64,
-    profit: f64,
-    equity: f64,
-    margin: f64,
-    margin_free: f64,
-    margin_level: f64,
-    margin_so_call: f64,
-    margin_so_so: f64,
-    margin_initial: f64,
-    margin_maintenance: f64,
-    assets: f64,
-    liabilities: f64,
-    commission_blocked: f64,
-    name: String,
-    server: String,
-    currency: String,
-    company: String,
+    pub login: i64,
+    pub trade_mode: AccountTradeMode,
+    pub leverage: i64,
+    pub limit_orders: i64,
+    pub margin_so_mode: AccountStopOutMode,
+    pub trade_allowed: bool,
+    pub trade_expert: bool,
+    pub margin_mode: AccountMarginMode,
+    pub currency_digits: i64,
+    pub fifo_close: bool,
+    pub balance: f64,
+    pub credit: f64,
+    pub profit: f64,
+    pub equity: f64,
+    pub margin: f64,
+    pub margin_free: f64,
+    pub margin_level: f64,
+    pub margin_so_call: f64,
+    pub margin_so_so: f64,
+    pub margin_initial: f64,
+    pub margin_maintenance: f64,
+    pub assets: f64,
+    pub liabilities: f64,
+    pub commission_blocked: f64,
+    pub name: String,
+    pub server: String,
+    pub currency: String,
+    pub company: String,
 }
 
 impl InfoTrait for AccountInfo {
     fn get_info_float(&self, info_property: crate::prelude::InfoProperties) -> MQLResult<f64> {
         let value = match info_property {
             InfoProperties::AccountInfoProperty(property) => match property {
-                AccountInfoProperty::Balance => self.balance.clone(),
-                AccountInfoProperty::Profit => self.profit.clone(),
-                AccountInfoProperty::Equity => self.equity.clone(),
-                AccountInfoProperty::Margin => self.margin.clone(),
-                AccountInfoProperty::MarginFree => self.margin_free.clone(),
-                AccountInfoProperty::MarginLevel => self.margin_level.clone(),
-                AccountInfoProperty::MarginSoCall => self.margin_so_call.clone(),
-                AccountInfoProperty::MarginSoSo => self.margin_so_so.clone(),
-                AccountInfoProperty::MarginInitial => self.margin_initial.clone(),
-                AccountInfoProperty::MarginMaintenance => self.margin_maintenance.clone(),
-                AccountInfoProperty::Assets => self.assets.clone(),
-                AccountInfoProperty::Liabilities => self.liabilities.clone(),
-                AccountInfoProperty::CommissionBlocked => self.commission_blocked.clone(),
+                AccountInfoProperty::Balance => self.balance,
+                AccountInfoProperty::Profit => self.profit,
+                AccountInfoProperty::Equity => self.equity,
+                AccountInfoProperty::Margin => self.margin,
+                AccountInfoProperty::MarginFree => self.margin_free,
+                AccountInfoProperty::MarginLevel => self.margin_level,
+                AccountInfoProperty::MarginSoCall => self.margin_so_call,
+                AccountInfoProperty::MarginSoSo => self.margin_so_so,
+                AccountInfoProperty::MarginInitial => self.margin_initial,
+                AccountInfoProperty::MarginMaintenance => self.margin_maintenance,
+                AccountInfoProperty::Assets => self.assets,
+                AccountInfoProperty::Liabilities => self.liabilities,
+                AccountInfoProperty::CommissionBlocked => self.commission_blocked,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("float"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("float")),
         };
         Ok(value)
     }
     fn get_info_boolean(&self, info_property: InfoProperties) -> MQLResult<bool> {
         let value = match info_property {
             InfoProperties::AccountInfoProperty(property) => match property {
-                AccountInfoProperty::TradeAllowed => self.trade_allowed.clone(),
-                AccountInfoProperty::TradeExpert => self.trade_expert.clone(),
-                AccountInfoProperty::FifoClose => self.fifo_close.clone(),
+                AccountInfoProperty::TradeAllowed => self.trade_allowed,
+                AccountInfoProperty::TradeExpert => self.trade_expert,
+                AccountInfoProperty::FifoClose => self.fifo_close,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("boolean"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("boolean")),
         };
         Ok(value)
     }
     fn get_info_integer(&self, info_property: InfoProperties) -> MQLResult<i64> {
         let value = match info_property {
             InfoProperties::AccountInfoProperty(property) => match property {
-                AccountInfoProperty::Login => self.login.clone(),
-                AccountInfoProperty::TradeMode => (self.trade_mode as i64).clone(),
-                AccountInfoProperty::Leverage => self.leverage.clone(),
-                AccountInfoProperty::LimitOrders => self.limit_orders.clone(),
-                AccountInfoProperty::MarginSoMode => (self.margin_so_mode as i64).clone(),
-                AccountInfoProperty::MarginMode => (self.margin_mode as i64).clone(),
-                AccountInfoProperty::CurrencyDigits => self.currency_digits.clone(),
+                AccountInfoProperty::Login => self.login,
+                AccountInfoProperty::TradeMode => self.trade_mode.value(),
+                AccountInfoProperty::Leverage => self.leverage,
+                AccountInfoProperty::LimitOrders => self.limit_orders,
+                AccountInfoProperty::MarginSoMode => self.margin_so_mode.value(),
+                AccountInfoProperty::MarginMode => self.margin_mode.value(),
+                AccountInfoProperty::CurrencyDigits => self.currency_digits,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("integer"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("integer")),
         };
         Ok(value)
     }
@@ -216,10 +237,10 @@ impl InfoTrait for AccountInfo {
                 AccountInfoProperty::Name => self.name.clone(),
                 AccountInfoProperty::Server => self.server.clone(),
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("string"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("string")),
         };
         Ok(value)
     }
@@ -232,105 +253,105 @@ pub struct AccountCredentials {
     pub server: String,
 }
 
-#[derive(Deserialize, FromPyObject, Debug, Iterable)]
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
 #[pyo3(from_item_all)]
 pub struct SymbolInfo {
-    custom: bool,
-    chart_mode: SymbolChartMode,
-    select: bool,
-    visible: bool,
-    session_deals: i64,
-    session_buy_orders: i64,
-    session_sell_orders: i64,
-    volume: f64,
-    volumehigh: f64,
-    volumelow: f64,
-    time: i64,
-    digits: i64,
-    spread: i64,
-    spread_float: bool,
-    ticks_bookdepth: i64,
-    trade_calc_mode: SymbolCalcMode,
-    trade_mode: SymbolTradeMode,
-    start_time: i64,
-    expiration_time: i64,
-    trade_stops_level: i64,
-    trade_freeze_level: i64,
-    trade_exemode: SymbolTradeExecution,
-    swap_mode: SymbolSwapMode,
-    swap_rollover3days: DayOfWeek,
-    margin_hedged_use_leg: bool,
-    expiration_mode: SymbolExpirationMode,
-    filling_mode: SymbolFillingMode,
-    order_mode: SymbolOrderMode,
-    order_gtc_mode: SymbolOrderGtcMode,
-    option_mode: SymbolOptionMode,
-    option_right: SymbolOptionRight,
-    bid: f64,
-    bidhigh: f64,
-    bidlow: f64,
-    ask: f64,
-    askhigh: f64,
-    asklow: f64,
-    last: f64,
-    lasthigh: f64,
-    lastlow: f64,
-    volume_real: f64,
-    volumehigh_real: f64,
-    volumelow_real: f64,
-    option_strike: f64,
-    point: f64,
-    trade_tick_value: f64,
-    trade_tick_value_profit: f64,
-    trade_tick_value_loss: f64,
-    trade_tick_size: f64,
-    trade_contract_size: f64,
-    trade_accrued_interest: f64,
-    trade_face_value: f64,
-    trade_liquidity_rate: f64,
-    volume_min: f64,
-    volume_max: f64,
-    volume_step: f64,
-    volume_limit: f64,
-    swap_long: f64,
-    swap_short: f64,
-    margin_initial: f64,
-    margin_maintenance: f64,
-    session_volume: f64,
-    session_turnover: f64,
-    session_interest: f64,
-    session_buy_orders_volume: f64,
-    session_sell_orders_volume: f64,
-    session_open: f64,
-    session_close: f64,
-    session_aw: f64,
-    session_price_settlement: f64,
-    session_price_limit_min: f64,
-    session_price_limit_max: f64,
-    margin_hedged: f64,
-    price_change: f64,
-    price_volatility: f64,
-    price_theoretical: f64,
-    price_greeks_delta: f64,
-    price_greeks_theta: f64,
-    price_greeks_gamma: f64,
-    price_greeks_vega: f64,
-    price_greeks_rho: f64,
-    price_greeks_omega: f64,
-    price_sensitivity: f64,
-    basis: String,
-    category: String,
-    currency_base: String,
-    currency_profit: String,
-    currency_margin: String,
-    bank: String,
-    description: String,
-    exchange: String,
-    formula: String,
-    isin: String,
-    name: String,
-    page: String,
-    path: String,
+    pub custom: bool,
+    pub chart_mode: SymbolChartMode,
+    pub select: bool,
+    pub visible: bool,
+    pub session_deals: i64,
+    pub session_buy_orders: i64,
+    pub session_sell_orders: i64,
+    pub volume: f64,
+    pub volumehigh: f64,
+    pub volumelow: f64,
+    pub time: i64,
+    pub digits: i64,
+    pub spread: i64,
+    pub spread_float: bool,
+    pub ticks_bookdepth: i64,
+    pub trade_calc_mode: SymbolCalcMode,
+    pub trade_mode: SymbolTradeMode,
+    pub start_time: i64,
+    pub expiration_time: i64,
+    pub trade_stops_level: i64,
+    pub trade_freeze_level: i64,
+    pub trade_exemode: SymbolTradeExecution,
+    pub swap_mode: SymbolSwapMode,
+    pub swap_rollover3days: DayOfWeek,
+    pub margin_hedged_use_leg: bool,
+    pub expiration_mode: SymbolExpirationMode,
+    pub filling_mode: SymbolFillingMode,
+    pub order_mode: SymbolOrderMode,
+    pub order_gtc_mode: SymbolOrderGtcMode,
+    pub option_mode: SymbolOptionMode,
+    pub option_right: SymbolOptionRight,
+    pub bid: f64,
+    pub bidhigh: f64,
+    pub bidlow: f64,
+    pub ask: f64,
+    pub askhigh: f64,
+    pub asklow: f64,
+    pub last: f64,
+    pub lasthigh: f64,
+    pub lastlow: f64,
+    pub volume_real: f64,
+    pub volumehigh_real: f64,
+    pub volumelow_real: f64,
+    pub option_strike: f64,
+    pub point: f64,
+    pub trade_tick_value: f64,
+    pub trade_tick_value_profit: f64,
+    pub trade_tick_value_loss: f64,
+    pub trade_tick_size: f64,
+    pub trade_contract_size: f64,
+    pub trade_accrued_interest: f64,
+    pub trade_face_value: f64,
+    pub trade_liquidity_rate: f64,
+    pub volume_min: f64,
+    pub volume_max: f64,
+    pub volume_step: f64,
+    pub volume_limit: f64,
+    pub swap_long: f64,
+    pub swap_short: f64,
+    pub margin_initial: f64,
+    pub margin_maintenance: f64,
+    pub session_volume: f64,
+    pub session_turnover: f64,
+    pub session_interest: f64,
+    pub session_buy_orders_volume: f64,
+    pub session_sell_orders_volume: f64,
+    pub session_open: f64,
+    pub session_close: f64,
+    pub session_aw: f64,
+    pub session_price_settlement: f64,
+    pub session_price_limit_min: f64,
+    pub session_price_limit_max: f64,
+    pub margin_hedged: f64,
+    pub price_change: f64,
+    pub price_volatility: f64,
+    pub price_theoretical: f64,
+    pub price_greeks_delta: f64,
+    pub price_greeks_theta: f64,
+    pub price_greeks_gamma: f64,
+    pub price_greeks_vega: f64,
+    pub price_greeks_rho: f64,
+    pub price_greeks_omega: f64,
+    pub price_sensitivity: f64,
+    pub basis: String,
+    pub category: String,
+    pub currency_base: String,
+    pub currency_profit: String,
+    pub currency_margin: String,
+    pub bank: String,
+    pub description: String,
+    pub exchange: String,
+    pub formula: String,
+    pub isin: String,
+    pub name: String,
+    pub page: String,
+    pub path: String,
 }
 
 impl InfoTrait for SymbolInfo {
@@ -351,122 +372,122 @@ impl InfoTrait for SymbolInfo {
                 enums::SymbolInfoProperty::Page => self.page.clone(),
                 enums::SymbolInfoProperty::Path => self.path.clone(),
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("string"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("string")),
         };
         Ok(value)
     }
     fn get_info_integer(&self, info_property: InfoProperties) -> MQLResult<i64> {
         let value = match info_property {
             InfoProperties::SymbolInfoProperty(property) => match property {
-                enums::SymbolInfoProperty::SessionDeals => self.session_deals.clone(),
-                enums::SymbolInfoProperty::SessionBuyOrders => self.session_buy_orders.clone(),
-                enums::SymbolInfoProperty::SessionSellOrders => self.session_sell_orders.clone(),
-                enums::SymbolInfoProperty::Time => self.time.clone(),
-                enums::SymbolInfoProperty::Digits => self.digits.clone(),
-                enums::SymbolInfoProperty::Spread => self.spread.clone(),
-                enums::SymbolInfoProperty::TicksBookDepth => self.ticks_bookdepth.clone(),
-                enums::SymbolInfoProperty::StartTime => self.start_time.clone(),
-                enums::SymbolInfoProperty::ExpirationTime => self.expiration_time.clone(),
-                enums::SymbolInfoProperty::TradeStopsLevel => self.trade_stops_level.clone(),
-                enums::SymbolInfoProperty::TradeFreezeLevel => self.trade_freeze_level.clone(),
+                enums::SymbolInfoProperty::SessionDeals => self.session_deals,
+                enums::SymbolInfoProperty::SessionBuyOrders => self.session_buy_orders,
+                enums::SymbolInfoProperty::SessionSellOrders => self.session_sell_orders,
+                enums::SymbolInfoProperty::Time => self.time,
+                enums::SymbolInfoProperty::Digits => self.digits,
+                enums::SymbolInfoProperty::Spread => self.spread,
+                enums::SymbolInfoProperty::TicksBookDepth => self.ticks_bookdepth,
+                enums::SymbolInfoProperty::StartTime => self.start_time,
+                enums::SymbolInfoProperty::ExpirationTime => self.expiration_time,
+                enums::SymbolInfoProperty::TradeStopsLevel => self.trade_stops_level,
+                enums::SymbolInfoProperty::TradeFreezeLevel => self.trade_freeze_level,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("integer"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("integer")),
         };
         Ok(value)
     }
     fn get_info_float(&self, info_property: InfoProperties) -> MQLResult<f64> {
         let value = match info_property {
             InfoProperties::SymbolInfoProperty(property) => match property {
-                enums::SymbolInfoProperty::Volume => self.volume.clone(),
-                enums::SymbolInfoProperty::VolumeHigh => self.volumehigh.clone(),
-                enums::SymbolInfoProperty::VolumeLow => self.volumelow.clone(),
-                enums::SymbolInfoProperty::Bid => self.bid.clone(),
-                enums::SymbolInfoProperty::BidHigh => self.bidhigh.clone(),
-                enums::SymbolInfoProperty::BidLow => self.bidlow.clone(),
-                enums::SymbolInfoProperty::Ask => self.ask.clone(),
-                enums::SymbolInfoProperty::AskHigh => self.askhigh.clone(),
-                enums::SymbolInfoProperty::AskLow => self.asklow.clone(),
-                enums::SymbolInfoProperty::Last => self.last.clone(),
-                enums::SymbolInfoProperty::LastHigh => self.lasthigh.clone(),
-                enums::SymbolInfoProperty::LastLow => self.lastlow.clone(),
-                enums::SymbolInfoProperty::VolumeReal => self.volume_real.clone(),
-                enums::SymbolInfoProperty::VolumeHighReal => self.volumehigh_real.clone(),
-                enums::SymbolInfoProperty::VolumeLowReal => self.volumelow_real.clone(),
-                enums::SymbolInfoProperty::OptionStrike => self.option_strike.clone(),
-                enums::SymbolInfoProperty::Point => self.point.clone(),
-                enums::SymbolInfoProperty::TradeTickValue => self.trade_tick_value.clone(),
-                enums::SymbolInfoProperty::TradeTickValueProfit => self.trade_tick_value_profit.clone(),
-                enums::SymbolInfoProperty::TradeTickValueLoss => self.trade_tick_value_loss.clone(),
-                enums::SymbolInfoProperty::TradeTickSize => self.trade_tick_size.clone(),
-                enums::SymbolInfoProperty::TradeContractSize => self.trade_contract_size.clone(),
-                enums::SymbolInfoProperty::TradeAccruedInterest => self.trade_accrued_interest.clone(),
-                enums::SymbolInfoProperty::TradeFaceValue => self.trade_face_value.clone(),
-                enums::SymbolInfoProperty::TradeLiquidityRate => self.trade_liquidity_rate.clone(),
-                enums::SymbolInfoProperty::VolumeMin => self.volume_min.clone(),
-                enums::SymbolInfoProperty::VolumeMax => self.volume_max.clone(),
-                enums::SymbolInfoProperty::VolumeStep => self.volume_step.clone(),
-                enums::SymbolInfoProperty::VolumeLimit => self.volume_limit.clone(),
-                enums::SymbolInfoProperty::SwapLong => self.swap_long.clone(),
-                enums::SymbolInfoProperty::SwapShort => self.swap_short.clone(),
-                enums::SymbolInfoProperty::MarginInitial => self.margin_initial.clone(),
-                enums::SymbolInfoProperty::MarginMaintenance => self.margin_maintenance.clone(),
-                enums::SymbolInfoProperty::SessionVolume => self.session_volume.clone(),
-                enums::SymbolInfoProperty::SessionTurnover => self.session_turnover.clone(),
-                enums::SymbolInfoProperty::SessionInterest => self.session_interest.clone(),
-                enums::SymbolInfoProperty::SessionBuyOrdersVolume => self.session_buy_orders_volume.clone(),
+                enums::SymbolInfoProperty::Volume => self.volume,
+                enums::SymbolInfoProperty::VolumeHigh => self.volumehigh,
+                enums::SymbolInfoProperty::VolumeLow => self.volumelow,
+                enums::SymbolInfoProperty::Bid => self.bid,
+                enums::SymbolInfoProperty::BidHigh => self.bidhigh,
+                enums::SymbolInfoProperty::BidLow => self.bidlow,
+                enums::SymbolInfoProperty::Ask => self.ask,
+                enums::SymbolInfoProperty::AskHigh => self.askhigh,
+                enums::SymbolInfoProperty::AskLow => self.asklow,
+                enums::SymbolInfoProperty::Last => self.last,
+                enums::SymbolInfoProperty::LastHigh => self.lasthigh,
+                enums::SymbolInfoProperty::LastLow => self.lastlow,
+                enums::SymbolInfoProperty::VolumeReal => self.volume_real,
+                enums::SymbolInfoProperty::VolumeHighReal => self.volumehigh_real,
+                enums::SymbolInfoProperty::VolumeLowReal => self.volumelow_real,
+                enums::SymbolInfoProperty::OptionStrike => self.option_strike,
+                enums::SymbolInfoProperty::Point => self.point,
+                enums::SymbolInfoProperty::TradeTickValue => self.trade_tick_value,
+                enums::SymbolInfoProperty::TradeTickValueProfit => self.trade_tick_value_profit,
+                enums::SymbolInfoProperty::TradeTickValueLoss => self.trade_tick_value_loss,
+                enums::SymbolInfoProperty::TradeTickSize => self.trade_tick_size,
+                enums::SymbolInfoProperty::TradeContractSize => self.trade_contract_size,
+                enums::SymbolInfoProperty::TradeAccruedInterest => self.trade_accrued_interest,
+                enums::SymbolInfoProperty::TradeFaceValue => self.trade_face_value,
+                enums::SymbolInfoProperty::TradeLiquidityRate => self.trade_liquidity_rate,
+                enums::SymbolInfoProperty::VolumeMin => self.volume_min,
+                enums::SymbolInfoProperty::VolumeMax => self.volume_max,
+                enums::SymbolInfoProperty::VolumeStep => self.volume_step,
+                enums::SymbolInfoProperty::VolumeLimit => self.volume_limit,
+                enums::SymbolInfoProperty::SwapLong => self.swap_long,
+                enums::SymbolInfoProperty::SwapShort => self.swap_short,
+                enums::SymbolInfoProperty::MarginInitial => self.margin_initial,
+                enums::SymbolInfoProperty::MarginMaintenance => self.margin_maintenance,
+                enums::SymbolInfoProperty::SessionVolume => self.session_volume,
+                enums::SymbolInfoProperty::SessionTurnover => self.session_turnover,
+                enums::SymbolInfoProperty::SessionInterest => self.session_interest,
+                enums::SymbolInfoProperty::SessionBuyOrdersVolume => self.session_buy_orders_volume,
                 enums::SymbolInfoProperty::SessionSellOrdersVolume => {
                     self.session_sell_orders_volume
                 }
-                enums::SymbolInfoProperty::SessionOpen => self.session_open.clone(),
-                enums::SymbolInfoProperty::SessionClose => self.session_close.clone(),
-                enums::SymbolInfoProperty::SessionAw => self.session_aw.clone(),
-                enums::SymbolInfoProperty::SessionPriceSettlement => self.session_price_settlement.clone(),
-                enums::SymbolInfoProperty::SessionPriceLimitMin => self.session_price_limit_min.clone(),
-                enums::SymbolInfoProperty::SessionPriceLimitMax => self.session_price_limit_max.clone(),
-                enums::SymbolInfoProperty::MarginHedged => self.margin_hedged.clone(),
-                enums::SymbolInfoProperty::PriceChange => self.price_change.clone(),
-                enums::SymbolInfoProperty::PriceVolatility => self.price_volatility.clone(),
-                enums::SymbolInfoProperty::PriceTheoretical => self.price_theoretical.clone(),
-                enums::SymbolInfoProperty::PriceGreeksDelta => self.price_greeks_delta.clone(),
-                enums::SymbolInfoProperty::PriceGreeksTheta => self.price_greeks_theta.clone(),
-                enums::SymbolInfoProperty::PriceGreeksGamma => self.price_greeks_gamma.clone(),
-                enums::SymbolInfoProperty::PriceGreeksVega => self.price_greeks_vega.clone(),
-                enums::SymbolInfoProperty::PriceGreeksRho => self.price_greeks_rho.clone(),
-                enums::SymbolInfoProperty::PriceGreeksOmega => self.price_greeks_omega.clone(),
-                enums::SymbolInfoProperty::PriceSensitivity => self.price_sensitivity.clone(),
+                enums::SymbolInfoProperty::SessionOpen => self.session_open,
+                enums::SymbolInfoProperty::SessionClose => self.session_close,
+                enums::SymbolInfoProperty::SessionAw => self.session_aw,
+                enums::SymbolInfoProperty::SessionPriceSettlement => self.session_price_settlement,
+                enums::SymbolInfoProperty::SessionPriceLimitMin => self.session_price_limit_min,
+                enums::SymbolInfoProperty::SessionPriceLimitMax => self.session_price_limit_max,
+                enums::SymbolInfoProperty::MarginHedged => self.margin_hedged,
+                enums::SymbolInfoProperty::PriceChange => self.price_change,
+                enums::SymbolInfoProperty::PriceVolatility => self.price_volatility,
+                enums::SymbolInfoProperty::PriceTheoretical => self.price_theoretical,
+                enums::SymbolInfoProperty::PriceGreeksDelta => self.price_greeks_delta,
+                enums::SymbolInfoProperty::PriceGreeksTheta => self.price_greeks_theta,
+                enums::SymbolInfoProperty::PriceGreeksGamma => self.price_greeks_gamma,
+                enums::SymbolInfoProperty::PriceGreeksVega => self.price_greeks_vega,
+                enums::SymbolInfoProperty::PriceGreeksRho => self.price_greeks_rho,
+                enums::SymbolInfoProperty::PriceGreeksOmega => self.price_greeks_omega,
+                enums::SymbolInfoProperty::PriceSensitivity => self.price_sensitivity,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("float"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("float")),
         };
         Ok(value)
     }
     fn get_info_boolean(&self, info_property: InfoProperties) -> MQLResult<bool> {
         let value = match info_property {
             InfoProperties::SymbolInfoProperty(property) => match property {
-                enums::SymbolInfoProperty::Custom => self.custom.clone(),
-                enums::SymbolInfoProperty::Select => self.select.clone(),
-                enums::SymbolInfoProperty::Visible => self.visible.clone(),
-                enums::SymbolInfoProperty::SpreadFloat => self.spread_float.clone(),
-                enums::SymbolInfoProperty::MarginHedgedUseLeg => self.margin_hedged_use_leg.clone(),
+                enums::SymbolInfoProperty::Custom => self.custom,
+                enums::SymbolInfoProperty::Select => self.select,
+                enums::SymbolInfoProperty::Visible => self.visible,
+                enums::SymbolInfoProperty::SpreadFloat => self.spread_float,
+                enums::SymbolInfoProperty::MarginHedgedUseLeg => self.margin_hedged_use_leg,
                 _ => {
-                    panic!("Property not found");
+                    return Err(property_not_found("boolean"));
                 }
             },
-            _ => panic!("Property not found"),
+            _ => return Err(property_not_found("boolean")),
         };
         Ok(value)
     }
 }
 
-#[derive(Deserialize, FromPyObject, Debug, Iterable)]
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
 #[pyo3(from_item_all)]
 pub struct SymbolTick {
     pub time: i64,
@@ -479,216 +500,283 @@ pub struct SymbolTick {
     pub volume_real: f64,
 }
 
-#[derive(Deserialize, FromPyObject, Debug)]
-#[pyo3(from_item_all)]
-pub struct SymbolRates {
-    pub time: i64, // change this into date time
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub tick_volume: isize,
-    pub spread: f64,
-    pub real_volume: isize,
-}
+pub use timestamped::{Deals, Order, Position, SymbolRates, TradeRequest};
 
-#[derive(Deserialize, FromPyObject, Debug, Iterable)]
-#[pyo3(from_item_all)]
-pub struct Order {
-    ticket: isize,
-    time_setup: i64, // convert this into date time
-    #[pyo3(item("type"))]
-    r#type: OrderType,
-    state: OrderState,
-    time_expiration: i64, // convert this into date time
-    time_done: i64,       // convert this into date time
-    time_setup_msc: isize,
-    time_done_msc: isize,
-    type_filling: OrderTypeFilling,
-    type_time: OrderTypeTime,
-    magic: isize,
-    reason: OrderReason,
-    position_id: isize,
-    position_by_id: isize,
-    volume_initial: f64,
-    volume_current: f64,
-    price_open: f64,
-    sl: f64,
-    tp: f64,
-    price_current: f64,
-    price_stoplimit: f64,
-    symbol: String,
-    comment: String,
-    external_id: String,
-}
+/// The records with `from_py_with` timestamps. pyo3 0.22 expands every `from_py_with`
+/// into an unused deprecation-check function next to the struct, which an `#[allow]`
+/// on the struct itself does not reach.
+#[allow(dead_code)]
+mod timestamped {
+    use super::*;
 
-#[derive(Deserialize, FromPyObject, Debug, Clone)]
-#[pyo3(from_item_all)]
-pub struct Position {
-    pub ticket: isize,
-    pub time: i64, // convert this into date time
-    pub time_msc: isize,
-    pub time_update: i64, // convert this into date time
-    pub time_update_msc: isize,
-    #[pyo3(item("type"))]
-    pub r#type: PositionType,
-    pub magic: isize,
-    pub identifier: isize,
-    pub reason: PositionReason,
-    pub volume: f64,
-    pub price_open: f64,
-    pub sl: f64,
-    pub tp: f64,
-    pub price_current: f64,
-    pub swap: f64,
-    pub profit: f64,
-    pub symbol: String,
-    pub comment: String,
-    pub external_id: String,
-}
+    #[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
+    #[pyo3(from_item_all)]
+    pub struct SymbolRates {
+        #[pyo3(from_py_with = "datetime_from_seconds")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time: DateTime<Utc>,
+        pub open: f64,
+        pub high: f64,
+        pub low: f64,
+        pub close: f64,
+        pub tick_volume: isize,
+        pub spread: f64,
+        pub real_volume: isize,
+    }
 
-#[derive(Deserialize, FromPyObject, Debug, Clone)]
-#[pyo3(from_item_all)]
-pub struct Deals {
-    pub ticket: isize,
-    pub order: isize,
-    pub time: i64, // convert this into date time
-    pub time_msc: i64,
-    #[pyo3(item("type"))]
-    pub r#type: DealType,
-    pub entry: DealEntry,
-    pub magic: isize,
-    pub reason: DealReason,
-    pub position_id: isize,
-    pub volume: f64,
-    pub price: f64,
-    pub commission: f64,
-    pub swap: f64,
-    pub profit: f64,
-    pub fee: f64,
-    pub symbol: String,
-    pub comment: String,
-    pub external_id: String,
+    #[derive(Deserialize, FromPyObject, Debug, Clone, Default, Iterable)]
+    #[pyo3(from_item_all)]
+    pub struct Order {
+        pub ticket: isize,
+        #[pyo3(item("time_setup_msc"), from_py_with = "datetime_from_millis")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time_setup: DateTime<Utc>,
+        #[pyo3(item("type"))]
+        pub r#type: OrderType,
+        pub state: OrderState,
+        #[pyo3(from_py_with = "datetime_from_seconds")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time_expiration: DateTime<Utc>,
+        #[pyo3(item("time_done_msc"), from_py_with = "datetime_from_millis")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time_done: DateTime<Utc>,
+        pub time_setup_msc: isize,
+        pub time_done_msc: isize,
+        pub type_filling: OrderTypeFilling,
+        pub type_time: OrderTypeTime,
+        pub magic: isize,
+        pub reason: OrderReason,
+        pub position_id: isize,
+        pub position_by_id: isize,
+        pub volume_initial: f64,
+        pub volume_current: f64,
+        pub price_open: f64,
+        pub sl: f64,
+        pub tp: f64,
+        pub price_current: f64,
+        pub price_stoplimit: f64,
+        pub symbol: String,
+        pub comment: String,
+        pub external_id: String,
+    }
+
+    #[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
+    #[pyo3(from_item_all)]
+    pub struct Position {
+        pub ticket: isize,
+        #[pyo3(item("time_msc"), from_py_with = "datetime_from_millis")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time: DateTime<Utc>,
+        pub time_msc: isize,
+        #[pyo3(item("time_update_msc"), from_py_with = "datetime_from_millis")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time_update: DateTime<Utc>,
+        pub time_update_msc: isize,
+        #[pyo3(item("type"))]
+        pub r#type: PositionType,
+        pub magic: isize,
+        pub identifier: isize,
+        pub reason: PositionReason,
+        pub volume: f64,
+        pub price_open: f64,
+        pub sl: f64,
+        pub tp: f64,
+        pub price_current: f64,
+        pub swap: f64,
+        pub profit: f64,
+        pub symbol: String,
+        pub comment: String,
+        pub external_id: String,
+    }
+
+    #[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
+    #[pyo3(from_item_all)]
+    pub struct Deals {
+        pub ticket: isize,
+        pub order: isize,
+        #[pyo3(item("time_msc"), from_py_with = "datetime_from_millis")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub time: DateTime<Utc>,
+        pub time_msc: i64,
+        #[pyo3(item("type"))]
+        pub r#type: DealType,
+        pub entry: DealEntry,
+        pub magic: isize,
+        pub reason: DealReason,
+        pub position_id: isize,
+        pub volume: f64,
+        pub price: f64,
+        pub commission: f64,
+        pub swap: f64,
+        pub profit: f64,
+        pub fee: f64,
+        pub symbol: String,
+        pub comment: String,
+        pub external_id: String,
+    }
+
+    #[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
+    #[pyo3(from_item_all)]
+    pub struct TradeRequest {
+        pub action: TradeActionRequest,
+        pub magic: usize,
+        pub order: usize,
+        pub symbol: String,
+        pub volume: f64,
+        pub price: f64,
+        pub stoplimit: f64,
+        pub sl: f64,
+        pub tp: f64,
+        pub deviation: usize,
+        #[pyo3(item("type"))]
+        pub r#type: OrderType,
+        pub type_filling: OrderTypeFilling,
+        pub type_time: OrderTypeTime,
+        #[pyo3(from_py_with = "datetime_from_seconds")]
+        #[serde(with = "chrono::serde::ts_seconds")]
+        pub expiration: DateTime<Utc>,
+        pub comment: String,
+        pub position: usize,
+        pub position_by: usize,
+    }
 }
 
-#[derive(Deserialize, FromPyObject, Debug, Clone)]
-#[pyo3(from_item_all)]
-pub struct TradeRequest {
-    pub action: TradeActionRequest,
-    pub magic: usize,
-    pub order: usize,
-    pub symbol: String,
-    pub volume: f64,
-    pub price: f64,
-    pub stoplimit: f64,
-    pub sl: f64,
-    pub tp: f64,
-    pub deviation: usize,
-    #[pyo3(item("type"))]
-    pub r#type: OrderType,
-    pub type_filling: OrderTypeFilling,
-    pub type_time: OrderTypeTime,
-    pub expiration: i64, // convert this into date time
-    pub comment: String,
-    pub position: usize,
-    pub position_by: usize,
+impl From<TradeRequestBuilder> for TradeRequest {
+    /// Fills every field the builder left unset with the value MT5 assumes for it.
+    fn from(builder: TradeRequestBuilder) -> Self {
+        TradeRequest {
+            action: builder.action.unwrap_or_default(),
+            magic: builder.magic.unwrap_or_default() as usize,
+            order: builder.order.unwrap_or_default(),
+            symbol: builder.symbol.unwrap_or_default(),
+            volume: builder.volume.unwrap_or_default(),
+            price: builder.price.unwrap_or_default(),
+            stoplimit: builder.stoplimit.unwrap_or_default(),
+            sl: builder.sl.unwrap_or_default(),
+            tp: builder.tp.unwrap_or_default(),
+            deviation: builder.deviation.unwrap_or_default(),
+            r#type: builder.r#type.unwrap_or_default(),
+            type_filling: builder.type_filling.unwrap_or_default(),
+            type_time: builder.type_time.unwrap_or_default(),
+            expiration: builder.expiration.unwrap_or_default(),
+            comment: builder.comment.unwrap_or_default(),
+            position: builder.position.unwrap_or_default(),
+            position_by: builder.position_by.unwrap_or_default(),
+        }
+    }
 }
 
-#[derive(Default, Clone)]
+/// Serializes to the request dict `order_check` and `order_send` take, leaving out unset fields.
+#[derive(Serialize, Default, Clone, Debug)]
 pub struct TradeRequestBuilder {
-    action: Option<TradeActionRequest>,
-    magic: Option<i64>,
-    order: Option<usize>,
-    symbol: Option<String>,
-    volume: Option<f64>,
-    price: Option<f64>,
-    stoplimit: Option<f64>,
-    sl: Option<f64>,
-    tp: Option<f64>,
-    deviation: Option<usize>,
-    r#type: Option<OrderType>,
-    type_filling: Option<OrderTypeFilling>,
-    type_time: Option<OrderTypeTime>,
-    expiration: Option<i64>, // convert this into datetime
-    comment: Option<String>,
-    position: Option<usize>,
-    position_by: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) action: Option<TradeActionRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) magic: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) order: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) volume: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) stoplimit: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sl: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tp: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deviation: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) r#type: Option<OrderType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) type_filling: Option<OrderTypeFilling>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) type_time: Option<OrderTypeTime>,
+    #[serde(
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) expiration: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) position: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) position_by: Option<usize>,
 }
 
 impl IntoPy<PyObject> for TradeRequestBuilder {
     fn into_py(self, py: Python<'_>) -> PyObject {
         let dict = PyDict::new_bound(py);
-        if self.action.is_some() {
-            dict.set_item("action", self.action.unwrap() as i64)
-                .unwrap();
+        if let Some(action) = self.action {
+            dict.set_item("action", action.value()).unwrap();
         }
 
-        if self.magic.is_some() {
-            dict.set_item("magic", self.magic.unwrap()).unwrap();
+        if let Some(magic) = self.magic {
+            dict.set_item("magic", magic).unwrap();
         }
 
-        if self.order.is_some() {
-            dict.set_item("order", self.order.unwrap() as i64).unwrap();
+        if let Some(order) = self.order {
+            dict.set_item("order", order as i64).unwrap();
         }
 
-        if self.symbol.is_some() {
-            dict.set_item("symbol", self.symbol.unwrap()).unwrap();
+        if let Some(symbol) = self.symbol {
+            dict.set_item("symbol", symbol).unwrap();
         }
 
-        if self.volume.is_some() {
-            dict.set_item("volume", self.volume.unwrap()).unwrap();
+        if let Some(volume) = self.volume {
+            dict.set_item("volume", volume).unwrap();
         }
 
-        if self.price.is_some() {
-            dict.set_item("price", self.price.unwrap()).unwrap();
+        if let Some(price) = self.price {
+            dict.set_item("price", price).unwrap();
         }
 
-        if self.stoplimit.is_some() {
-            dict.set_item("stoplimit", self.stoplimit.unwrap()).unwrap();
+        if let Some(stoplimit) = self.stoplimit {
+            dict.set_item("stoplimit", stoplimit).unwrap();
         }
 
-        if self.sl.is_some() {
-            dict.set_item("sl", self.sl.unwrap()).unwrap();
+        if let Some(sl) = self.sl {
+            dict.set_item("sl", sl).unwrap();
         }
 
-        if self.tp.is_some() {
-            dict.set_item("tp", self.tp.unwrap()).unwrap();
+        if let Some(tp) = self.tp {
+            dict.set_item("tp", tp).unwrap();
         }
 
-        if self.deviation.is_some() {
-            dict.set_item("deviation", self.deviation.unwrap()).unwrap();
+        if let Some(deviation) = self.deviation {
+            dict.set_item("deviation", deviation).unwrap();
         }
 
-        if self.r#type.is_some() {
-            dict.set_item("type", self.r#type.unwrap() as i64).unwrap();
+        if let Some(order_type) = self.r#type {
+            dict.set_item("type", order_type.value()).unwrap();
         }
 
-        if self.type_filling.is_some() {
-            dict.set_item("type_filling", self.type_filling.unwrap() as i64)
-                .unwrap();
+        if let Some(type_filling) = self.type_filling {
+            dict.set_item("type_filling", type_filling.value()).unwrap();
         }
 
-        if self.type_time.is_some() {
-            dict.set_item("type_time", self.type_time.unwrap() as i64)
-                .unwrap();
+        if let Some(type_time) = self.type_time {
+            dict.set_item("type_time", type_time.value()).unwrap();
         }
 
-        if self.expiration.is_some() {
-            dict.set_item("expiration", self.expiration.unwrap())
-                .unwrap();
+        if let Some(expiration) = self.expiration {
+            dict.set_item("expiration", expiration.timestamp()).unwrap();
         }
 
-        if self.comment.is_some() {
-            dict.set_item("comment", self.comment.unwrap()).unwrap();
+        if let Some(comment) = self.comment {
+            dict.set_item("comment", comment).unwrap();
         }
 
-        if self.position.is_some() {
-            dict.set_item("position", self.position.unwrap()).unwrap();
+        if let Some(position) = self.position {
+            dict.set_item("position", position).unwrap();
         }
 
-        if self.position_by.is_some() {
-            dict.set_item("position_by", self.position_by.unwrap())
-                .unwrap();
+        if let Some(position_by) = self.position_by {
+            dict.set_item("position_by", position_by).unwrap();
         }
 
         dict.into_py(py)
@@ -765,8 +853,9 @@ impl TradeRequestBuilder {
         self
     }
 
-    pub fn expiration(mut self, expiration: i64) -> Self {
-        self.expiration = Some(expiration);
+    /// Expiration of a pending order. The terminal only keeps whole seconds.
+    pub fn expiration<Tz: TimeZone>(mut self, expiration: DateTime<Tz>) -> Self {
+        self.expiration = Some(expiration.with_timezone(&Utc));
         self
     }
 
@@ -786,7 +875,113 @@ impl TradeRequestBuilder {
     }
 }
 
-#[derive(Deserialize, FromPyObject, Debug)]
+/// Narrows `orders_get_filtered` and `positions_get_filtered` to a subset of open trades.
+///
+/// `ticket`, `symbol` and `group` are the filters understood by the terminal, the
+/// remaining ones are applied on the Rust side. Every filter that is set must match.
+///
+/// ```rust
+/// use fishing_line::prelude::*;
+///
+/// let filter = TradeFilter::new()
+///     .group("*USD*,!BTC*")
+///     .magic(20240722)
+///     .comment("grid");
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct TradeFilter {
+    pub(crate) ticket: Option<usize>,
+    pub(crate) symbol: Option<String>,
+    pub(crate) group: Option<String>,
+    pub(crate) magic: Option<i64>,
+    pub(crate) order_type: Option<OrderType>,
+    pub(crate) position_type: Option<PositionType>,
+    pub(crate) comment: Option<String>,
+}
+
+impl TradeFilter {
+    pub fn new() -> Self {
+        TradeFilter::default()
+    }
+
+    pub fn ticket(mut self, ticket: usize) -> Self {
+        self.ticket = Some(ticket);
+        self
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Group mask such as `"*USD*,!EUR*"`, where `*` matches anything and `!` excludes.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    pub fn magic(mut self, magic: i64) -> Self {
+        self.magic = Some(magic);
+        self
+    }
+
+    /// Only keeps orders of this type. Positions are not affected.
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = Some(order_type);
+        self
+    }
+
+    /// Only keeps positions of this type. Orders are not affected.
+    pub fn position_type(mut self, position_type: PositionType) -> Self {
+        self.position_type = Some(position_type);
+        self
+    }
+
+    /// Only keeps trades whose comment starts with `comment`, since the terminal
+    /// truncates long comments.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    fn matches(&self, ticket: isize, symbol: &str, magic: isize, comment: &str) -> bool {
+        self.ticket
+            .is_none_or(|expected| expected as isize == ticket)
+            && self
+                .symbol
+                .as_deref()
+                .is_none_or(|expected| expected == symbol)
+            && self
+                .group
+                .as_deref()
+                .is_none_or(|group| group_matches(group, symbol))
+            && self.magic.is_none_or(|expected| expected as isize == magic)
+            && self
+                .comment
+                .as_deref()
+                .is_none_or(|prefix| comment.starts_with(prefix))
+    }
+
+    pub fn matches_order(&self, order: &Order) -> bool {
+        self.matches(order.ticket, &order.symbol, order.magic, &order.comment)
+            && self
+                .order_type
+                .is_none_or(|order_type| order_type == order.r#type)
+    }
+
+    pub fn matches_position(&self, position: &Position) -> bool {
+        self.matches(
+            position.ticket,
+            &position.symbol,
+            position.magic,
+            &position.comment,
+        ) && self
+            .position_type
+            .is_none_or(|position_type| position_type == position.r#type)
+    }
+}
+
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
 #[pyo3(from_item_all)]
 pub struct CheckResult {
     pub retcode: ReturnCode,
@@ -800,7 +995,7 @@ pub struct CheckResult {
     pub request: TradeRequest,
 }
 
-#[derive(Deserialize, FromPyObject, Debug)]
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
 #[pyo3(from_item_all)]
 pub struct TradeResult {
     pub retcode: ReturnCode,
@@ -815,3 +1010,302 @@ pub struct TradeResult {
     pub retcode_external: i64,
     pub request: TradeRequest,
 }
+
+#[derive(Deserialize, FromPyObject, Debug, Clone, Default)]
+#[pyo3(from_item_all)]
+pub struct BookEntry {
+    #[pyo3(item("type"))]
+    pub r#type: BookType,
+    pub price: f64,
+    pub volume: i64,
+    pub volume_real: f64,
+}
+
+/// Snapshot of a symbol's market depth, as returned by `market_book_get`.
+///
+/// The helpers only look at priced `BUY` and `SELL` levels, `BuyMarket` and
+/// `SellMarket` entries are kept in `entries` but carry no price level.
+#[derive(Debug, Clone, Default)]
+pub struct MarketBook {
+    pub entries: Vec<BookEntry>,
+}
+
+impl MarketBook {
+    fn side(&self, book_type: BookType) -> Vec<&BookEntry> {
+        let mut levels: Vec<&BookEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.r#type == book_type)
+            .collect();
+        levels.sort_by(|a, b| a.price.total_cmp(&b.price));
+        levels
+    }
+
+    /// Bid levels, best (highest) price first.
+    pub fn bids(&self) -> Vec<&BookEntry> {
+        let mut bids = self.side(BookType::BUY);
+        bids.reverse();
+        bids
+    }
+
+    /// Ask levels, best (lowest) price first.
+    pub fn asks(&self) -> Vec<&BookEntry> {
+        self.side(BookType::SELL)
+    }
+
+    pub fn best_bid(&self) -> Option<&BookEntry> {
+        self.bids().first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<&BookEntry> {
+        self.asks().first().copied()
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Cumulative bid volume over the best `levels` price levels.
+    pub fn bid_depth(&self, levels: usize) -> f64 {
+        self.bids()
+            .iter()
+            .take(levels)
+            .map(|entry| entry.volume_real)
+            .sum()
+    }
+
+    /// Cumulative ask volume over the best `levels` price levels.
+    pub fn ask_depth(&self, levels: usize) -> f64 {
+        self.asks()
+            .iter()
+            .take(levels)
+            .map(|entry| entry.volume_real)
+            .sum()
+    }
+
+    /// Order book imbalance over the best `levels`, from -1 (only asks) to 1 (only bids).
+    ///
+    /// Returns `None` when both sides are empty.
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        let bids = self.bid_depth(levels);
+        let asks = self.ask_depth(levels);
+        let total = bids + asks;
+        (total > 0.0).then(|| (bids - asks) / total)
+    }
+}
+
+/// Selects historical orders and deals for `history_orders_query` and `history_deals_query`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HistoryQuery {
+    /// Records between two dates, optionally restricted to symbols matching an MT5
+    /// group mask such as `"*USD*,!EUR*"`.
+    Range {
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        group: Option<String>,
+    },
+    /// The order with this ticket, or the deals executed for it.
+    Ticket(usize),
+    /// Every order or deal of the position with this identifier.
+    Position(usize),
+}
+
+impl HistoryQuery {
+    pub fn range<Tz: TimeZone>(date_from: DateTime<Tz>, date_to: DateTime<Tz>) -> Self {
+        HistoryQuery::Range {
+            date_from: date_from.to_utc(),
+            date_to: date_to.to_utc(),
+            group: None,
+        }
+    }
+
+    pub fn range_with_group<Tz: TimeZone>(
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+        group: impl Into<String>,
+    ) -> Self {
+        HistoryQuery::Range {
+            date_from: date_from.to_utc(),
+            date_to: date_to.to_utc(),
+            group: Some(group.into()),
+        }
+    }
+
+    fn matches(&self, time: DateTime<Utc>, symbol: &str, ticket: isize, position: isize) -> bool {
+        match self {
+            HistoryQuery::Range {
+                date_from,
+                date_to,
+                group,
+            } => {
+                time >= *date_from
+                    && time <= *date_to
+                    && group
+                        .as_deref()
+                        .is_none_or(|group| group_matches(group, symbol))
+            }
+            HistoryQuery::Ticket(expected) => *expected as isize == ticket,
+            HistoryQuery::Position(expected) => *expected as isize == position,
+        }
+    }
+
+    pub fn matches_order(&self, order: &Order) -> bool {
+        self.matches(
+            order.time_setup,
+            &order.symbol,
+            order.ticket,
+            order.position_id,
+        )
+    }
+
+    /// Deals are matched on the ticket of the order that produced them.
+    pub fn matches_deal(&self, deal: &Deals) -> bool {
+        self.matches(deal.time, &deal.symbol, deal.order, deal.position_id)
+    }
+}
+
+/// Orders and deals of a single position, from opening to closing.
+#[derive(Clone, Debug, Default)]
+pub struct PositionHistory {
+    pub orders: Vec<Order>,
+    pub deals: Vec<Deals>,
+}
+
+impl PositionHistory {
+    /// Realized profit of the position, including commissions, swaps and fees.
+    pub fn net_profit(&self) -> f64 {
+        self.deals
+            .iter()
+            .map(|deal| deal.profit + deal.commission + deal.swap + deal.fee)
+            .sum()
+    }
+
+    /// Whether every opened volume has been closed again.
+    pub fn is_closed(&self) -> bool {
+        let volume = |entries: &[DealEntry]| -> f64 {
+            self.deals
+                .iter()
+                .filter(|deal| entries.contains(&deal.entry))
+                .map(|deal| deal.volume)
+                .sum()
+        };
+        let opened = volume(&[DealEntry::IN]);
+        opened > 0.0 && volume(&[DealEntry::OUT, DealEntry::OutBy]) >= opened
+    }
+}
+
+/// Checks a symbol name against an MT5 group filter such as `"*USD*,!EUR*"`.
+///
+/// Conditions are comma separated, `*` matches any run of characters and a leading
+/// `!` excludes matching symbols. Conditions are applied in order, the same way the
+/// terminal evaluates the `group` argument of `symbols_get`, `orders_get` and
+/// `positions_get`.
+pub(crate) fn group_matches(group: &str, symbol: &str) -> bool {
+    fn wildcard_matches(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some((b'*', rest)) => {
+                (0..=text.len()).any(|skip| wildcard_matches(rest, &text[skip..]))
+            }
+            Some((head, rest)) => match text.split_first() {
+                Some((first, remaining)) if first.eq_ignore_ascii_case(head) => {
+                    wildcard_matches(rest, remaining)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    group
+        .split(',')
+        .map(str::trim)
+        .filter(|condition| !condition.is_empty())
+        .fold(false, |included, condition| {
+            match condition.strip_prefix('!') {
+                Some(excluded) if wildcard_matches(excluded.as_bytes(), symbol.as_bytes()) => false,
+                Some(_) => included,
+                None => included || wildcard_matches(condition.as_bytes(), symbol.as_bytes()),
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Timelike};
+    use pyo3::{prelude::*, types::PyDict};
+
+    use crate::prelude::*;
+
+    fn level(r#type: BookType, price: f64, volume_real: f64) -> BookEntry {
+        BookEntry {
+            r#type,
+            price,
+            volume: volume_real as i64,
+            volume_real,
+        }
+    }
+
+    #[test]
+    fn test_market_book_helpers() {
+        let book = MarketBook {
+            entries: vec![
+                level(BookType::SELL, 1.1004, 5.0),
+                level(BookType::SELL, 1.1002, 1.0),
+                level(BookType::BUY, 1.1000, 2.0),
+                level(BookType::BUY, 1.0998, 4.0),
+                level(BookType::BuyMarket, 0.0, 10.0),
+            ],
+        };
+        assert_eq!(book.best_bid().unwrap().price, 1.1000);
+        assert_eq!(book.best_ask().unwrap().price, 1.1002);
+        assert!(
+            (book.spread().unwrap() - 0.0002).abs() < 1e-9,
+            "Wrong spread"
+        );
+        assert_eq!(book.bid_depth(1), 2.0);
+        assert_eq!(book.bid_depth(10), 6.0);
+        assert_eq!(book.ask_depth(2), 6.0);
+        assert_eq!(book.imbalance(1), Some((2.0 - 1.0) / 3.0));
+        assert_eq!(MarketBook::default().imbalance(5), None);
+    }
+
+    #[test]
+    fn test_timestamps_keep_milliseconds() {
+        let position: Position = Python::with_gil(|py| {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in [
+                ("ticket", 1i64),
+                ("time", 1720413000),
+                ("time_msc", 1720413000123),
+                ("time_update", 1720413060),
+                ("time_update_msc", 1720413060456),
+                ("type", 0),
+                ("magic", 0),
+                ("identifier", 1),
+                ("reason", 3),
+            ] {
+                dict.set_item(key, value).unwrap();
+            }
+            for key in [
+                "volume",
+                "price_open",
+                "sl",
+                "tp",
+                "price_current",
+                "swap",
+                "profit",
+            ] {
+                dict.set_item(key, 0.0).unwrap();
+            }
+            for key in ["symbol", "comment", "external_id"] {
+                dict.set_item(key, "").unwrap();
+            }
+            dict.extract().unwrap()
+        });
+        assert_eq!(
+            position.time,
+            DateTime::from_timestamp_millis(1720413000123).unwrap()
+        );
+        assert_eq!(position.time_update.nanosecond(), 456_000_000);
+    }
+}
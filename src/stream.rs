@@ -0,0 +1,1095 @@
+//! Polling streams over a [`Terminal`].
+//!
+//! The MetaTrader5 Python API has no push notifications, so every stream here is
+//! fed by a worker thread polling the terminal every [`StreamOptions::interval`].
+//! Items go through a bounded channel: a consumer that falls behind pauses its
+//! poller instead of growing a queue, and the poller carries on from where it
+//! stopped, so nothing is skipped. Streams implement [`futures_util::Stream`] and
+//! work with any executor. Dropping a [`Subscription`] stops its poller.
+
+use std::collections::{BTreeMap, HashSet};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use futures_channel::mpsc;
+use futures_executor::block_on;
+use futures_util::future::poll_fn;
+use futures_util::{Stream, StreamExt};
+
+use crate::prelude::*;
+
+/// Number of ticks requested per `copy_ticks_from` call.
+const TICK_BATCH: i32 = 10_000;
+/// Bars requested by the first `copy_rates_from_pos` call of a poll, doubled until
+/// the last streamed bar is part of the answer.
+const BAR_BATCH: i32 = 4;
+/// Most bars requested when catching up after a long disconnection.
+const MAX_BARS: i32 = 100_000;
+
+/// Polling interval and buffer size of a [`Subscription`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamOptions {
+    interval: Duration,
+    buffer: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions {
+            interval: Duration::from_millis(100),
+            buffer: 1024,
+        }
+    }
+}
+
+impl StreamOptions {
+    /// Polls every 100 ms and buffers up to 1024 items.
+    pub fn new() -> Self {
+        StreamOptions::default()
+    }
+
+    /// Time between two polls once the poller has caught up.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Items buffered before the poller waits for the consumer.
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+}
+
+/// Stream of items produced by a polling worker thread.
+///
+/// A failed poll is yielded as an `Err` item and retried at the next interval, so
+/// the stream only ends once it is unsubscribed.
+pub struct Subscription<T> {
+    items: mpsc::Receiver<MQLResult<T>>,
+    stopped: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// Runs `poll` on a new thread named `fishing-line-<name>` and streams its items.
+    pub(crate) fn spawn<P>(name: &str, options: StreamOptions, mut poll: P) -> MQLResult<Self>
+    where
+        P: FnMut() -> Vec<MQLResult<T>> + Send + 'static,
+    {
+        let (mut sender, items) = mpsc::channel(options.buffer);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+        let worker = thread::Builder::new()
+            .name(format!("fishing-line-{}", name))
+            .spawn(move || {
+                while !stop.load(Ordering::Acquire) {
+                    for item in poll() {
+                        let ready = block_on(poll_fn(|cx| sender.poll_ready(cx)));
+                        if ready.is_err() || sender.start_send(item).is_err() {
+                            return;
+                        }
+                    }
+                    thread::park_timeout(options.interval);
+                }
+            })
+            .map_err(|error| {
+                FishingLineError::Environment(format!(
+                    "Unable to spawn {} poller thread: {}",
+                    name, error
+                ))
+            })?;
+        Ok(Subscription {
+            items,
+            stopped,
+            worker: Some(worker),
+        })
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Stops the poller and waits for its current poll to return.
+    pub fn unsubscribe(mut self) {
+        self.stop();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        self.items.close();
+        if let Some(worker) = &self.worker {
+            worker.thread().unpark();
+        }
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = MQLResult<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.items.poll_next_unpin(cx)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Tick position of one symbol: the last streamed `time_msc` and how many ticks
+/// carrying that timestamp were already streamed.
+struct TickCursor {
+    symbol: String,
+    time_msc: i64,
+    streamed: usize,
+}
+
+impl TickCursor {
+    /// Starts after the symbol's current tick.
+    fn new<T: Terminal + ?Sized>(terminal: &T, symbol: &str) -> MQLResult<Self> {
+        let last = terminal.symbol_info_tick(symbol)?;
+        let mut cursor = TickCursor {
+            symbol: symbol.to_string(),
+            time_msc: last.time_msc,
+            streamed: 0,
+        };
+        cursor.streamed = cursor
+            .copy(terminal)?
+            .iter()
+            .take_while(|tick| tick.time_msc == last.time_msc)
+            .count()
+            .max(1);
+        Ok(cursor)
+    }
+
+    fn copy<T: Terminal + ?Sized>(&self, terminal: &T) -> MQLResult<Vec<SymbolTick>> {
+        let from = DateTime::from_timestamp_millis(self.time_msc).unwrap_or_default();
+        terminal.copy_ticks_from(&self.symbol, from, TICK_BATCH, CopyTicksFlags::ALL)
+    }
+
+    /// Returns the ticks received since the last call, requesting further batches
+    /// while they come back full.
+    fn poll<T: Terminal + ?Sized>(&mut self, terminal: &T) -> MQLResult<Vec<SymbolTick>> {
+        let mut ticks = Vec::new();
+        loop {
+            let batch = match self.copy(terminal) {
+                Ok(batch) => batch,
+                // The ticks already taken moved the cursor, hand them out first.
+                Err(_) if !ticks.is_empty() => return Ok(ticks),
+                Err(error) => return Err(error),
+            };
+            let full = batch.len() >= TICK_BATCH as usize;
+            let before = ticks.len();
+            let mut same_time = 0;
+            for tick in batch {
+                if tick.time_msc < self.time_msc {
+                    continue;
+                }
+                if tick.time_msc == self.time_msc {
+                    same_time += 1;
+                    if same_time <= self.streamed {
+                        continue;
+                    }
+                    self.streamed += 1;
+                } else {
+                    self.time_msc = tick.time_msc;
+                    self.streamed = 1;
+                    same_time = 1;
+                }
+                ticks.push(tick);
+            }
+            if !full || ticks.len() == before {
+                return Ok(ticks);
+            }
+        }
+    }
+}
+
+/// A bar of `symbol` on `timeframe` that stopped changing because the next one opened.
+#[derive(Debug, Clone)]
+pub struct ClosedBar {
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub bar: SymbolRates,
+}
+
+/// Bar position of one `(symbol, timeframe)` pair: the open time of the bar that
+/// was still forming at the last poll.
+struct BarCursor {
+    symbol: String,
+    timeframe: Timeframe,
+    open: DateTime<Utc>,
+}
+
+impl BarCursor {
+    /// Starts at the currently forming bar, which is streamed once it closes.
+    fn new<T: Terminal + ?Sized>(
+        terminal: &T,
+        symbol: &str,
+        timeframe: Timeframe,
+    ) -> MQLResult<Self> {
+        let forming = terminal.copy_rates_from_pos(symbol, timeframe, 0, 1)?;
+        let open = forming.last().map(|bar| bar.time).ok_or_else(|| {
+            FishingLineError::runtime(
+                RuntimeError::NotFound,
+                format!("No {:?} bars for `{}`", timeframe, symbol),
+            )
+        })?;
+        Ok(BarCursor {
+            symbol: symbol.to_string(),
+            timeframe,
+            open,
+        })
+    }
+
+    /// Returns the bars closed since the last call, oldest first.
+    ///
+    /// Bars are told apart by their open time rather than by counting timeframe
+    /// periods, so weekends, holidays and periods without any tick simply yield
+    /// fewer bars.
+    fn poll<T: Terminal + ?Sized>(&mut self, terminal: &T) -> MQLResult<Vec<ClosedBar>> {
+        let mut count = BAR_BATCH;
+        let rates = loop {
+            let rates = terminal.copy_rates_from_pos(&self.symbol, self.timeframe, 0, count)?;
+            let reached = rates.first().is_none_or(|bar| bar.time <= self.open);
+            if reached || count >= MAX_BARS {
+                break rates;
+            }
+            if (rates.len() as i32) < count {
+                // The terminal is still loading history, typically right after a
+                // reconnection. Wait for it rather than skip the missing bars.
+                return Ok(Vec::new());
+            }
+            count = (count * 2).min(MAX_BARS);
+        };
+        let forming = match rates.last() {
+            Some(forming) if forming.time > self.open => forming.time,
+            _ => return Ok(Vec::new()),
+        };
+        let closed = rates[..rates.len() - 1]
+            .iter()
+            .filter(|bar| bar.time >= self.open)
+            .map(|bar| ClosedBar {
+                symbol: self.symbol.clone(),
+                timeframe: self.timeframe,
+                bar: bar.clone(),
+            })
+            .collect();
+        self.open = forming;
+        Ok(closed)
+    }
+}
+
+/// A change in the account's orders, positions or deals.
+#[derive(Debug, Clone)]
+pub enum TradeEvent {
+    /// A pending order appeared among the open orders.
+    OrderPlaced(Order),
+    /// A pending order left the open orders filled, carrying its historical record.
+    OrderFilled(Order),
+    /// A pending order left the open orders canceled, rejected or expired, carrying
+    /// its historical record.
+    OrderCancelled(Order),
+    PositionOpened(Position),
+    /// The volume, open price, stop loss or take profit of a position changed.
+    PositionModified {
+        previous: Position,
+        current: Position,
+    },
+    /// A position left the open positions, carrying its last snapshot.
+    PositionClosed(Position),
+    DealAdded(Deals),
+}
+
+/// Detects [`TradeEvent`]s by diffing snapshots of `orders_get`, `positions_get`
+/// and `history_deals_get`.
+///
+/// MQL5's `OnTradeTransaction` has no Python counterpart. The monitor stands in for
+/// it: every [`TradeEventMonitor::poll`] compares the terminal with the previous
+/// snapshot. Changes that cancel out between two polls, such as a position opened
+/// and closed in between, only show up as their deals.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+/// let mut monitor = TradeEventMonitor::new(terminal)?;
+/// loop {
+///     for event in monitor.poll(terminal)? {
+///         if let TradeEvent::PositionClosed(position) = event {
+///             println!("closed {} with {}", position.ticket, position.profit);
+///         }
+///     }
+///     std::thread::sleep(std::time::Duration::from_millis(250));
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TradeEventMonitor {
+    orders: BTreeMap<isize, Order>,
+    positions: BTreeMap<isize, Position>,
+    /// Deals are requested from this time on, the time of the latest deal seen.
+    deals_from: DateTime<Utc>,
+    /// Tickets of the deals seen at or after `deals_from`.
+    deals: HashSet<isize>,
+}
+
+impl TradeEventMonitor {
+    /// Takes the first snapshot. Orders, positions and deals of the last day already
+    /// present in the terminal produce no event.
+    pub fn new<T: Terminal + ?Sized>(terminal: &T) -> MQLResult<Self> {
+        let mut monitor = TradeEventMonitor {
+            orders: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            deals_from: Utc::now() - TimeDelta::days(1),
+            deals: HashSet::new(),
+        };
+        monitor.poll(terminal)?;
+        Ok(monitor)
+    }
+
+    /// Takes a new snapshot and returns the events since the previous one.
+    ///
+    /// On error the previous snapshot is kept, so the events are reported by the
+    /// next successful poll instead.
+    pub fn poll<T: Terminal + ?Sized>(&mut self, terminal: &T) -> MQLResult<Vec<TradeEvent>> {
+        let mut orders: BTreeMap<isize, Order> = terminal
+            .orders_get()?
+            .into_iter()
+            .map(|order| (order.ticket, order))
+            .collect();
+        let positions: BTreeMap<isize, Position> = terminal
+            .positions_get()?
+            .into_iter()
+            .map(|position| (position.ticket, position))
+            .collect();
+        // Server time offsets and clock drift must not hide the latest deals.
+        let mut deals = terminal.history_deals_query(&HistoryQuery::range(
+            self.deals_from,
+            Utc::now() + TimeDelta::days(1),
+        ))?;
+        deals.sort_by_key(|deal| deal.time_msc);
+
+        let mut events: Vec<TradeEvent> = orders
+            .values()
+            .filter(|order| !self.orders.contains_key(&order.ticket))
+            .cloned()
+            .map(TradeEvent::OrderPlaced)
+            .collect();
+        for (ticket, order) in &self.orders {
+            if orders.contains_key(ticket) {
+                continue;
+            }
+            let done = terminal.history_order(*ticket as usize)?;
+            match done.as_ref().map(|done| &done.state) {
+                Some(OrderState::FILLED | OrderState::PARTIAL) => {
+                    events.extend(done.map(TradeEvent::OrderFilled))
+                }
+                Some(OrderState::CANCELED | OrderState::REJECTED | OrderState::EXPIRED) => {
+                    events.extend(done.map(TradeEvent::OrderCancelled))
+                }
+                // Not in the history yet, look again at the next poll.
+                _ => {
+                    orders.insert(*ticket, order.clone());
+                }
+            }
+        }
+        events.extend(
+            deals
+                .iter()
+                .filter(|deal| !self.deals.contains(&deal.ticket))
+                .cloned()
+                .map(TradeEvent::DealAdded),
+        );
+        for position in positions.values() {
+            match self.positions.get(&position.ticket) {
+                None => events.push(TradeEvent::PositionOpened(position.clone())),
+                Some(previous) if position_modified(previous, position) => {
+                    events.push(TradeEvent::PositionModified {
+                        previous: previous.clone(),
+                        current: position.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        events.extend(
+            self.positions
+                .values()
+                .filter(|position| !positions.contains_key(&position.ticket))
+                .cloned()
+                .map(TradeEvent::PositionClosed),
+        );
+
+        if let Some(latest) = deals.last() {
+            self.deals_from = self.deals_from.max(latest.time);
+        }
+        self.deals = deals
+            .iter()
+            .filter(|deal| deal.time >= self.deals_from)
+            .map(|deal| deal.ticket)
+            .collect();
+        self.orders = orders;
+        self.positions = positions;
+        Ok(events)
+    }
+}
+
+fn position_modified(previous: &Position, current: &Position) -> bool {
+    previous.volume != current.volume
+        || previous.price_open != current.price_open
+        || previous.sl != current.sl
+        || previous.tp != current.tp
+}
+
+/// Threshold checked by an [`AccountMonitor`] on every poll.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountWatcher {
+    /// Margin level, in percent, falls below the threshold while margin is in use.
+    MarginLevelBelow(f64),
+    /// Equity falls more than the given percentage below the day's highest equity.
+    DrawdownFromDayHigh(f64),
+}
+
+/// An [`AccountWatcher`] whose threshold was crossed.
+#[derive(Debug, Clone)]
+pub struct AccountAlert {
+    pub watcher: AccountWatcher,
+    /// Margin level or drawdown, in percent, that crossed the threshold.
+    pub value: f64,
+    pub account: AccountInfo,
+}
+
+#[derive(Debug, Clone)]
+pub enum AccountEvent {
+    /// Balance, equity, margin, free margin or margin level changed.
+    Changed(AccountInfo),
+    Alert(AccountAlert),
+}
+
+type AlertCallback = Box<dyn FnMut(&AccountAlert) + Send>;
+
+/// Follows `account_info` and checks [`AccountWatcher`]s against it.
+///
+/// A watcher fires once when its threshold is crossed and again only after the
+/// account went back on the safe side. Alerts are returned as events and passed to
+/// the callbacks registered with [`AccountMonitor::on_alert`].
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use fishing_line::prelude::*;
+///
+/// # fn run(terminal: Arc<MT5PythonConnection>) -> MQLResult<()> {
+/// let monitor = AccountMonitor::new()
+///     .watch(AccountWatcher::MarginLevelBelow(150.0))
+///     .watch(AccountWatcher::DrawdownFromDayHigh(5.0))
+///     .on_alert(|alert| eprintln!("{:?} at {:.1}%", alert.watcher, alert.value));
+/// let account = terminal.subscribe_account(monitor, StreamOptions::new())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct AccountMonitor {
+    /// Each watcher and whether its threshold is currently crossed.
+    watchers: Vec<(AccountWatcher, bool)>,
+    callbacks: Vec<AlertCallback>,
+    timezone: Option<ServerTimezone>,
+    last: Option<AccountInfo>,
+    day: Option<NaiveDate>,
+    day_high: f64,
+}
+
+impl AccountMonitor {
+    /// Reports changes only, without any watcher.
+    pub fn new() -> Self {
+        AccountMonitor::default()
+    }
+
+    pub fn watch(mut self, watcher: AccountWatcher) -> Self {
+        self.watchers.push((watcher, false));
+        self
+    }
+
+    /// Calls `callback` with every alert, on the polling thread.
+    pub fn on_alert(mut self, callback: impl FnMut(&AccountAlert) + Send + 'static) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Starts the day used by [`AccountWatcher::DrawdownFromDayHigh`] at midnight
+    /// server time instead of midnight UTC.
+    pub fn server_timezone(mut self, timezone: ServerTimezone) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Highest equity seen since the start of the day.
+    pub fn day_high(&self) -> Option<f64> {
+        self.day.map(|_| self.day_high)
+    }
+
+    /// Reads `account_info` and returns what changed and which watchers fired.
+    pub fn poll<T: Terminal + ?Sized>(&mut self, terminal: &T) -> MQLResult<Vec<AccountEvent>> {
+        let account = terminal.account_info()?;
+        Ok(self.update(account, Utc::now()))
+    }
+
+    fn update(&mut self, account: AccountInfo, now: DateTime<Utc>) -> Vec<AccountEvent> {
+        let day = match &self.timezone {
+            Some(timezone) => timezone.to_server(&now).date_naive(),
+            None => now.date_naive(),
+        };
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.day_high = account.equity;
+        }
+        self.day_high = self.day_high.max(account.equity);
+
+        let mut events = Vec::new();
+        if self
+            .last
+            .as_ref()
+            .is_none_or(|last| account_changed(last, &account))
+        {
+            events.push(AccountEvent::Changed(account.clone()));
+        }
+        for (watcher, crossed) in &mut self.watchers {
+            let value = match *watcher {
+                AccountWatcher::MarginLevelBelow(threshold) => {
+                    let level = account.margin_level;
+                    (account.margin > 0.0 && level < threshold).then_some(level)
+                }
+                AccountWatcher::DrawdownFromDayHigh(threshold) => {
+                    let drawdown = if self.day_high > 0.0 {
+                        (self.day_high - account.equity) / self.day_high * 100.0
+                    } else {
+                        0.0
+                    };
+                    (drawdown > threshold).then_some(drawdown)
+                }
+            };
+            match value {
+                Some(value) if !*crossed => {
+                    *crossed = true;
+                    let alert = AccountAlert {
+                        watcher: *watcher,
+                        value,
+                        account: account.clone(),
+                    };
+                    for callback in &mut self.callbacks {
+                        callback(&alert);
+                    }
+                    events.push(AccountEvent::Alert(alert));
+                }
+                Some(_) => {}
+                None => *crossed = false,
+            }
+        }
+        self.last = Some(account);
+        events
+    }
+}
+
+fn account_changed(previous: &AccountInfo, current: &AccountInfo) -> bool {
+    previous.balance != current.balance
+        || previous.equity != current.equity
+        || previous.margin != current.margin
+        || previous.margin_free != current.margin_free
+        || previous.margin_level != current.margin_level
+}
+
+/// Streams built on a shared terminal.
+///
+/// Implemented for `Arc`s of any thread-safe [`Terminal`], including
+/// `Arc<dyn Terminal + Send + Sync>`. Each subscription polls from its own thread,
+/// concurrently with the other users of the terminal.
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use fishing_line::prelude::*;
+/// use futures_util::StreamExt;
+///
+/// # async fn run() -> MQLResult<()> {
+/// let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+/// let terminal = Arc::new(MT5PythonConnection::new()?.initialize(terminal_path.as_str())?);
+/// let mut ticks = terminal.subscribe_ticks(&["EURUSD", "GBPUSD"], StreamOptions::new())?;
+/// while let Some(tick) = ticks.next().await {
+///     println!("{:?}", tick?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait SubscribeTrait {
+    /// Streams every tick of `symbols` received after the call, oldest first.
+    ///
+    /// Ticks are read with `copy_ticks_from` starting at the last streamed
+    /// `time_msc`, so a slow consumer or a failed poll never opens a gap, and ticks
+    /// sharing a millisecond are streamed once each.
+    fn subscribe_ticks(
+        &self,
+        symbols: &[&str],
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<SymbolTick>>;
+
+    /// Streams each bar of the `(symbol, timeframe)` pairs once, when the next bar
+    /// opens.
+    ///
+    /// The first bar streamed is the one forming at the time of the call. Bars are
+    /// read with `copy_rates_from_pos` and a reconnected terminal streams every bar
+    /// closed in between, so the interval only sets the latency.
+    fn subscribe_bars(
+        &self,
+        pairs: &[(&str, Timeframe)],
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<ClosedBar>>;
+
+    /// Streams the [`TradeEvent`]s found by a [`TradeEventMonitor`] polling the
+    /// terminal.
+    fn subscribe_trade_events(&self, options: StreamOptions)
+        -> MQLResult<Subscription<TradeEvent>>;
+
+    /// Streams the account's state and alerts as checked by `monitor`, starting
+    /// with the current state.
+    fn subscribe_account(
+        &self,
+        monitor: AccountMonitor,
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<AccountEvent>>;
+}
+
+impl<T> SubscribeTrait for Arc<T>
+where
+    T: Terminal + Send + Sync + ?Sized + 'static,
+{
+    fn subscribe_ticks(
+        &self,
+        symbols: &[&str],
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<SymbolTick>> {
+        let mut cursors = symbols
+            .iter()
+            .map(|symbol| TickCursor::new(self.as_ref(), symbol))
+            .collect::<MQLResult<Vec<_>>>()?;
+        let terminal = self.clone();
+        Subscription::spawn("ticks", options, move || {
+            let mut errors = Vec::new();
+            let mut ticks = Vec::new();
+            for cursor in &mut cursors {
+                match cursor.poll(terminal.as_ref()) {
+                    Ok(batch) => ticks.extend(batch),
+                    Err(error) => errors.push(Err(error)),
+                }
+            }
+            ticks.sort_by_key(|tick| tick.time_msc);
+            errors
+                .into_iter()
+                .chain(ticks.into_iter().map(Ok))
+                .collect()
+        })
+    }
+
+    fn subscribe_bars(
+        &self,
+        pairs: &[(&str, Timeframe)],
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<ClosedBar>> {
+        let mut cursors = pairs
+            .iter()
+            .map(|(symbol, timeframe)| BarCursor::new(self.as_ref(), symbol, *timeframe))
+            .collect::<MQLResult<Vec<_>>>()?;
+        let terminal = self.clone();
+        Subscription::spawn("bars", options, move || {
+            let mut errors = Vec::new();
+            let mut bars = Vec::new();
+            for cursor in &mut cursors {
+                match cursor.poll(terminal.as_ref()) {
+                    Ok(closed) => bars.extend(closed),
+                    Err(error) => errors.push(Err(error)),
+                }
+            }
+            bars.sort_by_key(|closed| closed.bar.time);
+            errors.into_iter().chain(bars.into_iter().map(Ok)).collect()
+        })
+    }
+
+    fn subscribe_trade_events(
+        &self,
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<TradeEvent>> {
+        let mut monitor = TradeEventMonitor::new(self.as_ref())?;
+        let terminal = self.clone();
+        Subscription::spawn("trade-events", options, move || {
+            match monitor.poll(terminal.as_ref()) {
+                Ok(events) => events.into_iter().map(Ok).collect(),
+                Err(error) => vec![Err(error)],
+            }
+        })
+    }
+
+    fn subscribe_account(
+        &self,
+        mut monitor: AccountMonitor,
+        options: StreamOptions,
+    ) -> MQLResult<Subscription<AccountEvent>> {
+        let terminal = self.clone();
+        Subscription::spawn("account", options, move || {
+            match monitor.poll(terminal.as_ref()) {
+                Ok(events) => events.into_iter().map(Ok).collect(),
+                Err(error) => vec![Err(error)],
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use futures_executor::block_on_stream;
+
+    use crate::prelude::*;
+
+    fn tick(time_msc: i64, bid: f64) -> SymbolTick {
+        SymbolTick {
+            time: time_msc / 1000,
+            bid,
+            ask: bid + 0.0002,
+            time_msc,
+            ..Default::default()
+        }
+    }
+
+    fn options() -> StreamOptions {
+        StreamOptions::new().interval(Duration::from_millis(5))
+    }
+
+    #[test]
+    fn test_ticks_stream_without_gaps_or_duplicates() {
+        let mock = MockTerminal::new()
+            .with_ticks("EURUSD", vec![tick(1_000, 1.1000)])
+            .with_ticks("GBPUSD", vec![tick(1_500, 1.2500)]);
+        let terminal = Arc::new(mock.clone());
+        let ticks = terminal
+            .subscribe_ticks(&["EURUSD", "GBPUSD"], options().buffer(1))
+            .unwrap();
+        mock.push_tick("EURUSD", tick(2_000, 1.1001));
+        mock.push_tick("EURUSD", tick(2_000, 1.1002));
+        mock.push_tick("GBPUSD", tick(1_800, 1.2501));
+
+        let mut ticks = block_on_stream(ticks);
+        let mut bids: Vec<f64> = (0..3).map(|_| ticks.next().unwrap().unwrap().bid).collect();
+        bids.sort_by(f64::total_cmp);
+        assert_eq!(bids, vec![1.1001, 1.1002, 1.2501]);
+
+        mock.push_tick("EURUSD", tick(2_000, 1.1003));
+        mock.push_tick("EURUSD", tick(2_500, 1.1004));
+        let tick = ticks.next().unwrap().unwrap();
+        assert_eq!((tick.time_msc, tick.bid), (2_000, 1.1003));
+        let tick = ticks.next().unwrap().unwrap();
+        assert_eq!((tick.time_msc, tick.bid), (2_500, 1.1004));
+    }
+
+    #[test]
+    fn test_failed_poll_is_reported_and_resumed() {
+        let mock = MockTerminal::new().with_ticks("EURUSD", vec![tick(1_000, 1.1000)]);
+        let terminal: Arc<dyn Terminal + Send + Sync> = Arc::new(mock.clone());
+        let mut ticks = block_on_stream(terminal.subscribe_ticks(&["EURUSD"], options()).unwrap());
+
+        mock.fail_next(
+            "copy_ticks_from",
+            RuntimeError::NotFound,
+            "Terminal disconnected",
+        );
+        let error = ticks.next().unwrap().unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+
+        mock.push_tick("EURUSD", tick(1_200, 1.1001));
+        assert_eq!(ticks.next().unwrap().unwrap().bid, 1.1001);
+    }
+
+    fn bar(time: i64, close: f64) -> SymbolRates {
+        SymbolRates {
+            time: DateTime::from_timestamp(time, 0).unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bars_stream_each_closed_bar_once() {
+        // Friday 20:00 and 21:00 UTC, then the market reopens on Sunday 22:00.
+        let friday = 1_720_814_400;
+        let mock = MockTerminal::new().with_rates(
+            "EURUSD",
+            Timeframe::H1,
+            vec![bar(friday - 3_600, 1.1000), bar(friday, 1.1001)],
+        );
+        let terminal = Arc::new(mock.clone());
+        let mut bars = block_on_stream(
+            terminal
+                .subscribe_bars(&[("EURUSD", Timeframe::H1)], options())
+                .unwrap(),
+        );
+
+        mock.push_rate("EURUSD", Timeframe::H1, bar(friday + 3_600, 1.1002));
+        let closed = bars.next().unwrap().unwrap();
+        assert_eq!(
+            (closed.bar.time.timestamp(), closed.bar.close),
+            (friday, 1.1001)
+        );
+        assert_eq!(closed.symbol, "EURUSD");
+
+        mock.push_rate("EURUSD", Timeframe::H1, bar(friday + 50 * 3_600, 1.1003));
+        let closed = bars.next().unwrap().unwrap();
+        assert_eq!(closed.bar.time.timestamp(), friday + 3_600);
+    }
+
+    #[test]
+    fn test_bars_catch_up_after_reconnect() {
+        let start = 1_720_814_400;
+        let mock =
+            MockTerminal::new().with_rates("EURUSD", Timeframe::M1, vec![bar(start, 1.1000)]);
+        let terminal = Arc::new(mock.clone());
+        let mut bars = block_on_stream(
+            terminal
+                .subscribe_bars(&[("EURUSD", Timeframe::M1)], options())
+                .unwrap(),
+        );
+
+        mock.set_last_error(RuntimeError::InternalFailConnect, "Terminal disconnected");
+        let error = bars.next().unwrap().unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::InternalFailConnect));
+        // Minute 3 had no tick and is missing from the history.
+        for minute in [1, 2, 4, 5, 6, 7, 8] {
+            mock.push_rate("EURUSD", Timeframe::M1, bar(start + minute * 60, 1.1000));
+        }
+        mock.clear_last_error();
+
+        let opened: Vec<i64> = bars
+            .filter_map(Result::ok)
+            .take(7)
+            .map(|closed| (closed.bar.time.timestamp() - start) / 60)
+            .collect();
+        assert_eq!(opened, vec![0, 1, 2, 4, 5, 6, 7]);
+    }
+
+    fn position(ticket: isize, sl: f64) -> Position {
+        Position {
+            ticket,
+            identifier: ticket,
+            symbol: "EURUSD".to_string(),
+            volume: 0.1,
+            sl,
+            ..Default::default()
+        }
+    }
+
+    fn order(ticket: isize, state: OrderState) -> Order {
+        Order {
+            ticket,
+            state,
+            r#type: OrderType::BuyLimit,
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn deal(ticket: isize, order: isize, position_id: isize) -> Deals {
+        Deals {
+            ticket,
+            order,
+            position_id,
+            time: Utc::now(),
+            time_msc: Utc::now().timestamp_millis(),
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trade_event_monitor_diffs_snapshots() {
+        let terminal = MockTerminal::new()
+            .with_position(position(1, 1.0900))
+            .with_deal(deal(100, 1, 1));
+        terminal.set_orders(vec![
+            order(2, OrderState::PLACED),
+            order(3, OrderState::PLACED),
+        ]);
+        let mut monitor = TradeEventMonitor::new(&terminal).unwrap();
+        assert!(monitor.poll(&terminal).unwrap().is_empty());
+
+        // Order 2 fills into position 2, order 3 is canceled, position 1 moves its
+        // stop loss and a new order 4 is placed.
+        terminal.set_orders(vec![order(4, OrderState::PLACED)]);
+        terminal.push_history_order(order(2, OrderState::FILLED));
+        terminal.push_history_order(order(3, OrderState::CANCELED));
+        terminal.push_deal(deal(101, 2, 2));
+        terminal.set_positions(vec![position(1, 1.0950), position(2, 0.0)]);
+
+        let events = monitor.poll(&terminal).unwrap();
+        let described: Vec<String> = events
+            .iter()
+            .map(|event| match event {
+                TradeEvent::OrderPlaced(order) => format!("placed {}", order.ticket),
+                TradeEvent::OrderFilled(order) => format!("filled {}", order.ticket),
+                TradeEvent::OrderCancelled(order) => format!("cancelled {}", order.ticket),
+                TradeEvent::DealAdded(deal) => format!("deal {}", deal.ticket),
+                TradeEvent::PositionOpened(position) => format!("opened {}", position.ticket),
+                TradeEvent::PositionModified { previous, current } => {
+                    format!("modified {} {} {}", current.ticket, previous.sl, current.sl)
+                }
+                TradeEvent::PositionClosed(position) => format!("closed {}", position.ticket),
+            })
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "placed 4",
+                "filled 2",
+                "cancelled 3",
+                "deal 101",
+                "modified 1 1.09 1.095",
+                "opened 2",
+            ]
+        );
+
+        terminal.set_positions(vec![position(2, 0.0)]);
+        terminal.push_deal(deal(102, 5, 1));
+        let events = monitor.poll(&terminal).unwrap();
+        assert_eq!(events.len(), 2, "Unexpected events {:?}", events);
+        assert!(matches!(&events[0], TradeEvent::DealAdded(deal) if deal.ticket == 102));
+        assert!(matches!(&events[1], TradeEvent::PositionClosed(position) if position.ticket == 1));
+    }
+
+    #[test]
+    fn test_trade_event_monitor_waits_for_history() {
+        let terminal = MockTerminal::new();
+        terminal.set_orders(vec![order(7, OrderState::PLACED)]);
+        let mut monitor = TradeEventMonitor::new(&terminal).unwrap();
+
+        terminal.set_orders(Vec::new());
+        assert!(monitor.poll(&terminal).unwrap().is_empty());
+
+        terminal.push_history_order(order(7, OrderState::EXPIRED));
+        let events = monitor.poll(&terminal).unwrap();
+        assert!(matches!(&events[..], [TradeEvent::OrderCancelled(order)] if order.ticket == 7));
+    }
+
+    #[test]
+    fn test_trade_events_stream() {
+        let mock = MockTerminal::new();
+        let terminal = Arc::new(mock.clone());
+        let mut events = block_on_stream(terminal.subscribe_trade_events(options()).unwrap());
+
+        mock.set_positions(vec![position(9, 0.0)]);
+        match events.next().unwrap().unwrap() {
+            TradeEvent::PositionOpened(position) => assert_eq!(position.ticket, 9),
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    fn account(equity: f64, margin: f64) -> AccountInfo {
+        AccountInfo {
+            balance: 10_000.0,
+            equity,
+            margin,
+            margin_free: equity - margin,
+            margin_level: if margin > 0.0 {
+                equity / margin * 100.0
+            } else {
+                0.0
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_account_monitor_watchers() {
+        let alerts = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = alerts.clone();
+        let mut monitor = AccountMonitor::new()
+            .watch(AccountWatcher::MarginLevelBelow(150.0))
+            .watch(AccountWatcher::DrawdownFromDayHigh(5.0))
+            .on_alert(move |alert| recorded.lock().unwrap().push(alert.value));
+        let monday = DateTime::from_timestamp(1_720_396_800, 0).unwrap();
+        let hour = TimeDelta::hours(1);
+
+        let events = monitor.update(account(10_000.0, 0.0), monday);
+        assert!(matches!(&events[..], [AccountEvent::Changed(_)]));
+        assert!(monitor
+            .update(account(10_000.0, 0.0), monday + hour)
+            .is_empty());
+
+        monitor.update(account(10_500.0, 5_000.0), monday + hour * 2);
+        assert_eq!(monitor.day_high(), Some(10_500.0));
+        let events = monitor.update(account(7_000.0, 5_000.0), monday + hour * 3);
+        assert_eq!(events.len(), 3, "Unexpected events {:?}", events);
+        assert_eq!(alerts.lock().unwrap().len(), 2);
+
+        // Still below both thresholds: no new alert until the account recovers.
+        assert_eq!(
+            monitor
+                .update(account(6_900.0, 5_000.0), monday + hour * 4)
+                .len(),
+            1
+        );
+        monitor.update(account(10_400.0, 5_000.0), monday + hour * 5);
+        let events = monitor.update(account(7_000.0, 5_000.0), monday + hour * 6);
+        assert_eq!(events.len(), 3, "Unexpected events {:?}", events);
+
+        // A new day starts from the current equity.
+        monitor.update(account(7_000.0, 0.0), monday + hour * 25);
+        assert_eq!(monitor.day_high(), Some(7_000.0));
+        assert_eq!(alerts.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_account_stream() {
+        let mock = MockTerminal::new().with_account(account(10_000.0, 0.0));
+        let terminal = Arc::new(mock.clone());
+        let monitor = AccountMonitor::new().watch(AccountWatcher::MarginLevelBelow(150.0));
+        let mut events = block_on_stream(terminal.subscribe_account(monitor, options()).unwrap());
+        assert!(matches!(events.next(), Some(Ok(AccountEvent::Changed(_)))));
+
+        mock.set_account(account(9_000.0, 7_500.0));
+        assert!(matches!(events.next(), Some(Ok(AccountEvent::Changed(_)))));
+        match events.next() {
+            Some(Ok(AccountEvent::Alert(alert))) => assert_eq!(alert.value, 120.0),
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsubscribe_stops_poller() {
+        let mock = MockTerminal::new().with_ticks("EURUSD", vec![tick(1_000, 1.1000)]);
+        let terminal = Arc::new(mock.clone());
+        let ticks = terminal.subscribe_ticks(&["EURUSD"], options()).unwrap();
+        ticks.unsubscribe();
+        assert_eq!(
+            Arc::strong_count(&terminal),
+            1,
+            "Poller still holds the terminal"
+        );
+
+        let error = terminal
+            .subscribe_ticks(&["XAUUSD"], options())
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+    }
+}
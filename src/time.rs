@@ -0,0 +1,383 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Utc, Weekday};
+
+use crate::prelude::*;
+
+/// Daylight saving time rule followed by a trade server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DstRule {
+    /// Second Sunday of March to first Sunday of November, switching at 2:00 New York time.
+    UnitedStates,
+    /// Last Sunday of March to last Sunday of October, switching at 01:00 UTC.
+    Europe,
+}
+
+impl DstRule {
+    /// Whether daylight saving time is in effect at `utc`.
+    pub fn is_active(self, utc: DateTime<Utc>) -> bool {
+        let year = utc.year();
+        let (start, end) = match self {
+            DstRule::UnitedStates => (
+                sunday(year, 3, 2).and_hms_opt(7, 0, 0),
+                sunday(year, 11, 1).and_hms_opt(6, 0, 0),
+            ),
+            DstRule::Europe => (
+                last_sunday(year, 3).and_hms_opt(1, 0, 0),
+                last_sunday(year, 10).and_hms_opt(1, 0, 0),
+            ),
+        };
+        let (Some(start), Some(end)) = (start, end) else {
+            return false;
+        };
+        let time = utc.naive_utc();
+        time >= start && time < end
+    }
+}
+
+fn sunday(year: i32, month: u32, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n).unwrap_or_default()
+}
+
+fn last_sunday(year: i32, month: u32) -> NaiveDate {
+    let last_day = NaiveDate::from_ymd_opt(year, month + 1, 1)
+        .and_then(|first| first.pred_opt())
+        .unwrap_or_default();
+    last_day - Duration::days(last_day.weekday().num_days_from_sunday() as i64)
+}
+
+/// Time zone of the broker's trade server.
+///
+/// MT5 stamps bars, ticks, orders and deals with the server's wall-clock time
+/// written as if it were UTC, and reads time arguments the same way. A connection
+/// configured with the server time zone shifts time arguments into server time and
+/// normalizes every returned time back to real UTC, so data from brokers in
+/// different zones lines up.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ServerTimezone {
+    /// Server time is UTC, times are passed through unchanged.
+    #[default]
+    Utc,
+    /// A fixed offset from UTC.
+    Fixed(FixedOffset),
+    /// A standard offset from UTC, one hour ahead while `rule` is active.
+    Dst {
+        standard: FixedOffset,
+        rule: DstRule,
+    },
+}
+
+impl ServerTimezone {
+    /// UTC+2, or UTC+3 while US daylight saving time is active.
+    ///
+    /// Most retail brokers use this so the daily bar closes at 17:00 New York time.
+    pub fn new_york_close() -> Self {
+        ServerTimezone::Dst {
+            standard: FixedOffset::east_opt(2 * 3600).unwrap(),
+            rule: DstRule::UnitedStates,
+        }
+    }
+
+    /// Offset of the server clock from UTC at `utc`.
+    pub fn offset_at(&self, utc: DateTime<Utc>) -> FixedOffset {
+        match *self {
+            ServerTimezone::Utc => FixedOffset::east_opt(0).unwrap(),
+            ServerTimezone::Fixed(offset) => offset,
+            ServerTimezone::Dst { standard, rule } if rule.is_active(utc) => {
+                FixedOffset::east_opt(standard.local_minus_utc() + 3600).unwrap_or(standard)
+            }
+            ServerTimezone::Dst { standard, .. } => standard,
+        }
+    }
+
+    /// Converts a time in any zone into server time, written as if it were UTC.
+    pub fn to_server<Tz: TimeZone>(&self, date: &DateTime<Tz>) -> DateTime<Utc> {
+        let utc = date.to_utc();
+        utc + Duration::seconds(self.offset_at(utc).local_minus_utc() as i64)
+    }
+
+    /// Converts a server time returned by MT5 into UTC.
+    pub fn to_utc(&self, server: DateTime<Utc>) -> DateTime<Utc> {
+        let shift = |offset: i32| server - Duration::seconds(offset as i64);
+        match *self {
+            ServerTimezone::Utc => server,
+            ServerTimezone::Fixed(offset) => shift(offset.local_minus_utc()),
+            ServerTimezone::Dst { standard, rule } => {
+                let daylight = shift(standard.local_minus_utc() + 3600);
+                if rule.is_active(daylight) {
+                    daylight
+                } else {
+                    shift(standard.local_minus_utc())
+                }
+            }
+        }
+    }
+
+    /// Detects a fixed server offset by comparing the last quote of `symbol` with the
+    /// current time, rounded to the nearest quarter hour.
+    ///
+    /// `terminal` must be a connection without a server time zone: one that already
+    /// normalizes quotes to UTC yields a zero offset. The symbol must be actively
+    /// quoted, otherwise the last quote is stale and the detection fails.
+    ///
+    /// The result is always [`ServerTimezone::Fixed`], the offset in effect right now.
+    /// DST switching cannot be detected this way, so prefer [`ServerTimezone::Dst`]
+    /// when the broker's rule is known.
+    pub fn detect(terminal: &dyn SymbolInfoTrait, symbol: &str) -> MQLResult<Self> {
+        let tick = terminal.symbol_info_tick(symbol)?;
+        Self::detect_at(tick.time, Utc::now())
+    }
+
+    fn detect_at(server_seconds: i64, now: DateTime<Utc>) -> MQLResult<Self> {
+        const QUARTER_HOUR: i64 = 15 * 60;
+        let difference = server_seconds - now.timestamp();
+        let offset = (difference as f64 / QUARTER_HOUR as f64).round() as i64 * QUARTER_HOUR;
+        if (difference - offset).abs() > 5 * 60 || offset.abs() > 14 * 3600 {
+            return Err(FishingLineError::Environment(format!(
+                "Unable to detect server offset from a quote {} seconds away, the market may be closed",
+                difference
+            )));
+        }
+        Ok(ServerTimezone::Fixed(
+            FixedOffset::east_opt(offset as i32).unwrap(),
+        ))
+    }
+
+    pub(crate) fn seconds_to_utc(&self, seconds: i64) -> i64 {
+        match DateTime::from_timestamp(seconds, 0) {
+            Some(server) if seconds != 0 => self.to_utc(server).timestamp(),
+            _ => seconds,
+        }
+    }
+
+    pub(crate) fn millis_to_utc(&self, millis: i64) -> i64 {
+        match DateTime::from_timestamp_millis(millis) {
+            Some(server) if millis != 0 => self.to_utc(server).timestamp_millis(),
+            _ => millis,
+        }
+    }
+
+    fn datetime_to_utc(&self, server: DateTime<Utc>) -> DateTime<Utc> {
+        if server == DateTime::UNIX_EPOCH {
+            server
+        } else {
+            self.to_utc(server)
+        }
+    }
+}
+
+/// Values carrying times stamped in server time.
+pub(crate) trait ServerTime {
+    /// Moves every time in `self` from server time to UTC. Unset (zero) times are kept.
+    fn into_utc(self, timezone: &ServerTimezone) -> Self;
+}
+
+impl<T: ServerTime> ServerTime for Vec<T> {
+    fn into_utc(self, timezone: &ServerTimezone) -> Self {
+        self.into_iter()
+            .map(|value| value.into_utc(timezone))
+            .collect()
+    }
+}
+
+impl ServerTime for SymbolInfo {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time = timezone.seconds_to_utc(self.time);
+        self.start_time = timezone.seconds_to_utc(self.start_time);
+        self.expiration_time = timezone.seconds_to_utc(self.expiration_time);
+        self
+    }
+}
+
+impl ServerTime for SymbolTick {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time = timezone.seconds_to_utc(self.time);
+        self.time_msc = timezone.millis_to_utc(self.time_msc);
+        self
+    }
+}
+
+impl ServerTime for SymbolRates {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time = timezone.datetime_to_utc(self.time);
+        self
+    }
+}
+
+impl ServerTime for Order {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time_setup = timezone.datetime_to_utc(self.time_setup);
+        self.time_done = timezone.datetime_to_utc(self.time_done);
+        self.time_expiration = timezone.datetime_to_utc(self.time_expiration);
+        self.time_setup_msc = timezone.millis_to_utc(self.time_setup_msc as i64) as isize;
+        self.time_done_msc = timezone.millis_to_utc(self.time_done_msc as i64) as isize;
+        self
+    }
+}
+
+impl ServerTime for Position {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time = timezone.datetime_to_utc(self.time);
+        self.time_update = timezone.datetime_to_utc(self.time_update);
+        self.time_msc = timezone.millis_to_utc(self.time_msc as i64) as isize;
+        self.time_update_msc = timezone.millis_to_utc(self.time_update_msc as i64) as isize;
+        self
+    }
+}
+
+impl ServerTime for Deals {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.time = timezone.datetime_to_utc(self.time);
+        self.time_msc = timezone.millis_to_utc(self.time_msc);
+        self
+    }
+}
+
+impl ServerTime for TradeRequest {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.expiration = timezone.datetime_to_utc(self.expiration);
+        self
+    }
+}
+
+impl ServerTime for CheckResult {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.request = self.request.into_utc(timezone);
+        self
+    }
+}
+
+impl ServerTime for TradeResult {
+    fn into_utc(mut self, timezone: &ServerTimezone) -> Self {
+        self.request = self.request.into_utc(timezone);
+        self
+    }
+}
+
+impl TradeRequestBuilder {
+    /// Moves the expiration of a pending order request into server time.
+    pub(crate) fn into_server_time(self, timezone: &ServerTimezone) -> Self {
+        TradeRequestBuilder {
+            expiration: self
+                .expiration
+                .map(|expiration| timezone.to_server(&expiration)),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{FixedOffset, TimeZone, Utc};
+
+    use super::ServerTime;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_new_york_close_switches_with_us_dst() {
+        let timezone = ServerTimezone::new_york_close();
+        let winter = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap();
+        assert_eq!(timezone.offset_at(winter).local_minus_utc(), 2 * 3600);
+        assert_eq!(timezone.offset_at(summer).local_minus_utc(), 3 * 3600);
+
+        // US DST starts on 2024-03-10 at 07:00 UTC, two weeks before Europe.
+        let before = Utc.with_ymd_and_hms(2024, 3, 10, 6, 59, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 10, 7, 0, 0).unwrap();
+        assert_eq!(timezone.offset_at(before).local_minus_utc(), 2 * 3600);
+        assert_eq!(timezone.offset_at(after).local_minus_utc(), 3 * 3600);
+    }
+
+    #[test]
+    fn test_server_round_trip() {
+        let timezone = ServerTimezone::new_york_close();
+        let local = FixedOffset::west_opt(4 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 7, 8, 9, 30, 0)
+            .unwrap();
+        let server = timezone.to_server(&local);
+        assert_eq!(server, Utc.with_ymd_and_hms(2024, 7, 8, 16, 30, 0).unwrap());
+        assert_eq!(timezone.to_utc(server), local.to_utc());
+    }
+
+    #[test]
+    fn test_returned_times_are_normalized() {
+        let timezone = ServerTimezone::Fixed(FixedOffset::east_opt(3 * 3600).unwrap());
+        let tick = SymbolTick {
+            time: 1720447200,
+            time_msc: 1720447200250,
+            ..Default::default()
+        }
+        .into_utc(&timezone);
+        assert_eq!(tick.time, 1720436400);
+        assert_eq!(tick.time_msc, 1720436400250);
+
+        let symbol = SymbolInfo {
+            time: 1720447200,
+            start_time: 1719792000,
+            expiration_time: 1726790400,
+            ..Default::default()
+        }
+        .into_utc(&timezone);
+        assert_eq!(
+            (symbol.time, symbol.start_time, symbol.expiration_time),
+            (1720436400, 1719781200, 1726779600)
+        );
+        let perpetual = SymbolInfo::default().into_utc(&timezone);
+        assert_eq!((perpetual.start_time, perpetual.expiration_time), (0, 0));
+
+        let order = Order {
+            time_setup: Utc.with_ymd_and_hms(2024, 7, 8, 14, 0, 0).unwrap(),
+            ..Default::default()
+        }
+        .into_utc(&timezone);
+        assert_eq!(
+            order.time_setup,
+            Utc.with_ymd_and_hms(2024, 7, 8, 11, 0, 0).unwrap()
+        );
+        assert_eq!(
+            order.time_done,
+            chrono::DateTime::UNIX_EPOCH,
+            "Unset time was shifted"
+        );
+    }
+
+    #[test]
+    fn test_zoned_time_arguments() {
+        let new_york = FixedOffset::west_opt(4 * 3600).unwrap();
+        let rates = (0..6)
+            .map(|hour| SymbolRates {
+                time: Utc.with_ymd_and_hms(2024, 7, 8, 12 + hour, 0, 0).unwrap(),
+                close: hour as f64,
+                ..Default::default()
+            })
+            .collect();
+        let terminal: Box<dyn Terminal> =
+            Box::new(MockTerminal::new().with_rates("EURUSD", Timeframe::H1, rates));
+
+        let from = new_york.with_ymd_and_hms(2024, 7, 8, 9, 30, 0).unwrap();
+        let to = new_york.with_ymd_and_hms(2024, 7, 8, 12, 0, 0).unwrap();
+        let bars = terminal
+            .copy_rates_range_tz("EURUSD", Timeframe::H1, from, to)
+            .unwrap();
+        let closes: Vec<f64> = bars.iter().map(|rate| rate.close).collect();
+        assert_eq!(closes, vec![2.0, 3.0, 4.0]);
+
+        assert_eq!(
+            HistoryQuery::range(from, to),
+            HistoryQuery::range(from.to_utc(), to.to_utc())
+        );
+    }
+
+    #[test]
+    fn test_detect_rounds_to_quarter_hour() {
+        let now = Utc.with_ymd_and_hms(2024, 7, 8, 12, 0, 0).unwrap();
+        let detected = ServerTimezone::detect_at(now.timestamp() + 3 * 3600 + 4, now).unwrap();
+        assert_eq!(
+            detected,
+            ServerTimezone::Fixed(FixedOffset::east_opt(3 * 3600).unwrap())
+        );
+        assert!(
+            ServerTimezone::detect_at(now.timestamp() - 2 * 86400 + 1000, now).is_err(),
+            "Stale quote was accepted"
+        );
+    }
+}
@@ -0,0 +1,654 @@
+//! Position and pending order management on top of [`OrderTrait`] and [`PositionTrait`].
+//!
+//! [`TradingTrait`] builds, normalizes and validates the market and close-by
+//! requests that close, reduce or reverse positions, following the account's
+//! margin mode: on hedging accounts a closing deal names the position it closes,
+//! on netting accounts it is an opposite deal on the symbol's single position.
+//! It also moves stops and edits or deletes pending orders, skipping the requests
+//! that would not change anything.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::prelude::*;
+use crate::validation::{check_frozen, tick_size};
+
+/// High-level trading operations, available on every [`Terminal`].
+///
+/// Prices and filling types are normalized with [`TradeRequestBuilder::normalize_prices`]
+/// and [`TradeRequestBuilder::normalize_filling`] and requests are checked with
+/// [`validate`] before being sent, a request breaking a rule fails with
+/// [`FishingLineError::InvalidRequest`] without reaching the server. Volumes are
+/// sent as given: a deal larger than the symbol's `volume_max`, such as the close of
+/// a grown netting position, fails with [`TradeViolation::VolumeOutOfRange`] rather
+/// than being cut short, and can be split with [`close_partial`](Self::close_partial).
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+/// for (position, result) in terminal.close_all(&TradeFilter::new().symbol("EURUSD"))? {
+///     if let Err(error) = result {
+///         eprintln!("unable to close {}: {}", position.ticket, error);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait TradingTrait: Terminal {
+    /// The open position with `ticket`.
+    fn position(&self, ticket: usize) -> MQLResult<Position> {
+        self.positions_get_filtered(&TradeFilter::new().ticket(ticket))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                FishingLineError::runtime(
+                    RuntimeError::NotFound,
+                    format!("Position {} not found", ticket),
+                )
+            })
+    }
+
+    /// Closes the whole position with `ticket` at the market.
+    fn close_position(&self, ticket: usize) -> MQLResult<TradeResult> {
+        let position = self.position(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&position.symbol)?;
+        let order = closing_order(&position, position.volume, &symbol, &account)?;
+        send_checked(self, order.into(), &symbol, &account)
+    }
+
+    /// Closes `volume` of the position with `ticket` at the market.
+    fn close_partial(&self, ticket: usize, volume: f64) -> MQLResult<TradeResult> {
+        let position = self.position(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&position.symbol)?;
+        if volume > position.volume {
+            return Err(FishingLineError::InvalidRequest(vec![
+                TradeViolation::VolumeOutOfRange {
+                    volume,
+                    min: symbol.volume_min,
+                    max: position.volume,
+                },
+            ]));
+        }
+        let order = closing_order(&position, volume, &symbol, &account)?;
+        send_checked(self, order.into(), &symbol, &account)
+    }
+
+    /// Turns the position with `ticket` into one of the same volume on the other
+    /// side.
+    ///
+    /// Netting accounts send a single opposite deal of twice the volume. Hedging
+    /// accounts close the position, then open the opposite one: both requests are
+    /// validated before the close is sent, but the opening deal can still fail on
+    /// the server, which leaves the account without a position. The returned
+    /// [`Reversal`] holds the result of each leg, an `Err` means nothing was sent
+    /// or the close itself failed.
+    fn reverse_position(&self, ticket: usize) -> MQLResult<Reversal> {
+        let position = self.position(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&position.symbol)?;
+        let side = position_side(&position)?.opposite();
+        let hedging = is_hedging(&account);
+        let volume = if hedging {
+            position.volume
+        } else {
+            position.volume * 2.0
+        };
+        let open = market_order(&position.symbol, side, volume, &symbol)
+            .magic(position.magic as i64)
+            .comment(position.comment.clone());
+        let open = checked(open.into(), &symbol, &account)?;
+        let close = if hedging {
+            let close = closing_order(&position, position.volume, &symbol, &account)?;
+            Some(send_checked(self, close.into(), &symbol, &account)?)
+        } else {
+            None
+        };
+        Ok(Reversal {
+            close,
+            open: self.order_send(open),
+        })
+    }
+
+    /// Closes the position with `ticket` by the opposite position `opposite`,
+    /// on hedging accounts.
+    fn close_by(&self, ticket: usize, opposite: usize) -> MQLResult<TradeResult> {
+        let position = self.position(ticket)?;
+        let other = self.position(opposite)?;
+        if position.symbol != other.symbol || position.r#type == other.r#type {
+            return Err(FishingLineError::InvalidRequest(vec![
+                TradeViolation::CloseByMismatch,
+            ]));
+        }
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&position.symbol)?;
+        let request = CloseBy::new(ticket, opposite).magic(position.magic as i64);
+        send_checked(self, request.into(), &symbol, &account)
+    }
+
+    /// Closes every open position matching `filter`, returning each position with
+    /// the outcome of its close. Failing to close one position does not stop the
+    /// others.
+    fn close_all(
+        &self,
+        filter: &TradeFilter,
+    ) -> MQLResult<Vec<(Position, MQLResult<TradeResult>)>> {
+        let positions = self.positions_get_filtered(filter)?;
+        let account = self.account_info()?;
+        let mut symbols: HashMap<String, SymbolInfo> = HashMap::new();
+        let mut results = Vec::with_capacity(positions.len());
+        for position in positions {
+            let result = match symbols.get(&position.symbol) {
+                Some(symbol) => Ok(symbol.clone()),
+                None => self.symbol_info(&position.symbol).inspect(|symbol| {
+                    symbols.insert(position.symbol.clone(), symbol.clone());
+                }),
+            }
+            .and_then(|symbol| {
+                let order = closing_order(&position, position.volume, &symbol, &account)?;
+                send_checked(self, order.into(), &symbol, &account)
+            });
+            results.push((position, result));
+        }
+        Ok(results)
+    }
+
+    /// The pending order with `ticket`.
+    fn pending_order(&self, ticket: usize) -> MQLResult<Order> {
+        self.orders_get_filtered(&TradeFilter::new().ticket(ticket))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                FishingLineError::runtime(
+                    RuntimeError::NotFound,
+                    format!("Order {} not found", ticket),
+                )
+            })
+    }
+
+    /// Moves the stop loss and take profit of the position with `ticket`, `0.0`
+    /// removing a level.
+    ///
+    /// Returns `None` without sending anything when both levels already sit at the
+    /// requested prices, which the server would answer with `NoChanges`.
+    fn modify_position_stops(
+        &self,
+        ticket: usize,
+        sl: f64,
+        tp: f64,
+    ) -> MQLResult<Option<TradeResult>> {
+        let position = self.position(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&position.symbol)?;
+        let request: TradeRequestBuilder = ModifyStops::new(position.symbol.clone(), ticket)
+            .sl(sl)
+            .tp(tp)
+            .into();
+        let r#type = position_side(&position)?.market();
+        let request = request.normalize_prices(&symbol);
+        let tick_size = tick_size(&symbol);
+        if same_price(request.sl, position.sl, tick_size)
+            && same_price(request.tp, position.tp, tick_size)
+        {
+            return Ok(None);
+        }
+        send_modification(self, request, r#type, &symbol, &account).map(Some)
+    }
+
+    /// Moves the pending order with `ticket` to `price` with new stop loss and take
+    /// profit, `0.0` removing a level. `expiration` cancels the order at that time,
+    /// `None` keeps its current lifetime. The limit price of a stop limit order is
+    /// kept.
+    ///
+    /// Returns `None` without sending anything when the order already has these
+    /// prices and expiration, which the server would answer with `NoChanges`, and
+    /// fails with [`TradeViolation::Frozen`] while the current price is within the
+    /// symbol's freeze level of the market.
+    fn modify_pending(
+        &self,
+        ticket: usize,
+        price: f64,
+        sl: f64,
+        tp: f64,
+        expiration: Option<DateTime<Utc>>,
+    ) -> MQLResult<Option<TradeResult>> {
+        let order = self.pending_order(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&order.symbol)?;
+        let mut modify = ModifyPending::new(ticket, price).sl(sl).tp(tp);
+        if order.price_stoplimit > 0.0 {
+            modify = modify.stoplimit(order.price_stoplimit);
+        }
+        let expires = matches!(
+            order.type_time,
+            OrderTypeTime::SPECIFIED | OrderTypeTime::SpecifiedDay
+        );
+        let unchanged_expiration = match expiration {
+            Some(expiration) => {
+                modify = modify.expiration(expiration);
+                expires && expiration.timestamp() == order.time_expiration.timestamp()
+            }
+            None => {
+                modify = modify.type_time(order.type_time);
+                if expires {
+                    modify = modify.expiration(order.time_expiration);
+                }
+                true
+            }
+        };
+        let request: TradeRequestBuilder = modify.into();
+        let request = request.normalize_prices(&symbol);
+        let tick_size = tick_size(&symbol);
+        if unchanged_expiration
+            && same_price(request.price, order.price_open, tick_size)
+            && same_price(request.sl, order.sl, tick_size)
+            && same_price(request.tp, order.tp, tick_size)
+        {
+            return Ok(None);
+        }
+        not_frozen(&order, &symbol)?;
+        send_modification(self, request, order.r#type, &symbol, &account).map(Some)
+    }
+
+    /// Deletes the pending order with `ticket`, failing with [`TradeViolation::Frozen`]
+    /// while its price is within the symbol's freeze level of the market.
+    fn cancel_pending(&self, ticket: usize) -> MQLResult<TradeResult> {
+        let order = self.pending_order(ticket)?;
+        let account = self.account_info()?;
+        let symbol = self.symbol_info(&order.symbol)?;
+        not_frozen(&order, &symbol)?;
+        send_checked(self, CancelPending::new(ticket).into(), &symbol, &account)
+    }
+}
+
+impl<T: Terminal + ?Sized> TradingTrait for T {}
+
+/// Outcome of [`TradingTrait::reverse_position`].
+#[derive(Debug)]
+pub struct Reversal {
+    /// Deal closing the position on hedging accounts, `None` on netting accounts
+    /// where the opening deal reverses the position by itself.
+    pub close: Option<TradeResult>,
+    /// Deal opening the opposite position. An `Err` after a successful `close`
+    /// leaves the account without a position on the symbol.
+    pub open: MQLResult<TradeResult>,
+}
+
+/// Whether `requested`, unset meaning `0.0`, rounds to the same tick as `current`.
+fn same_price(requested: Option<f64>, current: f64, tick_size: f64) -> bool {
+    (requested.unwrap_or_default() - current).abs() < tick_size.max(f64::EPSILON) / 2.0
+}
+
+fn is_hedging(account: &AccountInfo) -> bool {
+    account.margin_mode == AccountMarginMode::RetailHedging
+}
+
+fn position_side(position: &Position) -> MQLResult<OrderSide> {
+    OrderSide::of_position(position.r#type).ok_or_else(|| FishingLineError::Conversion {
+        field: "type".to_string(),
+        message: format!(
+            "Position {} has unknown type {:?}",
+            position.ticket, position.r#type
+        ),
+    })
+}
+
+/// Market order at the price it fills at: the ask to buy, the bid to sell.
+fn market_order(name: &str, side: OrderSide, volume: f64, symbol: &SymbolInfo) -> MarketOrder {
+    let price = match side {
+        OrderSide::Buy => symbol.ask,
+        OrderSide::Sell => symbol.bid,
+    };
+    MarketOrder::new(name, side, volume).price(price)
+}
+
+/// Opposite deal closing `volume` of `position`.
+fn closing_order(
+    position: &Position,
+    volume: f64,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> MQLResult<MarketOrder> {
+    let side = position_side(position)?.opposite();
+    let order = market_order(&position.symbol, side, volume, symbol).magic(position.magic as i64);
+    Ok(if is_hedging(account) {
+        order.position(position.ticket as usize)
+    } else {
+        order
+    })
+}
+
+/// Normalizes the prices and filling type of `request` and validates it. The volume
+/// is left alone, so a volume the symbol refuses is reported instead of changed.
+fn checked(
+    request: TradeRequestBuilder,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> MQLResult<TradeRequestBuilder> {
+    let request = request.normalize_prices(symbol).normalize_filling(symbol);
+    let violations = validate(&request, symbol, account);
+    if !violations.is_empty() {
+        return Err(FishingLineError::InvalidRequest(violations));
+    }
+    Ok(request)
+}
+
+/// Sends `request` once [`checked`].
+fn send_checked<T: Terminal + ?Sized>(
+    terminal: &T,
+    request: TradeRequestBuilder,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> MQLResult<TradeResult> {
+    terminal.order_send(checked(request, symbol, account)?)
+}
+
+/// Fails with [`TradeViolation::Frozen`] when `order` is too close to the market to
+/// be modified or deleted.
+fn not_frozen(order: &Order, symbol: &SymbolInfo) -> MQLResult<()> {
+    match check_frozen(order, symbol) {
+        Some(violation) => Err(FishingLineError::InvalidRequest(vec![violation])),
+        None => Ok(()),
+    }
+}
+
+/// Sends a request modifying a position or pending order of `r#type` once its
+/// prices are normalized and it passes [`validate_with_type`].
+fn send_modification<T: Terminal + ?Sized>(
+    terminal: &T,
+    request: TradeRequestBuilder,
+    r#type: OrderType,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> MQLResult<TradeResult> {
+    let violations = validate_with_type(&request, r#type, symbol, account);
+    if !violations.is_empty() {
+        return Err(FishingLineError::InvalidRequest(violations));
+    }
+    terminal.order_send(request)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeDelta, Utc};
+
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    fn position(ticket: isize, r#type: PositionType, volume: f64) -> Position {
+        Position {
+            ticket,
+            identifier: ticket,
+            r#type,
+            volume,
+            magic: 7,
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn terminal(margin_mode: AccountMarginMode) -> MockTerminal {
+        MockTerminal::new()
+            .with_account(AccountInfo {
+                trade_allowed: true,
+                margin_mode,
+                ..Default::default()
+            })
+            .with_symbol(eurusd())
+            .with_position(position(1, PositionType::BUY, 0.3))
+            .with_position(position(2, PositionType::SELL, 0.1))
+    }
+
+    #[test]
+    fn test_close_position_by_margin_mode() {
+        let hedging = terminal(AccountMarginMode::RetailHedging);
+        hedging.close_position(1).unwrap();
+        let request = hedging.sent_requests().pop().unwrap();
+        assert_eq!(request.r#type, Some(OrderType::SELL));
+        assert_eq!(request.price, Some(1.10000));
+        assert_eq!(request.position, Some(1));
+        assert_eq!(request.type_filling, Some(OrderTypeFilling::IOC));
+
+        let netting = terminal(AccountMarginMode::RetailNetting);
+        netting.close_partial(2, 0.05).unwrap();
+        let request = netting.sent_requests().pop().unwrap();
+        assert_eq!(request.r#type, Some(OrderType::BUY));
+        assert_eq!(request.price, Some(1.10020));
+        assert_eq!(request.volume, Some(0.05));
+        assert_eq!(request.position, None);
+
+        let error = netting.close_partial(2, 0.5).unwrap_err();
+        assert!(matches!(error, FishingLineError::InvalidRequest(_)));
+        let error = netting.close_position(99).unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+    }
+
+    #[test]
+    fn test_reverse_position() {
+        let netting = terminal(AccountMarginMode::RetailNetting);
+        let reversal = netting.reverse_position(1).unwrap();
+        assert!(reversal.close.is_none() && reversal.open.is_ok());
+        let requests = netting.sent_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].r#type, Some(OrderType::SELL));
+        assert_eq!(requests[0].volume, Some(0.6));
+
+        let hedging = terminal(AccountMarginMode::RetailHedging);
+        let reversal = hedging.reverse_position(1).unwrap();
+        assert!(reversal.close.is_some() && reversal.open.is_ok());
+        let requests = hedging.sent_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].position, Some(1));
+        assert_eq!(
+            (requests[1].r#type, requests[1].volume, requests[1].position),
+            (Some(OrderType::SELL), Some(0.3), None)
+        );
+    }
+
+    #[test]
+    fn test_reverse_position_reports_failed_open() {
+        let terminal = terminal(AccountMarginMode::RetailHedging);
+        let mut sent = 0;
+        terminal.on_order_send(move |_| {
+            sent += 1;
+            match sent {
+                1 => Ok(TradeResult {
+                    retcode: ReturnCode::DONE,
+                    ..Default::default()
+                }),
+                _ => Err(FishingLineError::runtime(
+                    RuntimeError::InternalFailTimeout,
+                    "Timeout",
+                )),
+            }
+        });
+        let reversal = terminal.reverse_position(1).unwrap();
+        assert!(reversal.close.is_some());
+        assert_eq!(
+            reversal.open.unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert_eq!(terminal.sent_requests().len(), 2);
+    }
+
+    #[test]
+    fn test_volume_above_volume_max_is_not_capped() {
+        let terminal = terminal(AccountMarginMode::RetailNetting);
+        terminal.set_positions(vec![position(1, PositionType::BUY, 60.0)]);
+        let volume_max = |error: FishingLineError| match error {
+            FishingLineError::InvalidRequest(violations) => match violations[..] {
+                [TradeViolation::VolumeOutOfRange { volume, max, .. }] => (volume, max),
+                _ => panic!("Unexpected violations {:?}", violations),
+            },
+            other => panic!("Unexpected error {:?}", other),
+        };
+
+        terminal.close_position(1).unwrap();
+        terminal.set_positions(vec![position(1, PositionType::BUY, 150.0)]);
+        assert_eq!(
+            volume_max(terminal.close_position(1).unwrap_err()),
+            (150.0, 100.0)
+        );
+        let results = terminal.close_all(&TradeFilter::new()).unwrap();
+        assert_eq!(
+            volume_max(results[0].1.clone().unwrap_err()),
+            (150.0, 100.0)
+        );
+        terminal.set_positions(vec![position(1, PositionType::BUY, 60.0)]);
+        assert_eq!(
+            volume_max(terminal.reverse_position(1).unwrap_err()),
+            (120.0, 100.0)
+        );
+        assert_eq!(terminal.sent_requests().len(), 1);
+        terminal.close_partial(1, 40.0).unwrap();
+        assert_eq!(terminal.sent_requests()[1].volume, Some(40.0));
+    }
+
+    #[test]
+    fn test_close_by_requires_hedging() {
+        let hedging = terminal(AccountMarginMode::RetailHedging);
+        hedging.close_by(1, 2).unwrap();
+        let request = hedging.sent_requests().pop().unwrap();
+        assert_eq!(request.action, Some(TradeActionRequest::CloseBy));
+        assert_eq!((request.position, request.position_by), (Some(1), Some(2)));
+
+        let netting = terminal(AccountMarginMode::RetailNetting);
+        match netting.close_by(1, 2).unwrap_err() {
+            FishingLineError::InvalidRequest(violations) => {
+                assert_eq!(violations, vec![TradeViolation::HedgingRequired])
+            }
+            other => panic!("Unexpected error {:?}", other),
+        }
+        assert!(netting.sent_requests().is_empty());
+    }
+
+    #[test]
+    fn test_close_all_reports_each_position() {
+        let terminal = terminal(AccountMarginMode::RetailHedging);
+        terminal.fail_next("order_send", RuntimeError::InternalFailTimeout, "Timeout");
+        let results = terminal
+            .close_all(&TradeFilter::new().symbol("EURUSD"))
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].1.as_ref().unwrap_err().code(),
+            Some(RuntimeError::InternalFailTimeout)
+        );
+        assert!(results[1].1.is_ok());
+        assert_eq!(terminal.sent_requests().len(), 1);
+    }
+
+    #[test]
+    fn test_modify_position_stops_skips_no_op() {
+        let terminal = terminal(AccountMarginMode::RetailHedging);
+        terminal.set_positions(vec![Position {
+            sl: 1.09800,
+            ..position(1, PositionType::BUY, 0.3)
+        }]);
+        assert!(terminal
+            .modify_position_stops(1, 1.098001, 0.0)
+            .unwrap()
+            .is_none());
+        assert!(terminal.sent_requests().is_empty());
+
+        terminal
+            .modify_position_stops(1, 1.09750, 1.10300)
+            .unwrap()
+            .unwrap();
+        let request = terminal.sent_requests().pop().unwrap();
+        assert_eq!(request.action, Some(TradeActionRequest::SLTP));
+        assert_eq!((request.sl, request.tp), (Some(1.0975), Some(1.103)));
+        assert_eq!(request.r#type, None);
+
+        match terminal.modify_position_stops(1, 1.09999, 0.0).unwrap_err() {
+            FishingLineError::InvalidRequest(violations) => assert!(matches!(
+                violations[..],
+                [TradeViolation::TooClose { field: "sl", .. }]
+            )),
+            other => panic!("Unexpected error {:?}", other),
+        }
+
+        // Below the market is a valid stop loss for a buy, but not for a sell.
+        terminal.set_positions(vec![position(2, PositionType::SELL, 0.3)]);
+        match terminal.modify_position_stops(2, 1.09500, 0.0).unwrap_err() {
+            FishingLineError::InvalidRequest(violations) => assert!(matches!(
+                violations[..],
+                [TradeViolation::TooClose { field: "sl", .. }]
+            )),
+            other => panic!("Unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_modify_and_cancel_pending() {
+        let terminal = terminal(AccountMarginMode::RetailHedging).with_order(Order {
+            ticket: 5,
+            r#type: OrderType::BuyLimit,
+            price_open: 1.09500,
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        });
+        assert!(terminal
+            .modify_pending(5, 1.09500, 0.0, 0.0, None)
+            .unwrap()
+            .is_none());
+        assert!(terminal.sent_requests().is_empty());
+
+        let expiration = Utc::now() + TimeDelta::hours(1);
+        terminal
+            .modify_pending(5, 1.09400, 1.09000, 0.0, Some(expiration))
+            .unwrap()
+            .unwrap();
+        let request = terminal.sent_requests().pop().unwrap();
+        assert_eq!(request.action, Some(TradeActionRequest::MODIFY));
+        assert_eq!((request.order, request.price), (Some(5), Some(1.094)));
+        assert_eq!(request.type_time, Some(OrderTypeTime::SPECIFIED));
+        assert_eq!(request.r#type, None);
+
+        terminal.cancel_pending(5).unwrap();
+        let request = terminal.sent_requests().pop().unwrap();
+        assert_eq!(request.action, Some(TradeActionRequest::REMOVE));
+        assert_eq!(request.order, Some(5));
+        let error = terminal.cancel_pending(6).unwrap_err();
+        assert_eq!(error.code(), Some(RuntimeError::NotFound));
+    }
+
+    #[test]
+    fn test_frozen_pending_order_is_not_sent() {
+        let terminal = terminal(AccountMarginMode::RetailHedging)
+            .with_symbol(SymbolInfo {
+                trade_freeze_level: 30,
+                ..eurusd()
+            })
+            .with_order(Order {
+                ticket: 5,
+                r#type: OrderType::BuyLimit,
+                price_open: 1.10000,
+                symbol: "EURUSD".to_string(),
+                ..Default::default()
+            });
+
+        for error in [
+            terminal
+                .modify_pending(5, 1.09500, 0.0, 0.0, None)
+                .unwrap_err(),
+            terminal.cancel_pending(5).unwrap_err(),
+        ] {
+            match error {
+                FishingLineError::InvalidRequest(violations) => {
+                    assert!(matches!(
+                        violations[..],
+                        [TradeViolation::Frozen { price, reference, .. }]
+                            if price == 1.1 && reference == 1.1002
+                    ));
+                    assert_eq!(violations[0].retcode(), ReturnCode::FROZEN);
+                }
+                other => panic!("Unexpected error {:?}", other),
+            }
+        }
+        assert!(terminal.sent_requests().is_empty());
+    }
+}
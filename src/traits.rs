@@ -1,11 +1,9 @@
-use std::any::TypeId;
-
-use crate::enums::{AccountInfoProperty, SymbolInfoProperty, TerminalInfoProperty};
-use crate::prelude::{MQLError, MQLResult};
+use crate::enums::{AccountInfoProperty, RuntimeError, SymbolInfoProperty, TerminalInfoProperty};
+use crate::prelude::MQLResult;
 use crate::schemas::{
     AccountCredentials, AccountInfo, Deals, Order, Position, TerminalInfo, TerminalVersion,
 };
-use chrono::{DateTime, Local};
+use chrono::{DateTime, TimeZone, Utc};
 
 pub trait AccountInfoTrait {
     fn account_info(&self) -> MQLResult<AccountInfo>;
@@ -42,7 +40,7 @@ pub trait ConnectionTrait<T> {
     fn shutdown(self) -> MQLResult<()>;
 }
 pub trait ErrorTrait {
-    fn last_error(&self) -> MQLError;
+    fn last_error(&self) -> MQLResult<(RuntimeError, String)>;
 }
 
 pub trait SymbolInfoTrait {
@@ -58,7 +56,7 @@ pub trait SymbolRatesTrait {
         &self,
         symbol: &str,
         timeframe: crate::enums::Timeframe,
-        date_from: DateTime<Local>,
+        date_from: DateTime<Utc>,
         count: i32,
     ) -> MQLResult<Vec<crate::schemas::SymbolRates>>;
     fn copy_rates_from_pos(
@@ -72,8 +70,8 @@ pub trait SymbolRatesTrait {
         &self,
         symbol: &str,
         timeframe: crate::enums::Timeframe,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<crate::schemas::SymbolRates>>;
 }
 
@@ -81,22 +79,89 @@ pub trait SymbolTicksTrait {
     fn copy_ticks_from(
         &self,
         symbol: &str,
-        date_from: DateTime<Local>,
+        date_from: DateTime<Utc>,
         count: i32,
         flags: crate::enums::CopyTicksFlags,
     ) -> MQLResult<Vec<crate::schemas::SymbolTick>>;
     fn copy_ticks_range(
         &self,
         symbol: &str,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
         flags: crate::enums::CopyTicksFlags,
     ) -> MQLResult<Vec<crate::schemas::SymbolTick>>;
 }
 
+/// Columnar variants of [`SymbolRatesTrait`] returning Arrow record batches.
+///
+/// Columns are `time` (UTC seconds), `open`, `high`, `low`, `close`, `tick_volume`,
+/// `spread` and `real_volume`, see [`rates_schema`](crate::connection::arrow::rates_schema).
+#[cfg(feature = "arrow")]
+pub trait SymbolRatesArrowTrait {
+    fn copy_rates_from_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Utc>,
+        count: i32,
+    ) -> MQLResult<arrow_array::RecordBatch>;
+    fn copy_rates_from_pos_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        start_pos: i32,
+        count: i32,
+    ) -> MQLResult<arrow_array::RecordBatch>;
+    fn copy_rates_range_arrow(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> MQLResult<arrow_array::RecordBatch>;
+}
+
+/// Columnar variants of [`SymbolTicksTrait`] returning Arrow record batches.
+///
+/// Columns are `time` (UTC seconds), `bid`, `ask`, `last`, `volume`, `time_msc`
+/// (UTC milliseconds), `flags` and `volume_real`, see
+/// [`ticks_schema`](crate::connection::arrow::ticks_schema).
+#[cfg(feature = "arrow")]
+pub trait SymbolTicksArrowTrait {
+    fn copy_ticks_from_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        count: i32,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<arrow_array::RecordBatch>;
+    fn copy_ticks_range_arrow(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<arrow_array::RecordBatch>;
+}
+
 pub trait OrderTrait {
     fn orders_total(&self) -> MQLResult<i64>;
     fn orders_get(&self) -> MQLResult<Vec<crate::schemas::Order>>;
+    /// Open orders matching `filter`.
+    fn orders_get_filtered(
+        &self,
+        filter: &crate::schemas::TradeFilter,
+    ) -> MQLResult<Vec<crate::schemas::Order>> {
+        Ok(self
+            .orders_get()?
+            .into_iter()
+            .filter(|order| filter.matches_order(order))
+            .collect())
+    }
+    /// Number of open orders matching `filter`.
+    fn orders_total_filtered(&self, filter: &crate::schemas::TradeFilter) -> MQLResult<i64> {
+        Ok(self.orders_get_filtered(filter)?.len() as i64)
+    }
     fn order_calc_margin(
         &self,
         action: crate::enums::TradeActionRequest,
@@ -122,30 +187,407 @@ pub trait OrderTrait {
     ) -> MQLResult<crate::schemas::TradeResult>;
 }
 
+pub trait MarketBookTrait {
+    /// Subscribes to market depth change events for `symbol`.
+    fn market_book_add(&self, symbol: &str) -> MQLResult<bool>;
+    fn market_book_get(&self, symbol: &str) -> MQLResult<crate::schemas::MarketBook>;
+    /// Cancels the subscription made by `market_book_add`.
+    fn market_book_release(&self, symbol: &str) -> MQLResult<bool>;
+
+    /// Subscribes to `symbol`'s market depth until the returned guard is dropped.
+    fn market_book_subscribe(&self, symbol: &str) -> MQLResult<MarketBookSubscription<'_, Self>>
+    where
+        Self: Sized,
+    {
+        MarketBookSubscription::new(self, symbol)
+    }
+}
+
+/// Market depth subscription that calls `market_book_release` when dropped.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+/// let connection = MT5PythonConnection::new()
+///     .and_then(|connection| connection.initialize(terminal_path.as_str()))
+///     .expect("Unable to connect to terminal");
+///
+/// let subscription = connection
+///     .market_book_subscribe("EURUSD")
+///     .expect("Unable to subscribe to market depth");
+/// let book = subscription.get().expect("Unable to get market depth");
+/// println!("imbalance: {:?}", book.imbalance(5));
+/// ```
+pub struct MarketBookSubscription<'a, T: MarketBookTrait + ?Sized> {
+    terminal: &'a T,
+    symbol: String,
+}
+
+impl<'a, T: MarketBookTrait + ?Sized> MarketBookSubscription<'a, T> {
+    pub fn new(terminal: &'a T, symbol: &str) -> MQLResult<Self> {
+        if !terminal.market_book_add(symbol)? {
+            return Err(crate::error::FishingLineError::runtime(
+                RuntimeError::Fail,
+                format!("Unable to subscribe to `{}` market depth", symbol),
+            ));
+        }
+        Ok(MarketBookSubscription {
+            terminal,
+            symbol: symbol.to_string(),
+        })
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn get(&self) -> MQLResult<crate::schemas::MarketBook> {
+        self.terminal.market_book_get(&self.symbol)
+    }
+}
+
+impl<T: MarketBookTrait + ?Sized> Drop for MarketBookSubscription<'_, T> {
+    fn drop(&mut self) {
+        let _ = self.terminal.market_book_release(&self.symbol);
+    }
+}
+
 pub trait PositionTrait {
     fn positions_total(&self) -> MQLResult<i64>;
     fn positions_get(&self) -> MQLResult<Vec<Position>>;
+    /// Open positions matching `filter`.
+    fn positions_get_filtered(
+        &self,
+        filter: &crate::schemas::TradeFilter,
+    ) -> MQLResult<Vec<Position>> {
+        Ok(self
+            .positions_get()?
+            .into_iter()
+            .filter(|position| filter.matches_position(position))
+            .collect())
+    }
+    /// Number of open positions matching `filter`.
+    fn positions_total_filtered(&self, filter: &crate::schemas::TradeFilter) -> MQLResult<i64> {
+        Ok(self.positions_get_filtered(filter)?.len() as i64)
+    }
 }
 
 pub trait HistoryTrait {
     fn history_orders_total(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<i64>;
     fn history_orders_get(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<Order>>;
     fn history_deals_total(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<i64>;
     fn history_deals_get(
         &self,
-        date_from: DateTime<Local>,
-        date_to: DateTime<Local>,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
     ) -> MQLResult<Vec<Deals>>;
+    /// Historical orders selected by date range and group, ticket or position.
+    fn history_orders_query(
+        &self,
+        query: &crate::schemas::HistoryQuery,
+    ) -> MQLResult<Vec<crate::schemas::Order>>;
+    /// Deals selected by date range and group, order ticket or position.
+    fn history_deals_query(&self, query: &crate::schemas::HistoryQuery) -> MQLResult<Vec<Deals>>;
+
+    /// The historical order with `ticket`, if any.
+    fn history_order(&self, ticket: usize) -> MQLResult<Option<crate::schemas::Order>> {
+        Ok(self
+            .history_orders_query(&crate::schemas::HistoryQuery::Ticket(ticket))?
+            .into_iter()
+            .next())
+    }
+
+    /// Every order and deal of the position identified by `position`.
+    fn history_position(&self, position: usize) -> MQLResult<crate::schemas::PositionHistory> {
+        let query = crate::schemas::HistoryQuery::Position(position);
+        Ok(crate::schemas::PositionHistory {
+            orders: self.history_orders_query(&query)?,
+            deals: self.history_deals_query(&query)?,
+        })
+    }
+}
+
+/// Variants of the rates, ticks and history calls taking times in any time zone.
+///
+/// The capability traits take `DateTime<Utc>` so they stay object-safe. These
+/// methods convert a `DateTime<Tz>` to UTC and forward to them, and are available
+/// on every backend, `dyn Terminal` included.
+///
+/// ```rust,no_run
+/// use chrono::{FixedOffset, TimeZone};
+/// use fishing_line::prelude::*;
+///
+/// # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+/// let new_york = FixedOffset::west_opt(4 * 3600).unwrap();
+/// let open = new_york.with_ymd_and_hms(2024, 7, 8, 9, 30, 0).unwrap();
+/// let close = new_york.with_ymd_and_hms(2024, 7, 8, 16, 0, 0).unwrap();
+/// let bars = terminal.copy_rates_range_tz("US500", Timeframe::M5, open, close)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait ZonedTimeTrait: SymbolRatesTrait + SymbolTicksTrait + HistoryTrait {
+    fn copy_rates_from_tz<Tz: TimeZone>(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Tz>,
+        count: i32,
+    ) -> MQLResult<Vec<crate::schemas::SymbolRates>> {
+        self.copy_rates_from(symbol, timeframe, date_from.to_utc(), count)
+    }
+    fn copy_rates_range_tz<Tz: TimeZone>(
+        &self,
+        symbol: &str,
+        timeframe: crate::enums::Timeframe,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+    ) -> MQLResult<Vec<crate::schemas::SymbolRates>> {
+        self.copy_rates_range(symbol, timeframe, date_from.to_utc(), date_to.to_utc())
+    }
+    fn copy_ticks_from_tz<Tz: TimeZone>(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Tz>,
+        count: i32,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<Vec<crate::schemas::SymbolTick>> {
+        self.copy_ticks_from(symbol, date_from.to_utc(), count, flags)
+    }
+    fn copy_ticks_range_tz<Tz: TimeZone>(
+        &self,
+        symbol: &str,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+        flags: crate::enums::CopyTicksFlags,
+    ) -> MQLResult<Vec<crate::schemas::SymbolTick>> {
+        self.copy_ticks_range(symbol, date_from.to_utc(), date_to.to_utc(), flags)
+    }
+    fn history_orders_total_tz<Tz: TimeZone>(
+        &self,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+    ) -> MQLResult<i64> {
+        self.history_orders_total(date_from.to_utc(), date_to.to_utc())
+    }
+    fn history_orders_get_tz<Tz: TimeZone>(
+        &self,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+    ) -> MQLResult<Vec<Order>> {
+        self.history_orders_get(date_from.to_utc(), date_to.to_utc())
+    }
+    fn history_deals_total_tz<Tz: TimeZone>(
+        &self,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+    ) -> MQLResult<i64> {
+        self.history_deals_total(date_from.to_utc(), date_to.to_utc())
+    }
+    fn history_deals_get_tz<Tz: TimeZone>(
+        &self,
+        date_from: DateTime<Tz>,
+        date_to: DateTime<Tz>,
+    ) -> MQLResult<Vec<Deals>> {
+        self.history_deals_get(date_from.to_utc(), date_to.to_utc())
+    }
+}
+
+impl<T: SymbolRatesTrait + SymbolTicksTrait + HistoryTrait + ?Sized> ZonedTimeTrait for T {}
+
+/// Backend-agnostic facade over every terminal capability.
+///
+/// `Terminal` is object-safe, so strategy code can hold a `Box<dyn Terminal>` or
+/// `Arc<dyn Terminal + Send + Sync>` and stay independent of the backend behind it.
+/// It is implemented automatically for any type that implements all of the
+/// capability traits it combines, [`MT5PythonConnection`](crate::prelude::MT5PythonConnection)
+/// included. Connection management stays on [`ConnectionTrait`] because it consumes
+/// the backend.
+///
+/// ```rust,no_run
+/// use fishing_line::prelude::*;
+///
+/// fn spread(terminal: &dyn Terminal, symbol: &str) -> MQLResult<f64> {
+///     let tick = terminal.symbol_info_tick(symbol)?;
+///     Ok(tick.ask - tick.bid)
+/// }
+///
+/// let terminal_path = std::env::var("TERMINAL_PATH").unwrap();
+/// let terminal: Box<dyn Terminal> = Box::new(
+///     MT5PythonConnection::new()
+///         .and_then(|connection| connection.initialize(terminal_path.as_str()))
+///         .expect("Unable to connect to terminal"),
+/// );
+/// println!("spread: {:?}", spread(terminal.as_ref(), "BTCUSD"));
+/// ```
+pub trait Terminal:
+    ErrorTrait
+    + AccountInfoTrait
+    + TerminalInfoTrait
+    + SymbolInfoTrait
+    + SymbolRatesTrait
+    + SymbolTicksTrait
+    + OrderTrait
+    + PositionTrait
+    + HistoryTrait
+{
+}
+
+impl<T> Terminal for T where
+    T: ErrorTrait
+        + AccountInfoTrait
+        + TerminalInfoTrait
+        + SymbolInfoTrait
+        + SymbolRatesTrait
+        + SymbolTicksTrait
+        + OrderTrait
+        + PositionTrait
+        + HistoryTrait
+        + ?Sized
+{
+}
+
+#[cfg(test)]
+mod test {
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_market_book_subscription_releases_on_drop() {
+        let terminal = MockTerminal::new().with_symbol(eurusd()).with_market_book(
+            "EURUSD",
+            vec![
+                BookEntry {
+                    r#type: BookType::SELL,
+                    price: 1.1002,
+                    volume: 3,
+                    volume_real: 3.0,
+                },
+                BookEntry {
+                    r#type: BookType::BUY,
+                    price: 1.1000,
+                    volume: 1,
+                    volume_real: 1.0,
+                },
+            ],
+        );
+        assert!(
+            terminal.market_book_get("EURUSD").is_err(),
+            "Book was readable without a subscription"
+        );
+        {
+            let subscription = terminal.market_book_subscribe("EURUSD").unwrap();
+            assert!(
+                terminal.is_book_subscribed("EURUSD"),
+                "Book was not subscribed"
+            );
+            let book = subscription.get().unwrap();
+            assert_eq!(book.best_ask().unwrap().price, 1.1002);
+            assert_eq!(book.imbalance(1), Some(-0.5));
+        }
+        assert!(
+            !terminal.is_book_subscribed("EURUSD"),
+            "Book was not released"
+        );
+    }
+
+    #[test]
+    fn test_positions_get_filtered() {
+        let position = |ticket: isize, symbol: &str, magic: isize, comment: &str| Position {
+            ticket,
+            symbol: symbol.to_string(),
+            magic,
+            comment: comment.to_string(),
+            ..Default::default()
+        };
+        let terminal = MockTerminal::new()
+            .with_position(position(1, "EURUSD", 7, "grid #1"))
+            .with_position(position(2, "GBPUSD", 7, "manual"))
+            .with_position(Position {
+                r#type: PositionType::SELL,
+                ..position(3, "USDJPY", 7, "grid #2")
+            })
+            .with_position(position(4, "EURUSD", 8, "grid #3"));
+
+        let tickets = |filter: TradeFilter| -> Vec<isize> {
+            terminal
+                .positions_get_filtered(&filter)
+                .unwrap()
+                .into_iter()
+                .map(|position| position.ticket)
+                .collect()
+        };
+        assert_eq!(tickets(TradeFilter::new().symbol("EURUSD")), vec![1, 4]);
+        assert_eq!(
+            tickets(TradeFilter::new().group("*USD*,!USD*")),
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            tickets(TradeFilter::new().magic(7).comment("grid")),
+            vec![1, 3]
+        );
+        assert_eq!(
+            tickets(TradeFilter::new().position_type(PositionType::SELL)),
+            vec![3]
+        );
+        assert_eq!(tickets(TradeFilter::new().ticket(2)), vec![2]);
+        assert_eq!(
+            terminal
+                .positions_total_filtered(&TradeFilter::new().magic(8))
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_history_position() {
+        let order = |ticket: isize, position_id: isize| Order {
+            ticket,
+            position_id,
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        };
+        let deal = |order: isize, entry: DealEntry, profit: f64| Deals {
+            order,
+            entry,
+            profit,
+            position_id: 10,
+            volume: 0.1,
+            commission: -0.5,
+            symbol: "EURUSD".to_string(),
+            ..Default::default()
+        };
+        let terminal = MockTerminal::new()
+            .with_history_order(order(10, 10))
+            .with_history_order(order(11, 10))
+            .with_history_order(order(12, 12))
+            .with_deal(deal(10, DealEntry::IN, 0.0))
+            .with_deal(deal(11, DealEntry::OUT, 12.0));
+
+        let history = terminal.history_position(10).unwrap();
+        assert_eq!(history.orders.len(), 2);
+        assert_eq!(history.deals.len(), 2);
+        assert!(history.is_closed(), "Position was not closed");
+        assert_eq!(history.net_profit(), 11.0);
+
+        assert_eq!(terminal.history_order(12).unwrap().unwrap().position_id, 12);
+        assert!(terminal.history_order(13).unwrap().is_none());
+        let deals = terminal
+            .history_deals_query(&HistoryQuery::Ticket(11))
+            .unwrap();
+        assert_eq!(deals.len(), 1);
+        assert_eq!(deals[0].entry, DealEntry::OUT);
+    }
 }
@@ -0,0 +1,1006 @@
+//! Client-side checks and normalization of trade requests.
+//!
+//! [`validate`] applies the symbol and account rules the trade server enforces, so
+//! a request that would come back as `InvalidVolume`, `InvalidStops`, `InvalidFill`
+//! and the like is caught before `order_send`. [`TradeRequestBuilder::normalize`]
+//! fixes the part of them that has a single right answer: tick-aligned prices, a
+//! volume on the step and a filling type the symbol accepts.
+//!
+//! ```rust,no_run
+//! use fishing_line::prelude::*;
+//!
+//! # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+//! let symbol = terminal.symbol_info("EURUSD")?;
+//! let account = terminal.account_info()?;
+//! let request: TradeRequestBuilder = MarketOrder::buy("EURUSD", 0.015).sl(symbol.bid).into();
+//! for violation in validate(&request, &symbol, &account) {
+//!     println!("{} ({})", violation, violation.retcode());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use crate::orders::OrderSide;
+use crate::prelude::*;
+
+/// `SYMBOL_FILLING_FOK` flag of `SymbolInfo::filling_mode`.
+const FILLING_FOK: i64 = 1;
+/// `SYMBOL_FILLING_IOC` flag of `SymbolInfo::filling_mode`.
+const FILLING_IOC: i64 = 2;
+
+/// Tolerance, relative to a step, of volume and price alignment checks.
+const EPSILON: f64 = 1e-7;
+
+/// A rule of the symbol or account that a trade request breaks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeViolation {
+    /// Trading is disabled on the account.
+    TradeDisabled,
+    /// The symbol's trade mode forbids this request.
+    SymbolTradeMode(SymbolTradeMode),
+    /// The action needs a field the request leaves unset.
+    MissingField(&'static str),
+    /// The order type does not belong to the action, such as a `PENDING` `BUY`.
+    UnexpectedOrderType {
+        action: TradeActionRequest,
+        r#type: Option<OrderType>,
+    },
+    /// The symbol's order mode does not allow the order type.
+    OrderTypeNotAllowed(OrderType),
+    /// The symbol's order mode does not allow setting `sl` or `tp`.
+    StopNotAllowed(&'static str),
+    /// Close-by requests need a hedging account.
+    HedgingRequired,
+    /// Close-by requests need two opposite positions of the same symbol.
+    CloseByMismatch,
+    VolumeOutOfRange {
+        volume: f64,
+        min: f64,
+        max: f64,
+    },
+    /// The volume is not a multiple of `volume_step`.
+    VolumeStep {
+        volume: f64,
+        step: f64,
+    },
+    /// A price is not a multiple of the symbol's tick size.
+    PriceNotNormalized {
+        field: &'static str,
+        price: f64,
+        tick_size: f64,
+    },
+    /// A price is on the wrong side of, or closer than the stops or freeze level
+    /// to, the price it is measured from.
+    TooClose {
+        field: &'static str,
+        price: f64,
+        reference: f64,
+        min_distance: f64,
+    },
+    /// A pending order's trigger price is within the freeze level of the market, so
+    /// the order can be neither modified nor deleted.
+    Frozen {
+        price: f64,
+        reference: f64,
+        freeze_distance: f64,
+    },
+    /// The symbol's filling mode and execution mode forbid the filling type.
+    FillingNotAllowed(OrderTypeFilling),
+    /// The symbol's expiration mode forbids the order lifetime.
+    ExpirationNotAllowed(OrderTypeTime),
+}
+
+impl TradeViolation {
+    /// Return code the trade server answers the request with.
+    pub fn retcode(&self) -> ReturnCode {
+        match self {
+            TradeViolation::TradeDisabled => ReturnCode::TradeDisabled,
+            TradeViolation::SymbolTradeMode(mode) => match mode {
+                SymbolTradeMode::SymbolTradeModeLongonly => ReturnCode::LongOnly,
+                SymbolTradeMode::SymbolTradeModeShortonly => ReturnCode::ShortOnly,
+                SymbolTradeMode::SymbolTradeModeCloseonly => ReturnCode::CloseOnly,
+                _ => ReturnCode::TradeDisabled,
+            },
+            TradeViolation::MissingField(_)
+            | TradeViolation::UnexpectedOrderType { .. }
+            | TradeViolation::OrderTypeNotAllowed(_)
+            | TradeViolation::HedgingRequired
+            | TradeViolation::CloseByMismatch => ReturnCode::INVALID,
+            TradeViolation::StopNotAllowed(_) => ReturnCode::InvalidStops,
+            TradeViolation::VolumeOutOfRange { .. } | TradeViolation::VolumeStep { .. } => {
+                ReturnCode::InvalidVolume
+            }
+            TradeViolation::PriceNotNormalized { field, .. }
+            | TradeViolation::TooClose { field, .. } => match *field {
+                "sl" | "tp" => ReturnCode::InvalidStops,
+                _ => ReturnCode::InvalidPrice,
+            },
+            TradeViolation::Frozen { .. } => ReturnCode::FROZEN,
+            TradeViolation::FillingNotAllowed(_) => ReturnCode::InvalidFill,
+            TradeViolation::ExpirationNotAllowed(_) => ReturnCode::InvalidExpiration,
+        }
+    }
+}
+
+impl fmt::Display for TradeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradeViolation::TradeDisabled => write!(f, "Trading is disabled on the account"),
+            TradeViolation::SymbolTradeMode(mode) => {
+                write!(f, "Symbol trade mode {:?} forbids this request", mode)
+            }
+            TradeViolation::MissingField(field) => write!(f, "Missing `{}`", field),
+            TradeViolation::UnexpectedOrderType { action, r#type } => {
+                write!(
+                    f,
+                    "Order type {:?} does not match action {:?}",
+                    r#type, action
+                )
+            }
+            TradeViolation::OrderTypeNotAllowed(r#type) => {
+                write!(f, "Order type {:?} is not allowed for the symbol", r#type)
+            }
+            TradeViolation::StopNotAllowed(field) => {
+                write!(f, "`{}` is not allowed for the symbol", field)
+            }
+            TradeViolation::HedgingRequired => {
+                write!(f, "Close by is only available on hedging accounts")
+            }
+            TradeViolation::CloseByMismatch => {
+                write!(f, "Close by needs opposite positions of the same symbol")
+            }
+            TradeViolation::VolumeOutOfRange { volume, min, max } => {
+                write!(f, "Volume {} is outside [{}, {}]", volume, min, max)
+            }
+            TradeViolation::VolumeStep { volume, step } => {
+                write!(f, "Volume {} is not a multiple of {}", volume, step)
+            }
+            TradeViolation::PriceNotNormalized {
+                field,
+                price,
+                tick_size,
+            } => write!(
+                f,
+                "`{}` {} is not a multiple of the tick size {}",
+                field, price, tick_size
+            ),
+            TradeViolation::TooClose {
+                field,
+                price,
+                reference,
+                min_distance,
+            } => write!(
+                f,
+                "`{}` {} must be at least {} away from {} on the right side",
+                field, price, min_distance, reference
+            ),
+            TradeViolation::Frozen {
+                price,
+                reference,
+                freeze_distance,
+            } => write!(
+                f,
+                "Order price {} is within the freeze distance {} of {}",
+                price, freeze_distance, reference
+            ),
+            TradeViolation::FillingNotAllowed(filling) => {
+                write!(
+                    f,
+                    "Filling type {:?} is not allowed for the symbol",
+                    filling
+                )
+            }
+            TradeViolation::ExpirationNotAllowed(type_time) => {
+                write!(
+                    f,
+                    "Order lifetime {:?} is not allowed for the symbol",
+                    type_time
+                )
+            }
+        }
+    }
+}
+
+/// Whether `value` is a whole multiple of `step`.
+pub(crate) fn is_multiple(value: f64, step: f64) -> bool {
+    let steps = value / step;
+    (steps - steps.round()).abs() < EPSILON * steps.abs().max(1.0)
+}
+
+/// Tick size prices must be multiples of: `trade_tick_size`, or the point when
+/// the symbol has none.
+pub(crate) fn tick_size(symbol: &SymbolInfo) -> f64 {
+    if symbol.trade_tick_size > 0.0 {
+        symbol.trade_tick_size
+    } else {
+        symbol.point
+    }
+}
+
+/// Whether the symbol accepts `filling` for a request with `action`.
+///
+/// Request and instant execution accept any filling type. Market execution fills
+/// market orders with FOK or IOC, as the symbol allows, and leaves the choice free
+/// for pending orders. Exchange execution accepts FOK and IOC when the symbol
+/// allows them, and RETURN always.
+pub(crate) fn filling_allowed(
+    symbol: &SymbolInfo,
+    action: TradeActionRequest,
+    filling: OrderTypeFilling,
+) -> bool {
+    let flags = symbol.filling_mode.value();
+    match symbol.trade_exemode {
+        SymbolTradeExecution::Request | SymbolTradeExecution::Instant => true,
+        SymbolTradeExecution::Market if action == TradeActionRequest::PENDING => true,
+        SymbolTradeExecution::Market => match filling {
+            OrderTypeFilling::FOK => flags & FILLING_FOK != 0,
+            OrderTypeFilling::IOC => flags & FILLING_IOC != 0,
+            _ => false,
+        },
+        _ => match filling {
+            OrderTypeFilling::FOK => flags & FILLING_FOK != 0,
+            OrderTypeFilling::IOC => flags & FILLING_IOC != 0,
+            OrderTypeFilling::RETURN => true,
+            OrderTypeFilling::Unknown(_) => false,
+        },
+    }
+}
+
+/// Side of a market or pending order type.
+pub(crate) fn order_side(r#type: OrderType) -> Option<OrderSide> {
+    match r#type {
+        OrderType::BUY | OrderType::BuyLimit | OrderType::BuyStop | OrderType::BuyStopLimit => {
+            Some(OrderSide::Buy)
+        }
+        OrderType::SELL | OrderType::SellLimit | OrderType::SellStop | OrderType::SellStopLimit => {
+            Some(OrderSide::Sell)
+        }
+        _ => None,
+    }
+}
+
+/// Checks that `sl` and `tp` of a `side` trade sit at least `min_distance` from
+/// `reference`, on the losing and winning side respectively. Unset levels are `0.0`.
+pub(crate) fn check_stops(
+    side: OrderSide,
+    reference: f64,
+    sl: f64,
+    tp: f64,
+    min_distance: f64,
+) -> Vec<TradeViolation> {
+    let mut violations = Vec::new();
+    let too_close = |field, price| TradeViolation::TooClose {
+        field,
+        price,
+        reference,
+        min_distance,
+    };
+    let (sl_ok, tp_ok) = match side {
+        OrderSide::Buy => (
+            sl <= reference - min_distance + EPSILON,
+            tp >= reference + min_distance - EPSILON,
+        ),
+        OrderSide::Sell => (
+            sl >= reference + min_distance - EPSILON,
+            tp <= reference - min_distance + EPSILON,
+        ),
+    };
+    if sl > 0.0 && !sl_ok {
+        violations.push(too_close("sl", sl));
+    }
+    if tp > 0.0 && !tp_ok {
+        violations.push(too_close("tp", tp));
+    }
+    violations
+}
+
+/// Checks that the pending `order` is not frozen: its trigger price must be at least
+/// the symbol's freeze level from the ask for buy orders, from the bid for sell
+/// orders. Skipped without a quote.
+pub(crate) fn check_frozen(order: &Order, symbol: &SymbolInfo) -> Option<TradeViolation> {
+    let reference = match order_side(order.r#type)? {
+        OrderSide::Buy => symbol.ask,
+        OrderSide::Sell => symbol.bid,
+    };
+    let freeze_distance = symbol.trade_freeze_level as f64 * symbol.point;
+    let frozen = reference > 0.0
+        && freeze_distance > 0.0
+        && (reference - order.price_open).abs() < freeze_distance - EPSILON;
+    frozen.then_some(TradeViolation::Frozen {
+        price: order.price_open,
+        reference,
+        freeze_distance,
+    })
+}
+
+/// Side of a position or order from where its levels sit around `reference`.
+fn infer_side(reference: f64, sl: f64, tp: f64) -> OrderSide {
+    if (sl > 0.0 && sl < reference) || (tp > 0.0 && tp > reference) {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    }
+}
+
+/// Checks `request` against the trading rules of `symbol` and `account` and returns
+/// every rule it breaks, in no particular order.
+///
+/// Distances to the market use the symbol's current `bid` and `ask` and are skipped
+/// without a quote. Modifications (`SLTP` and `MODIFY`) are held to the larger of
+/// the stops and freeze levels. The side of a position whose stops are modified is
+/// taken from the request's order type when set, otherwise from where the levels sit
+/// around the market; [`validate_with_type`] gives it explicitly.
+pub fn validate(
+    request: &TradeRequestBuilder,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> Vec<TradeViolation> {
+    check_request(request, request.r#type, symbol, account)
+}
+
+/// [`validate`] for a request about an existing position or pending order of
+/// `r#type`, such as an `SLTP` or `MODIFY`, which the server matches by ticket and
+/// which is sent without an order type. The request is checked as if it were of
+/// `r#type`, so its stops are held to the right side of the market.
+pub fn validate_with_type(
+    request: &TradeRequestBuilder,
+    r#type: OrderType,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> Vec<TradeViolation> {
+    check_request(request, Some(r#type), symbol, account)
+}
+
+/// Checks of [`validate`], with the order type taken from `r#type`.
+fn check_request(
+    request: &TradeRequestBuilder,
+    r#type: Option<OrderType>,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> Vec<TradeViolation> {
+    let mut violations = Vec::new();
+    let action = match request.action {
+        Some(action) => action,
+        None => return vec![TradeViolation::MissingField("action")],
+    };
+    if !account.trade_allowed {
+        violations.push(TradeViolation::TradeDisabled);
+    }
+
+    let mut require = |present: bool, field: &'static str| {
+        if !present {
+            violations.push(TradeViolation::MissingField(field));
+        }
+    };
+    match action {
+        TradeActionRequest::DEAL => {
+            require(request.symbol.is_some(), "symbol");
+            require(request.volume.is_some(), "volume");
+            // Request and instant execution fill at the requested price or requote.
+            if matches!(
+                symbol.trade_exemode,
+                SymbolTradeExecution::Request | SymbolTradeExecution::Instant
+            ) {
+                require(request.price.is_some(), "price");
+            }
+        }
+        TradeActionRequest::PENDING => {
+            require(request.symbol.is_some(), "symbol");
+            require(request.volume.is_some(), "volume");
+            require(request.price.is_some(), "price");
+            if matches!(
+                r#type,
+                Some(OrderType::BuyStopLimit | OrderType::SellStopLimit)
+            ) {
+                require(request.stoplimit.is_some(), "stoplimit");
+            }
+        }
+        TradeActionRequest::SLTP => {
+            require(request.symbol.is_some(), "symbol");
+            require(request.position.is_some(), "position");
+        }
+        TradeActionRequest::MODIFY => {
+            require(request.order.is_some(), "order");
+            require(request.price.is_some(), "price");
+        }
+        TradeActionRequest::REMOVE => require(request.order.is_some(), "order"),
+        TradeActionRequest::CloseBy => {
+            require(request.position.is_some(), "position");
+            require(request.position_by.is_some(), "position_by");
+        }
+        TradeActionRequest::Unknown(_) => {}
+    }
+    let type_matches = match action {
+        TradeActionRequest::DEAL => {
+            matches!(r#type, Some(OrderType::BUY | OrderType::SELL))
+        }
+        TradeActionRequest::PENDING => matches!(
+            r#type,
+            Some(
+                OrderType::BuyLimit
+                    | OrderType::SellLimit
+                    | OrderType::BuyStop
+                    | OrderType::SellStop
+                    | OrderType::BuyStopLimit
+                    | OrderType::SellStopLimit
+            )
+        ),
+        _ => true,
+    };
+    if !type_matches {
+        violations.push(TradeViolation::UnexpectedOrderType { action, r#type });
+    }
+
+    violations.extend(check_modes(request, r#type, action, symbol, account));
+    if matches!(
+        action,
+        TradeActionRequest::DEAL | TradeActionRequest::PENDING
+    ) {
+        if let Some(volume) = request.volume {
+            violations.extend(check_volume(volume, symbol));
+        }
+    }
+    violations.extend(check_prices(request, r#type, action, symbol));
+    violations
+}
+
+/// Trade mode, order mode, filling and expiration checks.
+fn check_modes(
+    request: &TradeRequestBuilder,
+    r#type: Option<OrderType>,
+    action: TradeActionRequest,
+    symbol: &SymbolInfo,
+    account: &AccountInfo,
+) -> Vec<TradeViolation> {
+    let mut violations = Vec::new();
+    let side = r#type.and_then(order_side);
+    let opening = match action {
+        TradeActionRequest::DEAL => request.position.is_none(),
+        TradeActionRequest::PENDING => true,
+        _ => false,
+    };
+    let forbidden = match symbol.trade_mode {
+        SymbolTradeMode::SymbolTradeModeDisabled => true,
+        SymbolTradeMode::SymbolTradeModeLongonly => opening && side == Some(OrderSide::Sell),
+        SymbolTradeMode::SymbolTradeModeShortonly => opening && side == Some(OrderSide::Buy),
+        SymbolTradeMode::SymbolTradeModeCloseonly => opening,
+        _ => false,
+    };
+    if forbidden {
+        violations.push(TradeViolation::SymbolTradeMode(symbol.trade_mode));
+    }
+
+    let order_mode = symbol.order_mode.value();
+    let allows = |mode: SymbolOrderMode| order_mode & mode.value() != 0;
+    let mode = match r#type {
+        Some(OrderType::BUY | OrderType::SELL) => Some(SymbolOrderMode::Market),
+        Some(OrderType::BuyLimit | OrderType::SellLimit) => Some(SymbolOrderMode::Limit),
+        Some(OrderType::BuyStop | OrderType::SellStop) => Some(SymbolOrderMode::Stop),
+        Some(OrderType::BuyStopLimit | OrderType::SellStopLimit) => {
+            Some(SymbolOrderMode::StopLimit)
+        }
+        _ => None,
+    };
+    match (action, mode, r#type) {
+        (TradeActionRequest::DEAL | TradeActionRequest::PENDING, Some(mode), Some(r#type))
+            if !allows(mode) =>
+        {
+            violations.push(TradeViolation::OrderTypeNotAllowed(r#type))
+        }
+        (TradeActionRequest::CloseBy, _, _) => {
+            if !allows(SymbolOrderMode::CloseBy) {
+                violations.push(TradeViolation::OrderTypeNotAllowed(OrderType::CloseBy));
+            }
+            if account.margin_mode != AccountMarginMode::RetailHedging {
+                violations.push(TradeViolation::HedgingRequired);
+            }
+        }
+        _ => {}
+    }
+    if request.sl.unwrap_or_default() > 0.0 && !allows(SymbolOrderMode::Sl) {
+        violations.push(TradeViolation::StopNotAllowed("sl"));
+    }
+    if request.tp.unwrap_or_default() > 0.0 && !allows(SymbolOrderMode::Tp) {
+        violations.push(TradeViolation::StopNotAllowed("tp"));
+    }
+
+    if matches!(
+        action,
+        TradeActionRequest::DEAL | TradeActionRequest::PENDING
+    ) {
+        // An unset field reaches the server as 0, `ORDER_FILLING_FOK`.
+        let filling = request.type_filling.unwrap_or(OrderTypeFilling::FOK);
+        if !filling_allowed(symbol, action, filling) {
+            violations.push(TradeViolation::FillingNotAllowed(filling));
+        }
+    }
+    if matches!(
+        action,
+        TradeActionRequest::PENDING | TradeActionRequest::MODIFY
+    ) {
+        // An unset field reaches the server as 0, `ORDER_TIME_GTC`.
+        let type_time = request.type_time.unwrap_or(OrderTypeTime::GTC);
+        let flag = match type_time {
+            OrderTypeTime::GTC => SymbolExpirationMode::Gtc,
+            OrderTypeTime::DAY => SymbolExpirationMode::Day,
+            OrderTypeTime::SPECIFIED => SymbolExpirationMode::Specified,
+            OrderTypeTime::SpecifiedDay => SymbolExpirationMode::SpecifiedDay,
+            OrderTypeTime::Unknown(value) => SymbolExpirationMode::Unknown(value),
+        };
+        if symbol.expiration_mode.value() & flag.value() == 0 {
+            violations.push(TradeViolation::ExpirationNotAllowed(type_time));
+        }
+        if matches!(
+            type_time,
+            OrderTypeTime::SPECIFIED | OrderTypeTime::SpecifiedDay
+        ) && request.expiration.is_none()
+        {
+            violations.push(TradeViolation::MissingField("expiration"));
+        }
+    }
+    violations
+}
+
+fn check_volume(volume: f64, symbol: &SymbolInfo) -> Vec<TradeViolation> {
+    let mut violations = Vec::new();
+    let (min, max) = (symbol.volume_min, symbol.volume_max);
+    if volume < min - EPSILON || (max > 0.0 && volume > max + EPSILON) {
+        violations.push(TradeViolation::VolumeOutOfRange { volume, min, max });
+    }
+    let step = symbol.volume_step;
+    if step > 0.0 && !is_multiple(volume, step) {
+        violations.push(TradeViolation::VolumeStep { volume, step });
+    }
+    violations
+}
+
+/// Tick alignment of every price and distance of prices and stops to the market.
+fn check_prices(
+    request: &TradeRequestBuilder,
+    r#type: Option<OrderType>,
+    action: TradeActionRequest,
+    symbol: &SymbolInfo,
+) -> Vec<TradeViolation> {
+    let mut violations = Vec::new();
+    let tick_size = tick_size(symbol);
+    let prices = [
+        ("price", request.price),
+        ("stoplimit", request.stoplimit),
+        ("sl", request.sl),
+        ("tp", request.tp),
+    ];
+    if tick_size > 0.0 {
+        for (field, price) in prices {
+            match price {
+                Some(price) if price > 0.0 && !is_multiple(price, tick_size) => {
+                    violations.push(TradeViolation::PriceNotNormalized {
+                        field,
+                        price,
+                        tick_size,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let levels = match action {
+        TradeActionRequest::SLTP | TradeActionRequest::MODIFY => {
+            symbol.trade_stops_level.max(symbol.trade_freeze_level)
+        }
+        _ => symbol.trade_stops_level,
+    };
+    let min_distance = levels as f64 * symbol.point;
+    let quoted = symbol.bid > 0.0 && symbol.ask > 0.0;
+    let sl = request.sl.unwrap_or_default();
+    let tp = request.tp.unwrap_or_default();
+    let side = r#type.and_then(order_side);
+    match action {
+        TradeActionRequest::DEAL if quoted => {
+            if let Some(side) = side {
+                let reference = match side {
+                    OrderSide::Buy => symbol.bid,
+                    OrderSide::Sell => symbol.ask,
+                };
+                violations.extend(check_stops(side, reference, sl, tp, min_distance));
+            }
+        }
+        TradeActionRequest::SLTP if quoted => {
+            let side = side.unwrap_or_else(|| infer_side(symbol.bid, sl, tp));
+            let reference = match side {
+                OrderSide::Buy => symbol.bid,
+                OrderSide::Sell => symbol.ask,
+            };
+            violations.extend(check_stops(side, reference, sl, tp, min_distance));
+        }
+        TradeActionRequest::PENDING | TradeActionRequest::MODIFY => {
+            if let Some(price) = request.price {
+                let side = side.unwrap_or_else(|| infer_side(price, sl, tp));
+                if quoted {
+                    violations.extend(check_pending_price(r#type, price, symbol, min_distance));
+                }
+                let limit = request.stoplimit.filter(|stoplimit| *stoplimit > 0.0);
+                if let Some(stoplimit) = limit {
+                    // A stop limit buys below, or sells above, its trigger price.
+                    let field_side = side.opposite();
+                    violations.extend(
+                        check_stops(field_side, price, stoplimit, 0.0, min_distance)
+                            .into_iter()
+                            .map(|violation| match violation {
+                                TradeViolation::TooClose {
+                                    price,
+                                    reference,
+                                    min_distance,
+                                    ..
+                                } => TradeViolation::TooClose {
+                                    field: "stoplimit",
+                                    price,
+                                    reference,
+                                    min_distance,
+                                },
+                                other => other,
+                            }),
+                    );
+                }
+                let reference = limit.unwrap_or(price);
+                violations.extend(check_stops(side, reference, sl, tp, min_distance));
+            }
+        }
+        _ => {}
+    }
+    violations
+}
+
+/// Checks the trigger price of a pending order against the market.
+fn check_pending_price(
+    r#type: Option<OrderType>,
+    price: f64,
+    symbol: &SymbolInfo,
+    min_distance: f64,
+) -> Option<TradeViolation> {
+    let (reference, below) = match r#type? {
+        OrderType::BuyLimit => (symbol.ask, true),
+        OrderType::SellLimit => (symbol.bid, false),
+        OrderType::BuyStop | OrderType::BuyStopLimit => (symbol.ask, false),
+        OrderType::SellStop | OrderType::SellStopLimit => (symbol.bid, true),
+        _ => return None,
+    };
+    let valid = if below {
+        price <= reference - min_distance + EPSILON
+    } else {
+        price >= reference + min_distance - EPSILON
+    };
+    (!valid).then_some(TradeViolation::TooClose {
+        field: "price",
+        price,
+        reference,
+        min_distance,
+    })
+}
+
+/// Filling types in order of preference when picking one for a symbol.
+const FILLINGS: [OrderTypeFilling; 3] = [
+    OrderTypeFilling::FOK,
+    OrderTypeFilling::IOC,
+    OrderTypeFilling::RETURN,
+];
+
+/// Rounds `value` to `decimals` decimal places, dropping floating point noise.
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals.clamp(0, 15));
+    (value * scale).round() / scale
+}
+
+/// Decimal places of a step such as `0.01` or `0.25`.
+fn step_decimals(step: f64) -> i32 {
+    (0..=15)
+        .find(|decimals| is_multiple(step * 10f64.powi(*decimals), 1.0))
+        .unwrap_or(15)
+}
+
+impl SymbolInfo {
+    /// Rounds `price` to the nearest multiple of the tick size.
+    pub fn normalize_price(&self, price: f64) -> f64 {
+        let tick_size = tick_size(self);
+        if tick_size <= 0.0 {
+            return price;
+        }
+        let decimals = (self.digits as i32).max(step_decimals(tick_size));
+        round_to((price / tick_size).round() * tick_size, decimals)
+    }
+
+    /// Rounds `volume` down to the volume step and caps it at `volume_max`.
+    ///
+    /// A volume below `volume_min` is left below it rather than raised, which
+    /// [`validate`] reports.
+    pub fn normalize_volume(&self, volume: f64) -> f64 {
+        let step = self.volume_step;
+        let mut volume = if step > 0.0 {
+            let steps = (volume / step + EPSILON).floor();
+            round_to(steps * step, step_decimals(step))
+        } else {
+            volume
+        };
+        if self.volume_max > 0.0 {
+            volume = volume.min(self.volume_max);
+        }
+        volume
+    }
+
+    /// First of FOK, IOC and RETURN the symbol accepts for `action`, given its
+    /// filling and execution modes.
+    pub fn filling_for(&self, action: TradeActionRequest) -> Option<OrderTypeFilling> {
+        FILLINGS
+            .into_iter()
+            .find(|filling| filling_allowed(self, action, *filling))
+    }
+}
+
+impl TradeRequestBuilder {
+    /// Shortcut for [`validate`].
+    pub fn validate(&self, symbol: &SymbolInfo, account: &AccountInfo) -> Vec<TradeViolation> {
+        validate(self, symbol, account)
+    }
+
+    /// Rounds `price`, `stoplimit`, `sl` and `tp` to the symbol's tick size.
+    pub fn normalize_prices(mut self, symbol: &SymbolInfo) -> Self {
+        for price in [
+            &mut self.price,
+            &mut self.stoplimit,
+            &mut self.sl,
+            &mut self.tp,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if *price > 0.0 {
+                *price = symbol.normalize_price(*price);
+            }
+        }
+        self
+    }
+
+    /// Snaps the volume to the symbol's volume step, see [`SymbolInfo::normalize_volume`].
+    pub fn normalize_volume(mut self, symbol: &SymbolInfo) -> Self {
+        if let Some(volume) = self.volume {
+            self.volume = Some(symbol.normalize_volume(volume));
+        }
+        self
+    }
+
+    /// Replaces a missing or refused filling type of market and pending orders with
+    /// the first one the symbol accepts, see [`SymbolInfo::filling_for`].
+    pub fn normalize_filling(mut self, symbol: &SymbolInfo) -> Self {
+        let action = match self.action {
+            Some(action @ (TradeActionRequest::DEAL | TradeActionRequest::PENDING)) => action,
+            _ => return self,
+        };
+        let allowed = self
+            .type_filling
+            .is_some_and(|filling| filling_allowed(symbol, action, filling));
+        if !allowed {
+            if let Some(filling) = symbol.filling_for(action) {
+                self.type_filling = Some(filling);
+            }
+        }
+        self
+    }
+
+    /// Applies [`normalize_prices`](Self::normalize_prices),
+    /// [`normalize_volume`](Self::normalize_volume) and
+    /// [`normalize_filling`](Self::normalize_filling).
+    ///
+    /// ```rust,no_run
+    /// use fishing_line::prelude::*;
+    ///
+    /// # fn run(terminal: &dyn Terminal) -> MQLResult<()> {
+    /// let symbol = terminal.symbol_info("US500")?;
+    /// let request: TradeRequestBuilder = LimitOrder::buy("US500", 1.37, 5301.13).into();
+    /// terminal.order_send(request.normalize(&symbol))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize(self, symbol: &SymbolInfo) -> Self {
+        self.normalize_prices(symbol)
+            .normalize_volume(symbol)
+            .normalize_filling(symbol)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::connection::fixtures::eurusd;
+    use crate::prelude::*;
+
+    fn account() -> AccountInfo {
+        AccountInfo {
+            trade_allowed: true,
+            margin_mode: AccountMarginMode::RetailHedging,
+            ..Default::default()
+        }
+    }
+
+    fn violations(request: impl Into<TradeRequestBuilder>) -> Vec<TradeViolation> {
+        validate(&request.into(), &eurusd(), &account())
+    }
+
+    #[test]
+    fn test_valid_requests_pass() {
+        let buy = MarketOrder::buy("EURUSD", 0.1)
+            .sl(1.09900)
+            .tp(1.10100)
+            .type_filling(OrderTypeFilling::IOC);
+        assert_eq!(violations(buy), vec![]);
+        let limit = LimitOrder::sell("EURUSD", 0.1, 1.10100).sl(1.10200);
+        assert_eq!(violations(limit), vec![]);
+        assert_eq!(violations(CancelPending::new(3)), vec![]);
+    }
+
+    #[test]
+    fn test_volume_and_price_violations() {
+        let found = violations(
+            MarketOrder::buy("EURUSD", 0.015)
+                .sl(1.099995)
+                .type_filling(OrderTypeFilling::IOC),
+        );
+        assert!(found.contains(&TradeViolation::VolumeStep {
+            volume: 0.015,
+            step: 0.01
+        }));
+        assert!(found.iter().any(|violation| matches!(
+            violation,
+            TradeViolation::PriceNotNormalized { field: "sl", .. }
+        )));
+        assert!(found
+            .iter()
+            .any(|violation| matches!(violation, TradeViolation::TooClose { field: "sl", .. })));
+        assert_eq!(found.len(), 3, "Unexpected violations {:?}", found);
+
+        let found =
+            violations(MarketOrder::sell("EURUSD", 200.0).type_filling(OrderTypeFilling::IOC));
+        assert_eq!(found[0].retcode(), ReturnCode::InvalidVolume);
+    }
+
+    #[test]
+    fn test_instant_execution_requires_price() {
+        let symbol = SymbolInfo {
+            trade_exemode: SymbolTradeExecution::Instant,
+            ..eurusd()
+        };
+        let buy = MarketOrder::buy("EURUSD", 0.1);
+        assert_eq!(
+            validate(&buy.clone().into(), &symbol, &account()),
+            vec![TradeViolation::MissingField("price")]
+        );
+        assert_eq!(
+            validate(&buy.clone().price(1.10020).into(), &symbol, &account()),
+            vec![]
+        );
+        let market = SymbolInfo {
+            trade_exemode: SymbolTradeExecution::Market,
+            ..eurusd()
+        };
+        let buy = buy.type_filling(OrderTypeFilling::IOC);
+        assert_eq!(validate(&buy.into(), &market, &account()), vec![]);
+    }
+
+    #[test]
+    fn test_mode_violations() {
+        let found = violations(MarketOrder::buy("EURUSD", 0.1));
+        assert_eq!(
+            found,
+            vec![TradeViolation::FillingNotAllowed(OrderTypeFilling::FOK)]
+        );
+
+        let shape = TradeRequestBuilder::new()
+            .action(TradeActionRequest::PENDING)
+            .symbol("EURUSD".to_string())
+            .volume(0.1)
+            .price(1.09000)
+            .r#type(OrderType::BUY);
+        assert!(
+            violations(shape).contains(&TradeViolation::UnexpectedOrderType {
+                action: TradeActionRequest::PENDING,
+                r#type: Some(OrderType::BUY),
+            })
+        );
+
+        let symbol = SymbolInfo {
+            trade_mode: SymbolTradeMode::SymbolTradeModeLongonly,
+            expiration_mode: SymbolExpirationMode::Gtc,
+            order_mode: SymbolOrderMode::Unknown(
+                SymbolOrderMode::Market.value() | SymbolOrderMode::Limit.value(),
+            ),
+            ..eurusd()
+        };
+        let request: TradeRequestBuilder = StopOrder::sell("EURUSD", 0.1, 1.09000)
+            .sl(1.09500)
+            .expiration(chrono::Utc::now())
+            .into();
+        let found = validate(&request, &symbol, &account());
+        assert!(found.contains(&TradeViolation::SymbolTradeMode(
+            SymbolTradeMode::SymbolTradeModeLongonly
+        )));
+        assert!(found.contains(&TradeViolation::OrderTypeNotAllowed(OrderType::SellStop)));
+        assert!(found.contains(&TradeViolation::StopNotAllowed("sl")));
+        assert!(found.contains(&TradeViolation::ExpirationNotAllowed(
+            OrderTypeTime::SPECIFIED
+        )));
+
+        let netting = AccountInfo {
+            margin_mode: AccountMarginMode::RetailNetting,
+            ..account()
+        };
+        let request: TradeRequestBuilder = CloseBy::new(1, 2).into();
+        assert_eq!(
+            validate(&request, &eurusd(), &netting),
+            vec![TradeViolation::HedgingRequired]
+        );
+    }
+
+    #[test]
+    fn test_normalize_request() {
+        let future = SymbolInfo {
+            name: "ES".to_string(),
+            digits: 2,
+            point: 0.01,
+            trade_tick_size: 0.25,
+            volume_min: 1.0,
+            volume_max: 50.0,
+            volume_step: 1.0,
+            trade_exemode: SymbolTradeExecution::Exchange,
+            filling_mode: SymbolFillingMode::Unknown(0),
+            ..Default::default()
+        };
+        let request = TradeRequestBuilder::from(
+            LimitOrder::buy("ES", 2.7, 5301.13)
+                .sl(5290.9)
+                .type_filling(OrderTypeFilling::FOK),
+        )
+        .normalize(&future);
+        assert_eq!(request.price, Some(5301.25));
+        assert_eq!(request.sl, Some(5291.0));
+        assert_eq!(request.tp, None);
+        assert_eq!(request.volume, Some(2.0));
+        assert_eq!(request.type_filling, Some(OrderTypeFilling::RETURN));
+
+        let request = TradeRequestBuilder::from(MarketOrder::buy("EURUSD", 0.037).sl(1.0990049))
+            .normalize(&eurusd());
+        assert_eq!(request.sl, Some(1.0990));
+        assert_eq!(request.volume, Some(0.03));
+        assert_eq!(request.type_filling, Some(OrderTypeFilling::IOC));
+        assert_eq!(validate(&request, &eurusd(), &account()), vec![]);
+    }
+
+    #[test]
+    fn test_modifications_respect_freeze_level() {
+        let symbol = SymbolInfo {
+            trade_freeze_level: 30,
+            ..eurusd()
+        };
+        let request: TradeRequestBuilder = ModifyStops::new("EURUSD", 5).sl(1.09980).into();
+        let found = validate(&request, &symbol, &account());
+        assert!(matches!(
+            &found[..],
+            [TradeViolation::TooClose { field: "sl", min_distance, .. }]
+                if (*min_distance - 0.0003).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_type() {
+        let request: TradeRequestBuilder = ModifyStops::new("EURUSD", 5).sl(1.09900).into();
+        assert_eq!(validate(&request, &eurusd(), &account()), vec![]);
+        assert_eq!(
+            validate_with_type(&request, OrderType::BUY, &eurusd(), &account()),
+            vec![]
+        );
+        let found = validate_with_type(&request, OrderType::SELL, &eurusd(), &account());
+        assert!(matches!(
+            &found[..],
+            [TradeViolation::TooClose { field: "sl", .. }]
+        ));
+    }
+}